@@ -17,10 +17,10 @@ async fn main() {
         let futures = (0..TOTAL)
             .map(|_| {
                 tokio::spawn(async {
-                    let config = SudokuConfig { cells: CELLS };
+                    let config = SudokuConfig::new(CELLS);
                     let filled = Sudoku::new_filled(config);
                     let sudoku = LatinSquares.generate_from(filled.clone(), config);
-                    // let result = Sudoku::new_unique(SudokuConfig { cells: CELLS }).to_str_line();
+                    // let result = Sudoku::new_unique(SudokuConfig::new(CELLS)).to_str_line();
                     PROGRESS.fetch_add(1, Ordering::Relaxed);
                     println!("progress: {PROGRESS:?}/{TOTAL}");
                     (filled, sudoku)
@@ -0,0 +1,13 @@
+use soku::prelude::*;
+
+fn main() {
+    let mut sudoku = Sudoku::new_unique(SudokuConfig::new(30));
+    println!("{sudoku}");
+
+    while let Some(step) = sudoku.next_step() {
+        println!("{step}");
+        sudoku.apply_step(&step);
+    }
+
+    println!("{sudoku}");
+}
@@ -1,9 +1,7 @@
 use soku::{measure, prelude::*};
 
 fn main() -> SudokuResult<()> {
-    let sudoku = measure!("Generation", {
-        Sudoku::new_unique(SudokuConfig { cells: 23 })
-    });
+    let sudoku = measure!("Generation", { Sudoku::new_unique(SudokuConfig::new(23)) });
     println!("{sudoku}");
     Ok(())
 }
@@ -0,0 +1,464 @@
+use std::{path::PathBuf, str::FromStr};
+
+use soku::prelude::{Difficulty, SudokuConfig, MAX_SQUARE_SIZE, MIN_SQUARE_SIZE};
+
+use crate::CliError;
+
+pub const USAGE: &str = "\
+Usage: soku <command> [options] [files...]
+
+Puzzles are read from the given files, or from stdin when there are none or a file is `-`.
+
+Commands:
+  solve       Solve each puzzle
+  generate    Generate new puzzles
+  rate        Rate the difficulty of each puzzle
+  validate    Check that each puzzle has exactly one solution
+  convert     Rewrite each puzzle in another format
+  help        Print this message
+
+Options:
+  -i, --from <format>        Input format: line (default), grid or pretty
+  -f, --format <format>      Output format: line (default), grid or pretty
+  -s, --solver <solver>      solve: brute-force (default), dlx or logical
+  -n, --count <count>        generate: number of puzzles, 1 by default
+  -c, --cells <cells>        generate: number of givens, about a third of the grid by default
+  -d, --difficulty <range>   generate: a difficulty such as `hard`, or a range such as `easy..hard`
+  -b, --box-size <size>      generate: box height and width such as `2x3`, 3x3 by default
+  -g, --generator <name>     generate: latin-squares (default), jigsaw or killer
+      --diagonals            generate: both main diagonals must also contain every digit
+      --seed <seed>          generate: seed for reproducible puzzles
+
+Formats:
+  line     One puzzle per line, with `0` or `.` for empty cells. Cages and regions are dropped.
+  grid     One puzzle per block of lines, with box borders. Killer cages follow the grid.
+  pretty   Like grid, with an outer border. Jigsaw regions are drawn, but not read back.
+";
+
+pub enum Command {
+    Solve {
+        input: Input,
+        format: Format,
+        solver: SolverKind,
+    },
+    Generate {
+        config: SudokuConfig,
+        generator: GeneratorKind,
+        count: usize,
+        format: Format,
+    },
+    Rate {
+        input: Input,
+    },
+    Validate {
+        input: Input,
+    },
+    Convert {
+        input: Input,
+        format: Format,
+    },
+    Help,
+}
+
+pub struct Input {
+    pub paths: Vec<PathBuf>,
+    pub format: Format,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Line,
+    Grid,
+    Pretty,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolverKind {
+    BruteForce,
+    Dlx,
+    Logical,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeneratorKind {
+    LatinSquares,
+    Jigsaw,
+    Killer,
+}
+
+// Every option a command may take, before they're checked against the command
+#[derive(Default)]
+struct Options {
+    paths: Vec<PathBuf>,
+    from: Option<Format>,
+    format: Option<Format>,
+    solver: Option<SolverKind>,
+    count: Option<usize>,
+    cells: Option<usize>,
+    difficulty: Option<(Difficulty, Difficulty)>,
+    box_size: Option<(usize, usize)>,
+    generator: Option<GeneratorKind>,
+    diagonals: bool,
+    seed: Option<u64>,
+}
+
+impl Command {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, CliError> {
+        let mut args = args.into_iter();
+
+        let Some(command) = args.next() else {
+            return Ok(Self::Help);
+        };
+
+        let mut options = Options::default();
+        // Options given to a command that doesn't use them are rejected rather than ignored
+        let mut given = vec![];
+
+        while let Some(arg) = args.next() {
+            if arg == "-" || !arg.starts_with('-') {
+                options.paths.push(PathBuf::from(arg));
+                continue;
+            }
+
+            let (name, inline_value) = match arg.split_once('=') {
+                Some((name, value)) => (name.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| CliError::MissingValue {
+                        option: name.clone(),
+                    })
+            };
+
+            let option = match name.as_str() {
+                "-h" | "--help" => return Ok(Self::Help),
+                "-i" | "--from" => {
+                    options.from = Some(parse_value(&name, &value()?)?);
+                    "from"
+                }
+                "-f" | "--format" => {
+                    options.format = Some(parse_value(&name, &value()?)?);
+                    "format"
+                }
+                "-s" | "--solver" => {
+                    options.solver = Some(parse_value(&name, &value()?)?);
+                    "solver"
+                }
+                "-n" | "--count" => {
+                    options.count = Some(parse_value(&name, &value()?)?);
+                    "count"
+                }
+                "-c" | "--cells" => {
+                    options.cells = Some(parse_value(&name, &value()?)?);
+                    "cells"
+                }
+                "-d" | "--difficulty" => {
+                    options.difficulty = Some(parse_difficulty(&name, &value()?)?);
+                    "difficulty"
+                }
+                "-b" | "--box-size" => {
+                    options.box_size = Some(parse_box_size(&name, &value()?)?);
+                    "box-size"
+                }
+                "-g" | "--generator" => {
+                    options.generator = Some(parse_value(&name, &value()?)?);
+                    "generator"
+                }
+                "--diagonals" => {
+                    options.diagonals = true;
+                    "diagonals"
+                }
+                "--seed" => {
+                    options.seed = Some(parse_value(&name, &value()?)?);
+                    "seed"
+                }
+                _ => return Err(CliError::UnknownOption { option: name }),
+            };
+
+            given.push((option, name));
+        }
+
+        let allowed: &[&str] = match command.as_str() {
+            "solve" => &["from", "format", "solver"],
+            "generate" => &[
+                "format",
+                "count",
+                "cells",
+                "difficulty",
+                "box-size",
+                "generator",
+                "diagonals",
+                "seed",
+            ],
+            "rate" | "validate" => &["from"],
+            "convert" => &["from", "format"],
+            "help" | "-h" | "--help" => return Ok(Self::Help),
+            _ => return Err(CliError::UnknownCommand { command }),
+        };
+
+        if let Some((_, option)) = given.into_iter().find(|(id, _)| !allowed.contains(id)) {
+            return Err(CliError::UnexpectedOption { command, option });
+        }
+
+        if command == "generate" && !options.paths.is_empty() {
+            return Err(CliError::UnexpectedInput { command });
+        }
+
+        Ok(options.into_command(&command))
+    }
+}
+
+impl Options {
+    fn into_command(self, command: &str) -> Command {
+        let format = self.format.unwrap_or(Format::Line);
+
+        let input = Input {
+            paths: self.paths,
+            format: self.from.unwrap_or(Format::Line),
+        };
+
+        match command {
+            "solve" => Command::Solve {
+                input,
+                format,
+                solver: self.solver.unwrap_or(SolverKind::BruteForce),
+            },
+            "generate" => {
+                let (box_height, box_width) = self.box_size.unwrap_or((3, 3));
+                let grid_size = (box_height * box_width).pow(2);
+
+                let mut config =
+                    SudokuConfig::new(self.cells.unwrap_or(grid_size * 3 / 8).min(grid_size))
+                        .with_box_size(box_height, box_width);
+                config.difficulty = self.difficulty;
+                config.diagonals = self.diagonals;
+                config.seed = self.seed;
+
+                Command::Generate {
+                    config,
+                    generator: self.generator.unwrap_or(GeneratorKind::LatinSquares),
+                    count: self.count.unwrap_or(1),
+                    format,
+                }
+            }
+            "rate" => Command::Rate { input },
+            "validate" => Command::Validate { input },
+            _ => Command::Convert { input, format },
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "line" => Ok(Self::Line),
+            "grid" => Ok(Self::Grid),
+            "pretty" => Ok(Self::Pretty),
+            _ => Err(()),
+        }
+    }
+}
+
+impl FromStr for SolverKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "brute-force" => Ok(Self::BruteForce),
+            "dlx" => Ok(Self::Dlx),
+            "logical" => Ok(Self::Logical),
+            _ => Err(()),
+        }
+    }
+}
+
+impl FromStr for GeneratorKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "latin-squares" => Ok(Self::LatinSquares),
+            "jigsaw" => Ok(Self::Jigsaw),
+            "killer" => Ok(Self::Killer),
+            _ => Err(()),
+        }
+    }
+}
+
+fn parse_value<T: FromStr>(option: &str, value: &str) -> Result<T, CliError> {
+    value.parse().map_err(|_| CliError::InvalidValue {
+        option: option.to_string(),
+        value: value.to_string(),
+    })
+}
+
+// Either a single difficulty, or an inclusive range of them such as `easy..hard`
+fn parse_difficulty(option: &str, value: &str) -> Result<(Difficulty, Difficulty), CliError> {
+    let difficulty = |name: &str| {
+        Difficulty::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.to_string().eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| CliError::InvalidValue {
+                option: option.to_string(),
+                value: value.to_string(),
+            })
+    };
+
+    match value.split_once("..") {
+        Some((min, max)) => Ok((difficulty(min)?, difficulty(max.trim_start_matches('='))?)),
+        None => difficulty(value).map(|difficulty| (difficulty, difficulty)),
+    }
+}
+
+fn parse_box_size(option: &str, value: &str) -> Result<(usize, usize), CliError> {
+    value
+        .split_once(['x', 'X'])
+        .and_then(|(height, width)| Some((height.parse().ok()?, width.parse().ok()?)))
+        .filter(|&(height, width)| {
+            let sizes = MIN_SQUARE_SIZE..=MAX_SQUARE_SIZE;
+            sizes.contains(&height) && sizes.contains(&width)
+        })
+        .ok_or_else(|| CliError::InvalidValue {
+            option: option.to_string(),
+            value: value.to_string(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Command, CliError> {
+        Command::parse(args.split_whitespace().map(String::from))
+    }
+
+    fn parse_err(args: &str) -> CliError {
+        parse(args).err().expect("arguments should be rejected")
+    }
+
+    #[test]
+    fn parses_inline_and_separate_values() {
+        let Ok(Command::Generate {
+            config,
+            generator,
+            count,
+            format,
+        }) = parse("generate --cells=20 -b 2x3 -g=killer --seed 7 -n=3 --diagonals")
+        else {
+            panic!("expected a generate command");
+        };
+
+        let expected = SudokuConfig::new(20)
+            .with_box_size(2, 3)
+            .with_diagonals()
+            .with_seed(7);
+
+        assert_eq!(config, expected);
+        assert_eq!(generator, GeneratorKind::Killer);
+        assert_eq!(count, 3);
+        assert_eq!(format, Format::Line);
+    }
+
+    #[test]
+    fn defaults_cells_to_the_grid_size() {
+        let Ok(Command::Generate { config, .. }) = parse("generate -b 2X3") else {
+            panic!("expected a generate command");
+        };
+
+        assert_eq!(config.box_size, (2, 3));
+        assert_eq!(config.cells, 13);
+
+        let Ok(Command::Generate { config, .. }) = parse("generate") else {
+            panic!("expected a generate command");
+        };
+
+        assert_eq!(config.cells, 30);
+    }
+
+    #[test]
+    fn parses_difficulty_ranges() {
+        for (value, expected) in [
+            ("hard", (Difficulty::Hard, Difficulty::Hard)),
+            ("easy..hard", (Difficulty::Easy, Difficulty::Hard)),
+            ("Medium..=Expert", (Difficulty::Medium, Difficulty::Expert)),
+        ] {
+            let Ok(Command::Generate { config, .. }) = parse(&format!("generate -d {value}"))
+            else {
+                panic!("expected a generate command");
+            };
+
+            assert_eq!(config.difficulty, Some(expected));
+        }
+
+        assert!(matches!(
+            parse_err("generate --difficulty=easy..impossible"),
+            CliError::InvalidValue { option, value }
+                if option == "--difficulty" && value == "easy..impossible"
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_box_sizes() {
+        for value in ["2x6", "1x3", "2x", "23"] {
+            assert!(matches!(
+                parse_err(&format!("generate -b {value}")),
+                CliError::InvalidValue { option, .. } if option == "-b"
+            ));
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_and_unexpected_options() {
+        assert!(matches!(
+            parse_err("solve --bogus=1"),
+            CliError::UnknownOption { option } if option == "--bogus"
+        ));
+        assert!(matches!(
+            parse_err("solve --seed 1"),
+            CliError::UnexpectedOption { command, option }
+                if command == "solve" && option == "--seed"
+        ));
+        assert!(matches!(
+            parse_err("rate -f grid"),
+            CliError::UnexpectedOption { option, .. } if option == "-f"
+        ));
+        assert!(matches!(
+            parse_err("generate --cells"),
+            CliError::MissingValue { option } if option == "--cells"
+        ));
+        assert!(matches!(
+            parse_err("generate puzzles.txt"),
+            CliError::UnexpectedInput { .. }
+        ));
+        assert!(matches!(
+            parse_err("unsolve"),
+            CliError::UnknownCommand { command } if command == "unsolve"
+        ));
+    }
+
+    #[test]
+    fn reads_paths_and_stdin() {
+        let Ok(Command::Solve {
+            input,
+            format,
+            solver,
+        }) = parse("solve a.txt - -s dlx --from=grid")
+        else {
+            panic!("expected a solve command");
+        };
+
+        assert_eq!(input.paths, [PathBuf::from("a.txt"), PathBuf::from("-")]);
+        assert_eq!(input.format, Format::Grid);
+        assert_eq!(format, Format::Line);
+        assert_eq!(solver, SolverKind::Dlx);
+
+        assert!(matches!(parse("validate --help"), Ok(Command::Help)));
+        assert!(matches!(parse(""), Ok(Command::Help)));
+    }
+}
@@ -0,0 +1,184 @@
+#![forbid(unsafe_code)]
+#![deny(
+    clippy::correctness,
+    clippy::suspicious,
+    clippy::complexity,
+    clippy::perf,
+    clippy::style,
+    clippy::nursery,
+    clippy::cargo
+)]
+#![allow(clippy::module_name_repetitions, clippy::multiple_crate_versions)]
+
+mod args;
+mod puzzles;
+
+use std::{
+    io::{self, BufWriter, Write},
+    process::ExitCode,
+};
+
+use thiserror::Error;
+
+use soku::prelude::{
+    BruteForceSolver, CageError, DlxSolver, Generate, GenerateError, Jigsaw, Killer, LatinSquares,
+    LogicalSolver, ParseError, Rating, Sudoku,
+};
+
+use args::{Command, GeneratorKind, Input, SolverKind, USAGE};
+
+// TODO: docs
+
+// Exits with 1 when some puzzle couldn't be processed, and with 2 when the command itself failed
+fn main() -> ExitCode {
+    match Command::parse(std::env::args().skip(1)).and_then(run) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        // The reader went away, such as when piping into `head`
+        Err(CliError::Io(err)) if err.kind() == io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("soku: {err}");
+
+            if matches!(
+                err,
+                CliError::UnknownCommand { .. }
+                    | CliError::UnknownOption { .. }
+                    | CliError::UnexpectedOption { .. }
+            ) {
+                eprintln!("Run `soku help` for usage");
+            }
+
+            ExitCode::from(2)
+        }
+    }
+}
+
+// Returns whether every puzzle was processed successfully
+fn run(command: Command) -> Result<bool, CliError> {
+    let mut out = BufWriter::new(io::stdout().lock());
+
+    let success = match command {
+        Command::Solve {
+            input,
+            format,
+            solver,
+        } => for_each_puzzle(&input, |sudoku| {
+            let mut solution = sudoku.clone();
+
+            let solved = match solver {
+                SolverKind::BruteForce => solution.solve_with(BruteForceSolver),
+                SolverKind::Dlx => solution.solve_with(DlxSolver),
+                SolverKind::Logical => solution.solve_with(LogicalSolver::new()),
+            };
+
+            if solved {
+                puzzles::write(&mut out, &solution, format)?;
+                Ok(None)
+            } else {
+                Ok(Some("no solution found"))
+            }
+        })?,
+        Command::Generate {
+            config,
+            generator,
+            count,
+            format,
+        } => {
+            let mut rng = config.rng();
+
+            for _ in 0..count {
+                let sudoku = match generator {
+                    GeneratorKind::LatinSquares => {
+                        LatinSquares.try_generate_with_rng(config, &mut rng)
+                    }
+                    GeneratorKind::Jigsaw => Jigsaw.try_generate_with_rng(config, &mut rng),
+                    GeneratorKind::Killer => Killer.try_generate_with_rng(config, &mut rng),
+                }?;
+
+                puzzles::write(&mut out, &sudoku, format)?;
+            }
+
+            true
+        }
+        Command::Rate { input } => for_each_puzzle(&input, |sudoku| {
+            Rating::new(sudoku).map_or(Ok(Some("invalid puzzle")), |rating| {
+                writeln!(out, "{rating}")?;
+                Ok(None)
+            })
+        })?,
+        Command::Validate { input } => for_each_puzzle(&input, |sudoku| {
+            let status = if sudoku.is_valid() {
+                match sudoku.count_solutions(2) {
+                    0 => "no solution",
+                    1 => "unique",
+                    _ => "multiple solutions",
+                }
+            } else {
+                "invalid"
+            };
+
+            writeln!(out, "{status}")?;
+            Ok((status != "unique").then_some(status))
+        })?,
+        Command::Convert { input, format } => for_each_puzzle(&input, |sudoku| {
+            puzzles::write(&mut out, sudoku, format)?;
+            Ok(None)
+        })?,
+        Command::Help => {
+            write!(out, "{USAGE}")?;
+            true
+        }
+    };
+
+    out.flush()?;
+    Ok(success)
+}
+
+// Runs `f` on every puzzle of the input, which returns a problem with the puzzle, if any. Problems
+// and unreadable puzzles are reported on stderr without stopping the command.
+fn for_each_puzzle(
+    input: &Input,
+    mut f: impl FnMut(&Sudoku) -> Result<Option<&'static str>, CliError>,
+) -> Result<bool, CliError> {
+    let mut success = true;
+
+    for (location, sudoku) in puzzles::read(input)? {
+        let problem = match sudoku {
+            Ok(sudoku) => f(&sudoku)?.map(str::to_string),
+            Err(err) => Some(err.to_string()),
+        };
+
+        if let Some(problem) = problem {
+            eprintln!("{location}: {problem}");
+            success = false;
+        }
+    }
+
+    Ok(success)
+}
+
+#[derive(Error, Debug)]
+pub enum CliError {
+    #[error("unknown command `{command}`")]
+    UnknownCommand { command: String },
+    #[error("unknown option `{option}`")]
+    UnknownOption { option: String },
+    #[error("`{command}` doesn't take the option `{option}`")]
+    UnexpectedOption { command: String, option: String },
+    #[error("`{command}` doesn't read any puzzles")]
+    UnexpectedInput { command: String },
+    #[error("option `{option}` expects a value")]
+    MissingValue { option: String },
+    #[error("invalid value `{value}` for option `{option}`")]
+    InvalidValue { option: String, value: String },
+    #[error("can't read {path}: {source}")]
+    Read { path: String, source: io::Error },
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+    #[error(transparent)]
+    Cage(#[from] CageError),
+    #[error(transparent)]
+    Generate(#[from] GenerateError),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
@@ -0,0 +1,181 @@
+use std::{
+    fmt::Display,
+    fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use soku::prelude::Sudoku;
+
+use crate::{
+    args::{Format, Input},
+    CliError,
+};
+
+pub type Puzzle = (Location, Result<Sudoku, CliError>);
+
+// Where a puzzle was read from, for error messages
+pub struct Location {
+    source: String,
+    line: usize,
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.source, self.line)
+    }
+}
+
+// Reads every puzzle of the input, in order. Puzzles that can't be parsed are reported as errors
+// along with their location, so that the remaining puzzles can still be processed.
+pub fn read(input: &Input) -> Result<Vec<Puzzle>, CliError> {
+    let stdin = vec![PathBuf::from("-")];
+    let paths = if input.paths.is_empty() {
+        &stdin
+    } else {
+        &input.paths
+    };
+
+    let mut puzzles = vec![];
+
+    for path in paths {
+        let (source, contents) = if path == Path::new("-") {
+            let mut contents = String::new();
+            io::stdin().read_to_string(&mut contents)?;
+            ("<stdin>".to_string(), contents)
+        } else {
+            let contents = fs::read_to_string(path).map_err(|source| CliError::Read {
+                path: path.display().to_string(),
+                source,
+            })?;
+            (path.display().to_string(), contents)
+        };
+
+        for (line, text) in split(&contents, input.format) {
+            let location = Location {
+                source: source.clone(),
+                line,
+            };
+
+            puzzles.push((location, parse(&text)));
+        }
+    }
+
+    Ok(puzzles)
+}
+
+// Lines format puzzles are a line each, while the other formats are separated by blank lines.
+// Lines starting with `#` are comments. Yields each puzzle along with its first line number.
+fn split(contents: &str, format: Format) -> Vec<(usize, String)> {
+    let mut puzzles = vec![];
+    let mut current: Option<(usize, String)> = None;
+
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim_end();
+
+        if line.trim_start().starts_with('#') {
+            continue;
+        }
+
+        if line.trim().is_empty() {
+            puzzles.extend(current.take());
+            continue;
+        }
+
+        if format == Format::Line {
+            puzzles.push((index + 1, line.trim().to_string()));
+            continue;
+        }
+
+        let (_, text) = current.get_or_insert_with(|| (index + 1, String::new()));
+        text.push_str(line);
+        text.push('\n');
+    }
+
+    puzzles.extend(current);
+    puzzles
+}
+
+// Killer cages such as `15: r1c1 r1c2` follow the grid, and are told apart from grid lines by
+// starting with their sum
+fn parse(text: &str) -> Result<Sudoku, CliError> {
+    let is_cage = |line: &&str| {
+        let line = line.trim_start();
+        line.starts_with(|char: char| char.is_ascii_digit()) && line.contains(':')
+    };
+
+    let grid = text
+        .lines()
+        .filter(|line| !is_cage(line))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let cages = text.lines().filter(is_cage).collect::<Vec<_>>().join("\n");
+
+    let mut sudoku = grid.parse::<Sudoku>()?;
+    sudoku.add_cages_from_str(&cages)?;
+
+    Ok(sudoku)
+}
+
+pub fn write(out: &mut impl Write, sudoku: &Sudoku, format: Format) -> io::Result<()> {
+    match format {
+        Format::Line => return writeln!(out, "{}", sudoku.to_str_line()),
+        Format::Grid => write!(out, "{}", sudoku.to_str_grid())?,
+        Format::Pretty => writeln!(out, "{sudoku}")?,
+    }
+
+    write!(out, "{}", sudoku.to_str_cages())?;
+    writeln!(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soku::prelude::{Generate, Killer, SudokuConfig};
+
+    const PUZZLE: &str =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+
+    #[test]
+    fn splits_lines_and_skips_comments() {
+        let contents = format!("# classic\n{PUZZLE}\n\n  {PUZZLE}  \n");
+
+        assert_eq!(
+            split(&contents, Format::Line),
+            [(2, PUZZLE.to_string()), (4, PUZZLE.to_string())]
+        );
+    }
+
+    #[test]
+    fn parses_grids_with_trailing_cages() {
+        let killer = Killer.generate(SudokuConfig::new(0).with_seed(0));
+        let sudoku = PUZZLE.parse::<Sudoku>().unwrap();
+
+        let mut out = vec![];
+        write(&mut out, &killer, Format::Grid).unwrap();
+        out.extend_from_slice(b"# a comment between puzzles\n\n");
+        write(&mut out, &sudoku, Format::Grid).unwrap();
+        let contents = String::from_utf8(out).unwrap();
+
+        let puzzles = split(&contents, Format::Grid);
+        let grid_lines = sudoku.to_str_grid().lines().count();
+
+        assert_eq!(puzzles.len(), 2);
+        assert_eq!(puzzles[0].0, 1);
+        assert_eq!(puzzles[1].0, grid_lines + killer.cages().len() + 4);
+
+        let parsed = parse(&puzzles[0].1).unwrap();
+        assert_eq!(parsed.to_str_line(), killer.to_str_line());
+        assert_eq!(parsed.to_str_cages(), killer.to_str_cages());
+
+        let parsed = parse(&puzzles[1].1).unwrap();
+        assert_eq!(parsed.to_str_line(), PUZZLE);
+        assert!(!parsed.is_killer());
+    }
+
+    #[test]
+    fn reports_invalid_cages() {
+        let text = format!("{PUZZLE}\n10: r1c1 r1c1\n");
+        assert!(matches!(parse(&text), Err(CliError::Cage(_))));
+    }
+}
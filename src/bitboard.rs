@@ -0,0 +1,237 @@
+use crate::prelude::{Candidates, Digit, Layout, Sudoku, SudokuIndex};
+
+// TODO: docs
+
+// A compact grid for solvers: the digit of each cell, along with the digits placed in each house.
+// The house masks are kept up to date as digits are placed and cleared, so the candidates of a
+// cell are a few bitwise operations away instead of a scan of its peers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bitboard {
+    layout: Layout,
+    // `0` for empty cells
+    digits: Vec<u8>,
+    // Indexed by house id, as numbered by the layout
+    houses: Vec<Candidates>,
+    filled: usize,
+}
+
+impl Bitboard {
+    #[must_use]
+    pub fn new(layout: Layout) -> Self {
+        Self {
+            digits: vec![0; layout.grid_size()],
+            houses: vec![Candidates::empty(); layout.house_count()],
+            filled: 0,
+            layout,
+        }
+    }
+
+    // Repeated digits are kept as they are, so check the sudoku's validity beforehand
+    #[must_use]
+    pub fn from_sudoku(sudoku: &Sudoku) -> Self {
+        let mut board = Self::new(sudoku.layout().clone());
+
+        for (index, cell) in sudoku.cells().enumerate() {
+            if let Some(digit) = cell.digit {
+                board.place(index, digit);
+            }
+        }
+
+        board
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn layout(&self) -> &Layout {
+        &self.layout
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn digit<I: SudokuIndex>(&self, i: I) -> Option<Digit> {
+        let digit = self.digits[self.layout.index(i)];
+        (digit != 0).then_some(Digit::new_unchecked(digit))
+    }
+
+    // The digits placed in the house, by its id
+    #[inline]
+    #[must_use]
+    pub fn house_digits(&self, id: usize) -> Candidates {
+        self.houses[id]
+    }
+
+    // The digits that no house of the cell has yet, or none when the cell is filled
+    #[must_use]
+    pub fn candidates<I: SudokuIndex>(&self, i: I) -> Candidates {
+        let index = self.layout.index(i);
+
+        if self.digits[index] != 0 {
+            return Candidates::empty();
+        }
+
+        let placed = self
+            .layout
+            .house_ids_of_cell(index)
+            .iter()
+            .fold(Candidates::empty(), |acc, &id| acc.union(self.houses[id]));
+
+        self.layout.candidates().difference(placed)
+    }
+
+    // Replaces the digit already in the cell, if any
+    pub fn place<I: SudokuIndex>(&mut self, i: I, digit: Digit) {
+        let index = self.layout.index(i);
+        self.clear(index);
+
+        self.digits[index] = digit.0;
+        self.filled += 1;
+
+        for &id in self.layout.house_ids_of_cell(index) {
+            self.houses[id].add(digit);
+        }
+    }
+
+    pub fn clear<I: SudokuIndex>(&mut self, i: I) {
+        let index = self.layout.index(i);
+
+        let Some(digit) = self.digit(index) else {
+            return;
+        };
+
+        self.digits[index] = 0;
+        self.filled -= 1;
+
+        for &id in self.layout.house_ids_of_cell(index) {
+            self.houses[id].remove(digit);
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn count_filled_cells(&self) -> usize {
+        self.filled
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn is_filled(&self) -> bool {
+        self.filled == self.digits.len()
+    }
+
+    // Copies every digit over to a sudoku of the same layout, leaving the rest of its cells as
+    // they are
+    pub fn write_to(&self, sudoku: &mut Sudoku) {
+        debug_assert_eq!(sudoku.layout(), &self.layout);
+
+        for (cell, &digit) in sudoku.cells_mut().zip(&self.digits) {
+            cell.digit = (digit != 0).then_some(Digit::new_unchecked(digit));
+        }
+    }
+}
+
+impl From<&Sudoku> for Bitboard {
+    fn from(sudoku: &Sudoku) -> Self {
+        Self::from_sudoku(sudoku)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{generate_filled_with_layout, Jigsaw, SudokuConfig};
+
+    const PUZZLE: &str =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+
+    fn assert_candidates_match(board: &Bitboard, sudoku: &Sudoku) {
+        let candidates = (0..sudoku.size())
+            .map(|index| board.candidates(index))
+            .collect::<Vec<_>>();
+
+        assert_eq!(candidates, sudoku.all_candidates());
+        assert_eq!(board.count_filled_cells(), sudoku.count_filled_cells());
+    }
+
+    // Fills the empty cells with the solution one at a time and clears them again, checking the
+    // candidates after every change
+    fn assert_updates_match(mut sudoku: Sudoku) {
+        let solution = sudoku.solutions().next().unwrap();
+        let mut board = Bitboard::from_sudoku(&sudoku);
+        assert_candidates_match(&board, &sudoku);
+
+        let empty = (0..sudoku.size())
+            .filter(|&index| board.digit(index).is_none())
+            .collect::<Vec<_>>();
+
+        for &index in &empty {
+            let digit = solution.cell(index).unwrap().digit.unwrap();
+
+            board.place(index, digit);
+            sudoku.set_cell(index, digit);
+            assert_candidates_match(&board, &sudoku);
+        }
+
+        assert!(board.is_filled());
+
+        for &index in empty.iter().rev() {
+            board.clear(index);
+            sudoku.clear_cell(index);
+            assert_candidates_match(&board, &sudoku);
+        }
+    }
+
+    #[test]
+    fn candidates_follow_placed_and_cleared_digits() {
+        assert_updates_match(PUZZLE.parse().unwrap());
+
+        let mut rng = SudokuConfig::new(0).with_seed(0).rng();
+
+        for layout in [
+            Jigsaw::random_layout(9, &mut rng),
+            Layout::new_rectangular(2, 3).with_diagonals(),
+        ] {
+            let mut sudoku = generate_filled_with_layout(layout, &mut rng).unwrap();
+
+            for index in (0..sudoku.size()).step_by(3) {
+                sudoku.clear_cell(index);
+            }
+
+            assert_updates_match(sudoku);
+        }
+    }
+
+    #[test]
+    fn replaces_digits_in_place() {
+        let sudoku = PUZZLE.parse::<Sudoku>().unwrap();
+        let mut board = Bitboard::from_sudoku(&sudoku);
+        let filled = board.count_filled_cells();
+
+        // Row 1, column 3 and the first box, by house id
+        let houses = [0, 11, 18];
+
+        board.place(2, Digit(1));
+        board.place(2, Digit(4));
+
+        assert_eq!(board.digit(2), Some(Digit(4)));
+        assert_eq!(board.count_filled_cells(), filled + 1);
+        assert!(houses
+            .iter()
+            .all(|&id| board.house_digits(id).contains(Digit(4))
+                && !board.house_digits(id).contains(Digit(1))));
+        assert_eq!(
+            board.candidates(3),
+            Candidates::from(Digit(2)).union(Digit(6).into())
+        );
+
+        board.clear(2);
+        board.clear(2);
+
+        assert_eq!(board.digit(2), None);
+        assert_eq!(board.count_filled_cells(), filled);
+        assert_eq!(board, Bitboard::from_sudoku(&sudoku));
+
+        let mut written = Sudoku::new_empty_with_layout(Layout::classic());
+        board.write_to(&mut written);
+        assert_eq!(written.to_str_line(), sudoku.to_str_line());
+    }
+}
@@ -0,0 +1,367 @@
+use std::{fmt::Display, str::FromStr};
+
+use thiserror::Error;
+
+use crate::prelude::{Candidates, Constraint, ConstraintError, Coord, Digit, Layout, Sudoku};
+
+// TODO: docs
+
+// A killer cage: its digits add up to `sum`, and no digit repeats within it
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Cage {
+    sum: u32,
+    cells: Vec<Coord>,
+}
+
+impl Cage {
+    #[must_use]
+    pub fn new(sum: u32, cells: impl IntoIterator<Item = Coord>) -> Self {
+        let mut cells = cells.into_iter().collect::<Vec<_>>();
+        cells.sort_unstable();
+        cells.dedup();
+
+        Self { sum, cells }
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn sum(&self) -> u32 {
+        self.sum
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn cells(&self) -> &[Coord] {
+        &self.cells
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    #[must_use]
+    pub fn contains(&self, coord: Coord) -> bool {
+        self.cells.binary_search(&coord).is_ok()
+    }
+
+    // Every set of `count` distinct digits out of `digits` that adds up to `sum`
+    #[must_use]
+    pub fn combinations(digits: Candidates, count: usize, sum: u32) -> Vec<Candidates> {
+        fn search(
+            digits: &[u32],
+            count: usize,
+            sum: u32,
+            combination: Candidates,
+            combinations: &mut Vec<Candidates>,
+        ) {
+            if count == 0 {
+                if sum == 0 {
+                    combinations.push(combination);
+                }
+                return;
+            }
+
+            // `digits` are ascending, so the smallest and largest sums are at either end
+            if digits.len() < count
+                || digits[..count].iter().sum::<u32>() > sum
+                || digits[digits.len() - count..].iter().sum::<u32>() < sum
+            {
+                return;
+            }
+
+            for (i, &digit) in digits.iter().enumerate() {
+                if digit > sum {
+                    break;
+                }
+
+                #[allow(clippy::cast_possible_truncation)]
+                let digit_candidates = Candidates::from(Digit::new_unchecked(digit as u8));
+
+                search(
+                    &digits[i + 1..],
+                    count - 1,
+                    sum - digit,
+                    combination.union(digit_candidates),
+                    combinations,
+                );
+            }
+        }
+
+        let digits = digits
+            .digits()
+            .map(|digit| u32::from(digit.0))
+            .collect::<Vec<_>>();
+
+        let mut combinations = vec![];
+        search(&digits, count, sum, Candidates::empty(), &mut combinations);
+        combinations
+    }
+
+    // The digits that can still go in the empty cells of the cage, given the digits already placed
+    // in it
+    #[must_use]
+    pub fn candidates(&self, sudoku: &Sudoku) -> Candidates {
+        let (empty, combinations) = self.remaining_combinations(sudoku);
+
+        if empty.is_empty() {
+            return Candidates::empty();
+        }
+
+        combinations
+            .into_iter()
+            .fold(Candidates::empty(), Candidates::union)
+    }
+
+    // The indices of the empty cells, and the combinations of the digits that aren't placed yet
+    // which add up to the rest of the sum
+    fn remaining_combinations(&self, sudoku: &Sudoku) -> (Vec<usize>, Vec<Candidates>) {
+        let layout = sudoku.layout();
+
+        let mut empty = vec![];
+        let mut placed = Candidates::empty();
+        let mut placed_sum = 0;
+
+        for &coord in &self.cells {
+            let index = layout.index(coord);
+
+            match sudoku.cell(index).and_then(|cell| cell.digit) {
+                Some(digit) => {
+                    placed.add(digit);
+                    placed_sum += u32::from(digit.0);
+                }
+                None => empty.push(index),
+            }
+        }
+
+        let combinations = self
+            .sum
+            .checked_sub(placed_sum)
+            .map_or_else(Vec::new, |sum| {
+                Self::combinations(layout.candidates().difference(placed), empty.len(), sum)
+            });
+
+        (empty, combinations)
+    }
+}
+
+impl Constraint for Cage {
+    // Digits don't repeat, and don't add up to more than the sum, or to anything else once the cage
+    // is full
+    fn is_valid(&self, sudoku: &Sudoku) -> bool {
+        let digits = self
+            .cells
+            .iter()
+            .filter_map(|&coord| sudoku.cell(coord).and_then(|cell| cell.digit))
+            .collect::<Vec<_>>();
+
+        let sum = digits.iter().map(|digit| u32::from(digit.0)).sum::<u32>();
+
+        let unique = digits
+            .iter()
+            .fold(Candidates::empty(), |acc, &digit| acc.union(digit.into()))
+            .count()
+            == digits.len();
+
+        unique
+            && if digits.len() == self.len() {
+                sum == self.sum
+            } else {
+                sum < self.sum
+            }
+    }
+
+    fn cell_candidates(&self, sudoku: &Sudoku, index: usize) -> Candidates {
+        if self.contains(sudoku.layout().coord(index)) {
+            self.candidates(sudoku)
+        } else {
+            sudoku.layout().candidates()
+        }
+    }
+
+    // Narrows the candidates of the empty cells in the cage down to the combinations that every
+    // cell can take part in, and that the cells can cover between them
+    fn restrict_candidates(&self, sudoku: &Sudoku, all_candidates: &mut [Candidates]) {
+        let (empty, combinations) = self.remaining_combinations(sudoku);
+
+        let possible = combinations
+            .into_iter()
+            .filter(|&combination| {
+                let covered = empty.iter().fold(Candidates::empty(), |acc, &index| {
+                    let candidates = all_candidates[index].intersection(combination);
+                    acc.union(candidates)
+                });
+
+                covered == combination
+                    && empty
+                        .iter()
+                        .all(|&index| !all_candidates[index].intersection(combination).is_empty())
+            })
+            .fold(Candidates::empty(), Candidates::union);
+
+        for index in empty {
+            all_candidates[index] = all_candidates[index].intersection(possible);
+        }
+    }
+
+    fn check_cells(&self, layout: &Layout) -> Result<(), ConstraintError> {
+        self.cells
+            .iter()
+            .find(|&&coord| !layout.contains(coord))
+            .map_or(Ok(()), |&coord| Err(ConstraintError::InvalidCell { coord }))
+    }
+}
+
+// Cages are written as their sum followed by their cells, such as `15: r1c1 r1c2 r2c1`
+impl Display for Cage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:", self.sum)?;
+
+        for Coord(row, col) in &self.cells {
+            write!(f, " r{}c{}", row + 1, col + 1)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Cage {
+    type Err = CageError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || CageError::InvalidFormat {
+            cage: s.trim().to_string(),
+        };
+
+        let (sum, cells) = s.split_once(':').ok_or_else(invalid)?;
+        let sum = sum.trim().parse().map_err(|_| invalid())?;
+
+        let cells = cells
+            .split_whitespace()
+            .map(|cell| {
+                let cell = cell.to_ascii_lowercase();
+                let (row, col) = cell.strip_prefix('r')?.split_once('c')?;
+                let (row, col) = (row.parse::<usize>().ok()?, col.parse::<usize>().ok()?);
+
+                Some(Coord(row.checked_sub(1)?, col.checked_sub(1)?))
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(invalid)?;
+
+        Ok(Self::new(sum, cells))
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum CageError {
+    #[error("cages must have at least one cell")]
+    Empty,
+    #[error("cages can have at most {max} cells, found {found}")]
+    TooLarge { max: usize, found: usize },
+    #[error("r{}c{} is outside the grid", .coord.0 + 1, .coord.1 + 1)]
+    InvalidCell { coord: Coord },
+    #[error("r{}c{} is already in another cage", .coord.0 + 1, .coord.1 + 1)]
+    OverlappingCell { coord: Coord },
+    #[error("no {cells} different digits add up to {sum}")]
+    ImpossibleSum { sum: u32, cells: usize },
+    #[error("invalid cage `{cage}`, expected a sum followed by cells, such as `15: r1c1 r1c2`")]
+    InvalidFormat { cage: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digits(digits: &[u8]) -> Candidates {
+        digits.iter().fold(Candidates::empty(), |acc, &digit| {
+            acc.union(Digit(digit).into())
+        })
+    }
+
+    #[test]
+    fn writes_and_parses_cages() {
+        let cage = Cage::new(15, [Coord(1, 0), Coord(0, 0), Coord(0, 1)]);
+
+        assert_eq!(cage.to_string(), "15: r1c1 r1c2 r2c1");
+        assert_eq!(cage.to_string().parse::<Cage>().unwrap(), cage);
+        assert_eq!(" 15 :R1C1  r1c2 R2c1 ".parse::<Cage>().unwrap(), cage);
+    }
+
+    #[test]
+    fn rejects_invalid_cages() {
+        for s in [
+            "15 r1c1", "x: r1c1", "15: r0c1", "15: r1", "15: c1r1", "-3: r1c1",
+        ] {
+            assert!(
+                matches!(s.parse::<Cage>(), Err(CageError::InvalidFormat { .. })),
+                "{s}"
+            );
+        }
+    }
+
+    #[test]
+    fn finds_combinations() {
+        let all = digits(&[1, 2, 3, 4, 5, 6, 7, 8, 9]);
+
+        assert_eq!(Cage::combinations(all, 2, 3), [digits(&[1, 2])]);
+        assert_eq!(Cage::combinations(all, 3, 24), [digits(&[7, 8, 9])]);
+        assert_eq!(
+            Cage::combinations(all, 2, 10),
+            [
+                digits(&[1, 9]),
+                digits(&[2, 8]),
+                digits(&[3, 7]),
+                digits(&[4, 6])
+            ]
+        );
+        assert!(Cage::combinations(all, 2, 18).is_empty());
+        assert!(Cage::combinations(digits(&[1, 2, 4]), 2, 4).is_empty());
+    }
+
+    #[test]
+    fn checks_sums_and_repeats() {
+        let cage = Cage::new(10, [Coord(0, 0), Coord(0, 1), Coord(1, 0)]);
+        let mut sudoku = Sudoku::new_empty();
+
+        sudoku.set_cell(Coord(0, 0), Digit(2));
+        assert!(cage.is_valid(&sudoku));
+        assert_eq!(cage.candidates(&sudoku), digits(&[1, 3, 5, 7]));
+
+        sudoku.set_cell(Coord(1, 0), Digit(2));
+        assert!(!cage.is_valid(&sudoku));
+
+        sudoku.set_cell(Coord(1, 0), Digit(8));
+        assert!(!cage.is_valid(&sudoku));
+
+        sudoku.set_cell(Coord(1, 0), Digit(7));
+        assert_eq!(cage.candidates(&sudoku), digits(&[1]));
+
+        sudoku.set_cell(Coord(0, 1), Digit(1));
+        assert!(cage.is_valid(&sudoku));
+        assert!(cage.candidates(&sudoku).is_empty());
+    }
+
+    #[test]
+    fn restricts_candidates_to_covered_combinations() {
+        let sudoku = Sudoku::new_empty();
+        let cage = Cage::new(10, [Coord(0, 0), Coord(0, 1)]);
+
+        // The second cell can't take an 8, which rules out 2 + 8
+        let mut all_candidates = sudoku.all_candidates();
+        all_candidates[0] = digits(&[1, 2, 3]);
+        all_candidates[1] = digits(&[7, 9]);
+
+        cage.restrict_candidates(&sudoku, &mut all_candidates);
+
+        assert_eq!(all_candidates[0], digits(&[1, 3]));
+        assert_eq!(all_candidates[1], digits(&[7, 9]));
+    }
+}
@@ -0,0 +1,629 @@
+mod arrow;
+mod chess;
+mod kropki;
+mod non_consecutive;
+mod odd_even;
+mod sandwich;
+mod thermometer;
+mod xv;
+
+use std::{any::Any, fmt::Debug, sync::OnceLock};
+
+use thiserror::Error;
+
+use crate::prelude::{Candidates, Coord, Digit, House, Layout, Sudoku, MAX_HOUSE_SIZE};
+pub use arrow::Arrow;
+pub use chess::{AntiKing, AntiKnight};
+pub use kropki::Kropki;
+pub use non_consecutive::NonConsecutive;
+pub use odd_even::OddEven;
+pub use sandwich::Sandwich;
+pub use thermometer::Thermometer;
+pub use xv::Xv;
+
+// TODO: docs
+
+// A rule on top of the houses of a layout, which validators and solvers consult along with them.
+// Constraints only need to prune soundly: solvers check every full grid with `is_valid` before
+// accepting it.
+pub trait Constraint: Debug + Send + Sync + ConstraintEq {
+    // Whether the digits placed so far keep the constraint. Empty cells never break it.
+    fn is_valid(&self, sudoku: &Sudoku) -> bool;
+
+    // The digits that `index` can take, given the digits placed so far
+    fn cell_candidates(&self, sudoku: &Sudoku, index: usize) -> Candidates;
+
+    // Removes the candidates of empty cells that can't keep the constraint, and every candidate
+    // once it's broken. Constraints that can prune further by looking at the candidates of other
+    // cells should override this.
+    fn restrict_candidates(&self, sudoku: &Sudoku, all_candidates: &mut [Candidates]) {
+        if !self.is_valid(sudoku) {
+            all_candidates.fill(Candidates::empty());
+            return;
+        }
+
+        for (index, candidates) in all_candidates.iter_mut().enumerate() {
+            if !candidates.is_empty() {
+                *candidates = candidates.intersection(self.cell_candidates(sudoku, index));
+            }
+        }
+    }
+
+    // Fails when the constraint refers to cells outside of the layout's grid. Constraints over the
+    // whole grid, such as anti-knight, never do.
+    fn check_cells(&self, _layout: &Layout) -> Result<(), ConstraintError> {
+        Ok(())
+    }
+}
+
+#[derive(Error, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ConstraintError {
+    #[error("r{}c{} is outside the grid", .coord.0 + 1, .coord.1 + 1)]
+    InvalidCell { coord: Coord },
+    #[error("{house} is outside the grid")]
+    InvalidHouse { house: House },
+}
+
+// Lets constraints be compared as trait objects, so that sudokus with different constraints aren't
+// equal. Every constraint that implements `PartialEq` gets it.
+pub trait ConstraintEq {
+    fn as_any(&self) -> &dyn Any;
+
+    fn eq_constraint(&self, other: &dyn Constraint) -> bool;
+}
+
+impl<T: Constraint + PartialEq + 'static> ConstraintEq for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn eq_constraint(&self, other: &dyn Constraint) -> bool {
+        other
+            .as_any()
+            .downcast_ref::<Self>()
+            .is_some_and(|other| self == other)
+    }
+}
+
+impl PartialEq for dyn Constraint {
+    fn eq(&self, other: &Self) -> bool {
+        self.eq_constraint(other)
+    }
+}
+
+// Constraints made of pairs of cells alone, which all check and prune them the same way
+trait PairConstraint {
+    fn pairs(&self, house_size: usize) -> impl Iterator<Item = &Pairs>;
+}
+
+impl<T: PairConstraint + Debug + PartialEq + Send + Sync + 'static> Constraint for T {
+    fn is_valid(&self, sudoku: &Sudoku) -> bool {
+        self.pairs(sudoku.house_size())
+            .all(|pairs| pairs.is_valid(sudoku))
+    }
+
+    fn cell_candidates(&self, sudoku: &Sudoku, index: usize) -> Candidates {
+        self.pairs(sudoku.house_size())
+            .fold(sudoku.layout().candidates(), |candidates, pairs| {
+                candidates.intersection(pairs.cell_candidates(sudoku, index))
+            })
+    }
+
+    fn restrict_candidates(&self, sudoku: &Sudoku, all_candidates: &mut [Candidates]) {
+        for pairs in self.pairs(sudoku.house_size()) {
+            pairs.restrict_candidates(sudoku, all_candidates);
+        }
+    }
+
+    fn check_cells(&self, layout: &Layout) -> Result<(), ConstraintError> {
+        self.pairs(layout.house_size()).try_for_each(|pairs| {
+            check_coords(
+                layout,
+                pairs
+                    .pairs
+                    .iter()
+                    .flat_map(|&pair| <[Coord; 2]>::from(pair)),
+            )
+        })
+    }
+}
+
+// Most constraints relate pairs of cells, whose digits must be allowed together. What each digit
+// allows is worked out once, for every digit up to the largest house size.
+#[derive(Default, Clone)]
+struct Pairs {
+    pairs: Vec<(Coord, Coord)>,
+    // The digits of each side of a pair that every digit of the other side allows, indexed by that
+    // digit
+    first_allowed: Vec<Candidates>,
+    second_allowed: Vec<Candidates>,
+}
+
+// The pairs of the constraints that only depend on the house size, built once for each
+type PairsCache = [OnceLock<Pairs>; MAX_HOUSE_SIZE + 1];
+
+impl Pairs {
+    fn new(
+        pairs: impl IntoIterator<Item = (Coord, Coord)>,
+        allowed: impl Fn(u8, u8) -> bool,
+    ) -> Self {
+        #[allow(clippy::cast_possible_truncation)]
+        let digits = 1..=MAX_HOUSE_SIZE as u8;
+
+        let allowed_with = |is_second: bool| {
+            std::iter::once(Candidates::empty())
+                .chain(digits.clone().map(|other| {
+                    digits
+                        .clone()
+                        .filter(|&digit| {
+                            if is_second {
+                                allowed(other, digit)
+                            } else {
+                                allowed(digit, other)
+                            }
+                        })
+                        .fold(Candidates::empty(), |acc, digit| {
+                            acc.union(Digit::new_unchecked(digit).into())
+                        })
+                }))
+                .collect::<Vec<_>>()
+        };
+
+        Self {
+            pairs: pairs.into_iter().collect(),
+            first_allowed: allowed_with(false),
+            second_allowed: allowed_with(true),
+        }
+    }
+
+    const fn new_cache() -> PairsCache {
+        [const { OnceLock::new() }; MAX_HOUSE_SIZE + 1]
+    }
+
+    fn cached(
+        cache: &'static PairsCache,
+        house_size: usize,
+        pairs: impl FnOnce(usize) -> Vec<(Coord, Coord)>,
+        allowed: impl Fn(u8, u8) -> bool,
+    ) -> &'static Self {
+        cache[house_size].get_or_init(|| Self::new(pairs(house_size), allowed))
+    }
+
+    fn pairs(&self) -> &[(Coord, Coord)] {
+        &self.pairs
+    }
+
+    fn is_valid(&self, sudoku: &Sudoku) -> bool {
+        let layout = sudoku.layout();
+
+        self.pairs.iter().all(|&(a, b)| {
+            match (
+                digit(sudoku, layout.index(a)),
+                digit(sudoku, layout.index(b)),
+            ) {
+                (Some(a), Some(b)) => self.first_allowed[usize::from(b)].contains(Digit(a)),
+                _ => true,
+            }
+        })
+    }
+
+    fn cell_candidates(&self, sudoku: &Sudoku, index: usize) -> Candidates {
+        let layout = sudoku.layout();
+        let all = layout.candidates();
+        let coord = layout.coord(index);
+
+        self.pairs.iter().fold(all, |candidates, &(a, b)| {
+            if a == coord {
+                let other = domain(sudoku, None, layout.index(b));
+                candidates.intersection(self.supported(all, other, false))
+            } else if b == coord {
+                let other = domain(sudoku, None, layout.index(a));
+                candidates.intersection(self.supported(all, other, true))
+            } else {
+                candidates
+            }
+        })
+    }
+
+    // Revises both cells of every pair, forwards and then backwards, so that chains of pairs such
+    // as thermometers propagate in both directions
+    fn restrict_candidates(&self, sudoku: &Sudoku, all_candidates: &mut [Candidates]) {
+        let layout = sudoku.layout();
+        let all = layout.candidates();
+
+        for &(a, b) in self.pairs.iter().chain(self.pairs.iter().rev()) {
+            let (a, b) = (layout.index(a), layout.index(b));
+
+            for (cell, other, is_second) in [(a, b, false), (b, a, true)] {
+                if digit(sudoku, cell).is_none() {
+                    let other = domain(sudoku, Some(all_candidates), other);
+                    let supported = self.supported(all, other, is_second);
+                    all_candidates[cell] = all_candidates[cell].intersection(supported);
+                }
+            }
+        }
+    }
+
+    // The digits that some digit of `other` allows, where `is_second` tells which side of the
+    // pair they're on
+    fn supported(&self, digits: Candidates, other: Candidates, is_second: bool) -> Candidates {
+        let allowed = if is_second {
+            &self.second_allowed
+        } else {
+            &self.first_allowed
+        };
+
+        other
+            .digits()
+            .fold(Candidates::empty(), |acc, other| {
+                acc.union(allowed[usize::from(other.0)])
+            })
+            .intersection(digits)
+    }
+}
+
+// The tables follow from the pairs and the constraint holding them, so they're left out
+impl Debug for Pairs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(&self.pairs).finish()
+    }
+}
+
+impl PartialEq for Pairs {
+    fn eq(&self, other: &Self) -> bool {
+        self.pairs == other.pairs
+    }
+}
+
+impl Eq for Pairs {}
+
+// Fails on the first coordinate outside of the layout's grid
+fn check_coords(
+    layout: &Layout,
+    coords: impl IntoIterator<Item = Coord>,
+) -> Result<(), ConstraintError> {
+    coords
+        .into_iter()
+        .find(|&coord| !layout.contains(coord))
+        .map_or(Ok(()), |coord| Err(ConstraintError::InvalidCell { coord }))
+}
+
+fn digit(sudoku: &Sudoku, index: usize) -> Option<u8> {
+    sudoku
+        .cell(index)
+        .and_then(|cell| cell.digit)
+        .map(|digit| digit.0)
+}
+
+// The digit placed in `index`, or else its candidates when known, or else every digit
+fn domain(sudoku: &Sudoku, all_candidates: Option<&[Candidates]>, index: usize) -> Candidates {
+    sudoku.cell(index).and_then(|cell| cell.digit).map_or_else(
+        || {
+            all_candidates.map_or_else(
+                || sudoku.layout().candidates(),
+                |all_candidates| all_candidates[index],
+            )
+        },
+        Candidates::from,
+    )
+}
+
+// The cells that are `offsets` away from each cell, each pair appearing once
+fn offset_pairs(house_size: usize, offsets: &[(isize, isize)]) -> Vec<(Coord, Coord)> {
+    let mut pairs = vec![];
+
+    for row in 0..house_size {
+        for col in 0..house_size {
+            for &(row_offset, col_offset) in offsets {
+                let other = row
+                    .checked_add_signed(row_offset)
+                    .zip(col.checked_add_signed(col_offset))
+                    .filter(|&(row, col)| row < house_size && col < house_size);
+
+                if let Some((other_row, other_col)) = other {
+                    if (row, col) < (other_row, other_col) {
+                        pairs.push((Coord(row, col), Coord(other_row, other_col)));
+                    }
+                }
+            }
+        }
+    }
+
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::prelude::{BruteForceSolver, Cage, DlxSolver};
+
+    const PUZZLE: &str =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+
+    const SOLUTION: &str =
+        "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+    fn is_valid(constraint: &impl Constraint, digits: &[(Coord, u8)]) -> bool {
+        let mut sudoku = Sudoku::new_empty();
+
+        for &(coord, digit) in digits {
+            sudoku.set_cell(coord, Digit(digit));
+        }
+
+        constraint.is_valid(&sudoku)
+    }
+
+    fn candidates(digits: impl IntoIterator<Item = u8>) -> Candidates {
+        digits.into_iter().fold(Candidates::empty(), |acc, digit| {
+            acc.union(Digit(digit).into())
+        })
+    }
+
+    #[test]
+    fn checks_pairs() {
+        let (a, b) = (Coord(0, 0), Coord(0, 1));
+
+        assert!(!is_valid(&AntiKnight, &[(a, 1), (Coord(1, 2), 1)]));
+        assert!(is_valid(&AntiKnight, &[(a, 1), (Coord(1, 2), 2)]));
+        assert!(!is_valid(&AntiKing, &[(a, 1), (Coord(1, 1), 1)]));
+        assert!(!is_valid(&NonConsecutive, &[(a, 1), (b, 2)]));
+        assert!(is_valid(&NonConsecutive, &[(a, 1), (b, 3)]));
+
+        let kropki = Kropki::new([(a, b)], [(Coord(1, 0), Coord(1, 1))]);
+        assert!(is_valid(&kropki, &[(a, 4), (b, 5), (Coord(1, 0), 3)]));
+        assert!(!is_valid(&kropki, &[(a, 4), (b, 6)]));
+        assert!(is_valid(&kropki, &[(Coord(1, 0), 8), (Coord(1, 1), 4)]));
+        assert!(!is_valid(&kropki, &[(Coord(1, 0), 3), (Coord(1, 1), 4)]));
+
+        let xv = Xv::new([(a, b)], [(Coord(1, 0), Coord(1, 1))]);
+        assert!(is_valid(
+            &xv,
+            &[(a, 3), (b, 7), (Coord(1, 0), 1), (Coord(1, 1), 4)]
+        ));
+        assert!(!is_valid(&xv, &[(a, 3), (b, 6)]));
+        assert!(!is_valid(&xv, &[(Coord(1, 0), 2), (Coord(1, 1), 4)]));
+    }
+
+    #[test]
+    fn narrows_pair_candidates() {
+        let mut sudoku = Sudoku::new_empty();
+        sudoku.set_cell(Coord(0, 0), Digit(5));
+
+        // A knight's move away, next to it, and a king's move away
+        let index = sudoku.layout().index(Coord(1, 2));
+        assert_eq!(
+            AntiKnight.cell_candidates(&sudoku, index),
+            candidates([1, 2, 3, 4, 6, 7, 8, 9])
+        );
+
+        let index = sudoku.layout().index(Coord(0, 1));
+        assert_eq!(
+            NonConsecutive.cell_candidates(&sudoku, index),
+            candidates([1, 2, 3, 5, 7, 8, 9])
+        );
+
+        let kropki = Kropki::new([], [(Coord(1, 1), Coord(2, 2))]);
+        let mut all_candidates = sudoku.all_candidates();
+        all_candidates[sudoku.layout().index(Coord(1, 1))] = candidates([3, 7]);
+
+        kropki.restrict_candidates(&sudoku, &mut all_candidates);
+
+        assert_eq!(
+            all_candidates[sudoku.layout().index(Coord(2, 2))],
+            candidates([6])
+        );
+    }
+
+    #[test]
+    fn checks_other_constraints() {
+        let thermometer = Thermometer::new([Coord(0, 0), Coord(0, 1), Coord(0, 2)]);
+        assert!(is_valid(
+            &thermometer,
+            &[(Coord(0, 0), 1), (Coord(0, 2), 9)]
+        ));
+        assert!(!is_valid(
+            &thermometer,
+            &[(Coord(0, 0), 3), (Coord(0, 1), 2)]
+        ));
+        assert_eq!(
+            thermometer.cell_candidates(&Sudoku::new_empty(), 0),
+            candidates(1..=7)
+        );
+
+        let odd_even = OddEven::new([Coord(0, 0)], [Coord(0, 1)]);
+        assert!(is_valid(&odd_even, &[(Coord(0, 0), 3), (Coord(0, 1), 4)]));
+        assert!(!is_valid(&odd_even, &[(Coord(0, 0), 2)]));
+        assert!(!is_valid(&odd_even, &[(Coord(0, 1), 5)]));
+
+        let arrow = Arrow::new(Coord(0, 0), [Coord(0, 1), Coord(0, 2)]);
+        assert!(is_valid(&arrow, &[(Coord(0, 0), 9), (Coord(0, 1), 4)]));
+        assert!(is_valid(
+            &arrow,
+            &[(Coord(0, 0), 9), (Coord(0, 1), 4), (Coord(0, 2), 5)]
+        ));
+        assert!(!is_valid(
+            &arrow,
+            &[(Coord(0, 0), 8), (Coord(0, 1), 4), (Coord(0, 2), 5)]
+        ));
+        assert!(!is_valid(&arrow, &[(Coord(0, 0), 3), (Coord(0, 1), 4)]));
+
+        let solution = SOLUTION.parse::<Sudoku>().unwrap();
+        assert!(Sandwich::col(0, 19).is_valid(&solution));
+        assert!(Sandwich::row(0, 0).is_valid(&solution));
+        assert!(Sandwich::row(3, 13).is_valid(&solution));
+        assert!(!Sandwich::row(3, 12).is_valid(&solution));
+        assert_eq!(Sandwich::row(3, 12).house(), House::Row(3));
+    }
+
+    #[test]
+    fn rejects_cells_outside_the_grid() {
+        let mut sudoku = Sudoku::new_empty();
+        let invalid_cell = |row, col| {
+            Err(ConstraintError::InvalidCell {
+                coord: Coord(row, col),
+            })
+        };
+
+        assert_eq!(
+            sudoku.add_constraint(Kropki::new([(Coord(0, 8), Coord(0, 9))], [])),
+            invalid_cell(0, 9)
+        );
+        assert_eq!(
+            sudoku.add_constraint(Xv::new([], [(Coord(9, 0), Coord(8, 0))])),
+            invalid_cell(9, 0)
+        );
+        assert_eq!(
+            sudoku.add_constraint(Thermometer::new([Coord(7, 8), Coord(8, 8), Coord(9, 8)])),
+            invalid_cell(9, 8)
+        );
+        assert_eq!(
+            sudoku.add_constraint(Arrow::new(Coord(9, 9), [Coord(8, 8)])),
+            invalid_cell(9, 9)
+        );
+        assert_eq!(
+            sudoku.add_constraint(OddEven::new([Coord(0, 0)], [Coord(0, 10)])),
+            invalid_cell(0, 10)
+        );
+        assert_eq!(
+            sudoku.add_constraint(Cage::new(3, [Coord(8, 8), Coord(8, 9)])),
+            invalid_cell(8, 9)
+        );
+        assert_eq!(
+            sudoku.add_constraint(Sandwich::row(9, 10)),
+            Err(ConstraintError::InvalidHouse {
+                house: House::Row(9)
+            })
+        );
+        assert_eq!(
+            sudoku.add_constraint(Sandwich::col(12, 0)),
+            Err(ConstraintError::InvalidHouse {
+                house: House::Col(12)
+            })
+        );
+
+        assert!(!sudoku.has_constraints());
+        assert_eq!(sudoku.count_solutions(1), 1);
+
+        // What fits a 9x9 grid doesn't necessarily fit a smaller one
+        let mut sudoku = Sudoku::new_empty_with_layout(Layout::new(2));
+
+        assert!(sudoku.add_constraint(AntiKnight).is_ok());
+        assert!(sudoku.add_constraint(Sandwich::row(3, 5)).is_ok());
+        let error = sudoku
+            .add_constraint(Thermometer::new([Coord(3, 3), Coord(4, 4)]))
+            .unwrap_err();
+        assert_eq!(error, ConstraintError::InvalidCell { coord: Coord(4, 4) });
+        assert_eq!(error.to_string(), "r5c5 is outside the grid");
+
+        let error = sudoku.add_constraint(Sandwich::col(4, 5)).unwrap_err();
+        assert_eq!(error.to_string(), "column 5 is outside the grid");
+    }
+
+    #[test]
+    fn compares_constraints() {
+        let knight: Arc<dyn Constraint> = Arc::new(AntiKnight);
+        let other_knight: Arc<dyn Constraint> = Arc::new(AntiKnight);
+        let king: Arc<dyn Constraint> = Arc::new(AntiKing);
+
+        assert!(*knight == *other_knight);
+        assert!(*knight != *king);
+
+        let kropki = Kropki::new([(Coord(0, 0), Coord(0, 1))], []);
+        assert_eq!(kropki, Kropki::new([(Coord(0, 0), Coord(0, 1))], []));
+        assert_ne!(kropki, Kropki::new([], [(Coord(0, 0), Coord(0, 1))]));
+
+        let sudoku = Sudoku::new_empty().with_constraint(AntiKnight).unwrap();
+        assert_eq!(
+            sudoku,
+            Sudoku::new_empty().with_constraint(AntiKnight).unwrap()
+        );
+        assert_ne!(
+            sudoku,
+            Sudoku::new_empty().with_constraint(AntiKing).unwrap()
+        );
+        assert_ne!(sudoku, Sudoku::new_empty());
+    }
+
+    // Constraints that the solution keeps, on the puzzle with its first rows cleared, which leave
+    // it with two solutions
+    #[test]
+    fn solvers_agree_with_constraints() {
+        let solution = SOLUTION.parse::<Sudoku>().unwrap();
+        let digit = |row, col| solution.cell(Coord(row, col)).unwrap().digit.unwrap().0;
+
+        let neighbors = (0..9)
+            .flat_map(|row| (0..8).map(move |col| (Coord(row, col), Coord(row, col + 1))))
+            .collect::<Vec<_>>();
+        let dots = |allowed: fn(u8, u8) -> bool| {
+            neighbors
+                .iter()
+                .copied()
+                .filter(move |&(a, b)| allowed(digit(a.0, a.1), digit(b.0, b.1)))
+        };
+
+        let mut sudoku = PUZZLE.parse::<Sudoku>().unwrap();
+
+        for row in 0..3 {
+            for col in 0..9 {
+                sudoku.clear_cell(Coord(row, col));
+            }
+        }
+
+        sudoku = sudoku
+            .with_constraint(Kropki::new([], dots(|a, b| a == b * 2 || b == a * 2)))
+            .unwrap()
+            .with_constraint(Xv::new([], dots(|a, b| a + b == 5)))
+            .unwrap()
+            .with_constraint(Thermometer::new((2..6).map(|col| Coord(0, col))))
+            .unwrap()
+            .with_constraint(Arrow::new(Coord(3, 2), [Coord(4, 2), Coord(5, 2)]))
+            .unwrap()
+            .with_constraint(OddEven::new(
+                (0..9)
+                    .filter(|&row| digit(row, 4) % 2 == 1)
+                    .map(|row| Coord(row, 4)),
+                (0..9)
+                    .filter(|&row| digit(row, 4) % 2 == 0)
+                    .map(|row| Coord(row, 4)),
+            ))
+            .unwrap()
+            .with_constraint(Sandwich::col(0, 19))
+            .unwrap()
+            .with_constraint(Sandwich::row(0, 0))
+            .unwrap();
+
+        let brute_force = BruteForceSolver.solutions(&sudoku).collect::<Vec<_>>();
+        let dlx = DlxSolver.solutions(&sudoku).collect::<Vec<_>>();
+
+        assert_eq!(brute_force.len(), 2);
+        assert_eq!(dlx.len(), 2);
+        assert!(brute_force.iter().all(|solution| dlx.contains(solution)));
+        assert!(brute_force.iter().all(Sudoku::is_valid));
+        assert!(brute_force
+            .iter()
+            .any(|found| found.to_str_line() == SOLUTION));
+    }
+
+    // The Miracle Sudoku, which only needs two givens along with its constraints
+    #[test]
+    fn solves_miracle_sudoku() {
+        let sudoku = Sudoku::new_empty()
+            .with_cell(Coord(4, 2), Digit(1))
+            .with_cell(Coord(5, 6), Digit(2))
+            .with_constraint(AntiKnight)
+            .unwrap()
+            .with_constraint(AntiKing)
+            .unwrap()
+            .with_constraint(NonConsecutive)
+            .unwrap();
+
+        let brute_force = BruteForceSolver.solutions(&sudoku).collect::<Vec<_>>();
+        let dlx = DlxSolver.solutions(&sudoku).collect::<Vec<_>>();
+
+        assert_eq!(brute_force.len(), 1);
+        assert_eq!(dlx, brute_force);
+        assert!(brute_force[0].is_filled() && brute_force[0].is_valid());
+    }
+}
@@ -0,0 +1,102 @@
+use crate::prelude::{Candidates, Coord, Digit, Layout, Sudoku};
+
+use super::{check_coords, Constraint, ConstraintError};
+
+// The digits along the arrow add up to the digit in its circle
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Arrow {
+    circle: Coord,
+    arrow: Vec<Coord>,
+}
+
+impl Arrow {
+    #[must_use]
+    pub fn new(circle: Coord, arrow: impl IntoIterator<Item = Coord>) -> Self {
+        Self {
+            circle,
+            arrow: arrow.into_iter().collect(),
+        }
+    }
+
+    #[must_use]
+    pub const fn circle(&self) -> Coord {
+        self.circle
+    }
+
+    #[must_use]
+    pub fn arrow(&self) -> &[Coord] {
+        &self.arrow
+    }
+
+    // The sum of the digits placed along the arrow, and the number of its empty cells, leaving out
+    // `skip`
+    fn arrow_sum(&self, sudoku: &Sudoku, skip: Option<Coord>) -> (usize, usize) {
+        self.arrow
+            .iter()
+            .filter(|&&coord| Some(coord) != skip)
+            .fold((0, 0), |(sum, empty), &coord| {
+                sudoku
+                    .cell(coord)
+                    .and_then(|cell| cell.digit)
+                    .map_or((sum, empty + 1), |digit| {
+                        (sum + usize::from(digit.0), empty)
+                    })
+            })
+    }
+}
+
+impl Constraint for Arrow {
+    fn is_valid(&self, sudoku: &Sudoku) -> bool {
+        let (sum, empty) = self.arrow_sum(sudoku, None);
+
+        match sudoku.cell(self.circle).and_then(|cell| cell.digit) {
+            Some(circle) if empty == 0 => sum == usize::from(circle.0),
+            Some(circle) => sum + empty <= usize::from(circle.0),
+            None => sum + empty <= sudoku.house_size(),
+        }
+    }
+
+    // Every empty cell along the arrow holds at least a 1
+    fn cell_candidates(&self, sudoku: &Sudoku, index: usize) -> Candidates {
+        let layout = sudoku.layout();
+        let coord = layout.coord(index);
+        let circle = sudoku.cell(self.circle).and_then(|cell| cell.digit);
+
+        let digits = |range: std::ops::RangeInclusive<usize>| {
+            layout
+                .digits()
+                .filter(|digit| range.contains(&usize::from(digit.0)))
+                .fold(Candidates::empty(), |acc, digit| acc.union(digit.into()))
+        };
+
+        if coord == self.circle {
+            let (sum, empty) = self.arrow_sum(sudoku, None);
+
+            return if empty == 0 {
+                digits(sum..=sum)
+            } else {
+                digits(sum + empty..=usize::MAX)
+            };
+        }
+
+        if !self.arrow.contains(&coord) {
+            return layout.candidates();
+        }
+
+        let (sum, empty) = self.arrow_sum(sudoku, Some(coord));
+        let max = circle.map_or_else(|| layout.house_size(), |Digit(circle)| usize::from(circle));
+
+        match (circle, max.checked_sub(sum + empty)) {
+            (_, None) => Candidates::empty(),
+            (Some(_), Some(rest)) if empty == 0 => digits(rest..=rest),
+            (_, Some(rest)) => digits(1..=rest),
+        }
+    }
+
+    fn check_cells(&self, layout: &Layout) -> Result<(), ConstraintError> {
+        check_coords(
+            layout,
+            std::iter::once(self.circle).chain(self.arrow.iter().copied()),
+        )
+    }
+}
@@ -0,0 +1,53 @@
+use super::{offset_pairs, PairConstraint, Pairs, PairsCache};
+
+const KNIGHT_OFFSETS: [(isize, isize); 8] = [
+    (-2, -1),
+    (-2, 1),
+    (-1, -2),
+    (-1, 2),
+    (1, -2),
+    (1, 2),
+    (2, -1),
+    (2, 1),
+];
+
+// Orthogonal neighbors always share a row or a column already
+const KING_OFFSETS: [(isize, isize); 4] = [(-1, -1), (-1, 1), (1, -1), (1, 1)];
+
+// Cells a knight's move apart can't hold the same digit
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct AntiKnight;
+
+impl PairConstraint for AntiKnight {
+    fn pairs(&self, house_size: usize) -> impl Iterator<Item = &Pairs> {
+        static PAIRS: PairsCache = Pairs::new_cache();
+
+        let pairs = Pairs::cached(
+            &PAIRS,
+            house_size,
+            |house_size| offset_pairs(house_size, &KNIGHT_OFFSETS),
+            |a, b| a != b,
+        );
+
+        std::iter::once(pairs)
+    }
+}
+
+// Cells a king's move apart can't hold the same digit
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct AntiKing;
+
+impl PairConstraint for AntiKing {
+    fn pairs(&self, house_size: usize) -> impl Iterator<Item = &Pairs> {
+        static PAIRS: PairsCache = Pairs::new_cache();
+
+        let pairs = Pairs::cached(
+            &PAIRS,
+            house_size,
+            |house_size| offset_pairs(house_size, &KING_OFFSETS),
+            |a, b| a != b,
+        );
+
+        std::iter::once(pairs)
+    }
+}
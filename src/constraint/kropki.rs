@@ -0,0 +1,40 @@
+use crate::prelude::Coord;
+
+use super::{PairConstraint, Pairs};
+
+// A white dot between two cells means their digits are consecutive, and a black dot means one is
+// double the other
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Kropki {
+    white: Pairs,
+    black: Pairs,
+}
+
+impl Kropki {
+    #[must_use]
+    pub fn new(
+        white: impl IntoIterator<Item = (Coord, Coord)>,
+        black: impl IntoIterator<Item = (Coord, Coord)>,
+    ) -> Self {
+        Self {
+            white: Pairs::new(white, |a, b| a.abs_diff(b) == 1),
+            black: Pairs::new(black, |a, b| a == b * 2 || b == a * 2),
+        }
+    }
+
+    #[must_use]
+    pub fn white(&self) -> &[(Coord, Coord)] {
+        self.white.pairs()
+    }
+
+    #[must_use]
+    pub fn black(&self) -> &[(Coord, Coord)] {
+        self.black.pairs()
+    }
+}
+
+impl PairConstraint for Kropki {
+    fn pairs(&self, _: usize) -> impl Iterator<Item = &Pairs> {
+        [&self.white, &self.black].into_iter()
+    }
+}
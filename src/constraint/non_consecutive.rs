@@ -0,0 +1,22 @@
+use super::{offset_pairs, PairConstraint, Pairs, PairsCache};
+
+const NEIGHBOR_OFFSETS: [(isize, isize); 2] = [(0, 1), (1, 0)];
+
+// Orthogonally adjacent cells can't hold consecutive digits
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct NonConsecutive;
+
+impl PairConstraint for NonConsecutive {
+    fn pairs(&self, house_size: usize) -> impl Iterator<Item = &Pairs> {
+        static PAIRS: PairsCache = Pairs::new_cache();
+
+        let pairs = Pairs::cached(
+            &PAIRS,
+            house_size,
+            |house_size| offset_pairs(house_size, &NEIGHBOR_OFFSETS),
+            |a, b| a.abs_diff(b) != 1,
+        );
+
+        std::iter::once(pairs)
+    }
+}
@@ -0,0 +1,70 @@
+use crate::prelude::{Candidates, Coord, Layout, Sudoku};
+
+use super::{check_coords, Constraint, ConstraintError};
+
+// Marked cells hold either odd or even digits
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct OddEven {
+    odd: Vec<Coord>,
+    even: Vec<Coord>,
+}
+
+impl OddEven {
+    #[must_use]
+    pub fn new(
+        odd: impl IntoIterator<Item = Coord>,
+        even: impl IntoIterator<Item = Coord>,
+    ) -> Self {
+        Self {
+            odd: odd.into_iter().collect(),
+            even: even.into_iter().collect(),
+        }
+    }
+
+    #[must_use]
+    pub fn odd(&self) -> &[Coord] {
+        &self.odd
+    }
+
+    #[must_use]
+    pub fn even(&self) -> &[Coord] {
+        &self.even
+    }
+
+    fn parity(&self, sudoku: &Sudoku, index: usize) -> Option<u8> {
+        let coord = sudoku.layout().coord(index);
+
+        if self.odd.contains(&coord) {
+            Some(1)
+        } else if self.even.contains(&coord) {
+            Some(0)
+        } else {
+            None
+        }
+    }
+}
+
+impl Constraint for OddEven {
+    fn is_valid(&self, sudoku: &Sudoku) -> bool {
+        sudoku.cells().enumerate().all(|(index, cell)| {
+            cell.digit
+                .zip(self.parity(sudoku, index))
+                .is_none_or(|(digit, parity)| digit.0 % 2 == parity)
+        })
+    }
+
+    fn cell_candidates(&self, sudoku: &Sudoku, index: usize) -> Candidates {
+        let candidates = sudoku.layout().candidates();
+
+        self.parity(sudoku, index).map_or(candidates, |parity| {
+            candidates
+                .digits()
+                .filter(|digit| digit.0 % 2 == parity)
+                .fold(Candidates::empty(), |acc, digit| acc.union(digit.into()))
+        })
+    }
+
+    fn check_cells(&self, layout: &Layout) -> Result<(), ConstraintError> {
+        check_coords(layout, self.odd.iter().chain(&self.even).copied())
+    }
+}
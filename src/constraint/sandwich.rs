@@ -0,0 +1,119 @@
+use crate::prelude::{Candidates, House, Layout, Sudoku};
+
+use super::{Constraint, ConstraintError};
+
+// The digits between the 1 and the highest digit of a row or column add up to `sum`
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Sandwich {
+    house: House,
+    sum: u32,
+}
+
+impl Sandwich {
+    #[must_use]
+    pub const fn row(row: usize, sum: u32) -> Self {
+        Self {
+            house: House::Row(row),
+            sum,
+        }
+    }
+
+    #[must_use]
+    pub const fn col(col: usize, sum: u32) -> Self {
+        Self {
+            house: House::Col(col),
+            sum,
+        }
+    }
+
+    #[must_use]
+    pub const fn house(&self) -> House {
+        self.house
+    }
+
+    #[must_use]
+    pub const fn sum(&self) -> u32 {
+        self.sum
+    }
+
+    // The cells between the crusts, once both are placed
+    fn filling<'a>(&self, sudoku: &'a Sudoku) -> Option<&'a [usize]> {
+        let indices = sudoku.layout().house_indices(self.house);
+        let house_size = sudoku.house_size();
+
+        let position = |value: usize| {
+            indices.iter().position(|&index| {
+                sudoku
+                    .cell(index)
+                    .and_then(|cell| cell.digit)
+                    .is_some_and(|digit| usize::from(digit.0) == value)
+            })
+        };
+
+        let (first, second) = (position(1)?, position(house_size)?);
+        Some(&indices[first.min(second) + 1..first.max(second)])
+    }
+
+    fn filling_sum(sudoku: &Sudoku, filling: &[usize], skip: Option<usize>) -> (u32, u32) {
+        filling
+            .iter()
+            .filter(|&&index| Some(index) != skip)
+            .fold((0, 0), |(sum, empty), &index| {
+                sudoku
+                    .cell(index)
+                    .and_then(|cell| cell.digit)
+                    .map_or((sum, empty + 1), |digit| (sum + u32::from(digit.0), empty))
+            })
+    }
+}
+
+impl Constraint for Sandwich {
+    // The filling is made of digits between 2 and one less than the highest digit
+    fn is_valid(&self, sudoku: &Sudoku) -> bool {
+        let Some(filling) = self.filling(sudoku) else {
+            return true;
+        };
+
+        let (sum, empty) = Self::filling_sum(sudoku, filling, None);
+        #[allow(clippy::cast_possible_truncation)]
+        let largest = sudoku.house_size() as u32 - 1;
+
+        if empty == 0 {
+            sum == self.sum
+        } else {
+            (sum + empty * 2..=sum + empty * largest).contains(&self.sum)
+        }
+    }
+
+    // Only the last empty cell of the filling is pinned down
+    fn cell_candidates(&self, sudoku: &Sudoku, index: usize) -> Candidates {
+        let candidates = sudoku.layout().candidates();
+
+        let Some(filling) = self
+            .filling(sudoku)
+            .filter(|filling| filling.contains(&index))
+        else {
+            return candidates;
+        };
+
+        let (sum, empty) = Self::filling_sum(sudoku, filling, Some(index));
+
+        if empty > 0 {
+            return candidates;
+        }
+
+        candidates
+            .digits()
+            .filter(|digit| sum + u32::from(digit.0) == self.sum)
+            .fold(Candidates::empty(), |acc, digit| acc.union(digit.into()))
+    }
+
+    fn check_cells(&self, layout: &Layout) -> Result<(), ConstraintError> {
+        match self.house {
+            House::Row(line) | House::Col(line) if line >= layout.house_size() => {
+                Err(ConstraintError::InvalidHouse { house: self.house })
+            }
+            _ => Ok(()),
+        }
+    }
+}
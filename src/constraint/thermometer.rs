@@ -0,0 +1,77 @@
+use crate::prelude::{Candidates, Coord, Layout, Sudoku};
+
+use super::{check_coords, Constraint, ConstraintError, Pairs};
+
+// Digits strictly increase from the bulb, the first cell, to the tip
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Thermometer {
+    cells: Vec<Coord>,
+    pairs: Pairs,
+}
+
+impl Thermometer {
+    #[must_use]
+    pub fn new(cells: impl IntoIterator<Item = Coord>) -> Self {
+        let cells = cells.into_iter().collect::<Vec<_>>();
+        let pairs = Pairs::new(cells.windows(2).map(|pair| (pair[0], pair[1])), |a, b| {
+            a < b
+        });
+
+        Self { cells, pairs }
+    }
+
+    #[must_use]
+    pub fn cells(&self) -> &[Coord] {
+        &self.cells
+    }
+
+    // A cell needs room for the cells before it below, and for the cells after it above
+    fn bounds(&self, sudoku: &Sudoku, index: usize) -> Candidates {
+        let layout = sudoku.layout();
+        let coord = layout.coord(index);
+
+        self.cells
+            .iter()
+            .position(|&cell| cell == coord)
+            .map_or_else(
+                || layout.candidates(),
+                |position| {
+                    let below = position;
+                    let above = self.cells.len() - 1 - position;
+
+                    layout
+                        .digits()
+                        .filter(|digit| {
+                            usize::from(digit.0) > below
+                                && usize::from(digit.0) + above <= layout.house_size()
+                        })
+                        .fold(Candidates::empty(), |acc, digit| acc.union(digit.into()))
+                },
+            )
+    }
+}
+
+impl Constraint for Thermometer {
+    fn is_valid(&self, sudoku: &Sudoku) -> bool {
+        self.pairs.is_valid(sudoku)
+    }
+
+    fn cell_candidates(&self, sudoku: &Sudoku, index: usize) -> Candidates {
+        self.pairs
+            .cell_candidates(sudoku, index)
+            .intersection(self.bounds(sudoku, index))
+    }
+
+    fn restrict_candidates(&self, sudoku: &Sudoku, all_candidates: &mut [Candidates]) {
+        for &coord in &self.cells {
+            let index = sudoku.layout().index(coord);
+            all_candidates[index] = all_candidates[index].intersection(self.bounds(sudoku, index));
+        }
+
+        self.pairs.restrict_candidates(sudoku, all_candidates);
+    }
+
+    fn check_cells(&self, layout: &Layout) -> Result<(), ConstraintError> {
+        check_coords(layout, self.cells.iter().copied())
+    }
+}
@@ -0,0 +1,42 @@
+use crate::prelude::Coord;
+
+use super::{PairConstraint, Pairs};
+
+const X_SUM: u8 = 10;
+const V_SUM: u8 = 5;
+
+// An X between two cells means their digits add up to 10, and a V means they add up to 5
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Xv {
+    x: Pairs,
+    v: Pairs,
+}
+
+impl Xv {
+    #[must_use]
+    pub fn new(
+        x: impl IntoIterator<Item = (Coord, Coord)>,
+        v: impl IntoIterator<Item = (Coord, Coord)>,
+    ) -> Self {
+        Self {
+            x: Pairs::new(x, |a, b| a + b == X_SUM),
+            v: Pairs::new(v, |a, b| a + b == V_SUM),
+        }
+    }
+
+    #[must_use]
+    pub fn x(&self) -> &[(Coord, Coord)] {
+        self.x.pairs()
+    }
+
+    #[must_use]
+    pub fn v(&self) -> &[(Coord, Coord)] {
+        self.v.pairs()
+    }
+}
+
+impl PairConstraint for Xv {
+    fn pairs(&self, _: usize) -> impl Iterator<Item = &Pairs> {
+        [&self.x, &self.v].into_iter()
+    }
+}
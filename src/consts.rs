@@ -4,6 +4,11 @@ pub const GRID_SIZE: usize = HOUSE_SIZE * HOUSE_SIZE;
 pub const HOUSE_SIZE: usize = SQUARE_SIZE * SQUARE_SIZE;
 pub const SQUARE_SIZE: usize = 3;
 
+// The range of supported square sizes, from 4x4 up to 25x25 grids
+pub const MIN_SQUARE_SIZE: usize = 2;
+pub const MAX_SQUARE_SIZE: usize = 5;
+pub const MAX_HOUSE_SIZE: usize = MAX_SQUARE_SIZE * MAX_SQUARE_SIZE;
+
 #[allow(clippy::cast_possible_truncation)]
 pub const DIGITS: RangeInclusive<u8> = 1..=HOUSE_SIZE as u8;
 pub const HOUSE_INDICES: Range<usize> = 0..HOUSE_SIZE;
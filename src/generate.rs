@@ -1,15 +1,355 @@
+mod gattai;
+mod jigsaw;
+mod killer;
 mod latin_squares;
 
-use crate::prelude::Sudoku;
+use std::ops::RangeInclusive;
+
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use thiserror::Error;
+
+use crate::prelude::{
+    Coord, Difficulty, DlxSolver, Layout, Rating, Sudoku, Technique, Techniques, SQUARE_SIZE,
+};
+pub use gattai::Gattai;
+pub use jigsaw::Jigsaw;
+pub use killer::Killer;
 pub use latin_squares::LatinSquares;
 
-pub trait Generate {
-    fn generate(self, config: SudokuConfig) -> Sudoku;
-    fn generate_filled(self, config: SudokuConfig) -> Sudoku;
-    fn generate_from(self, sudoku: Sudoku, config: SudokuConfig) -> Sudoku;
+// Filling a grid always succeeds, and so does removing givens for unrated configs. Rated configs
+// can ask for a difficulty or techniques that no puzzle is found with, which the `try_` methods
+// report instead of panicking.
+pub trait Generate: Sized {
+    fn try_generate_with_rng(
+        self,
+        config: SudokuConfig,
+        rng: &mut impl Rng,
+    ) -> Result<Sudoku, GenerateError>;
+    fn generate_filled_with_rng(self, config: SudokuConfig, rng: &mut impl Rng) -> Sudoku;
+    fn try_generate_from_with_rng(
+        self,
+        sudoku: Sudoku,
+        config: SudokuConfig,
+        rng: &mut impl Rng,
+    ) -> Result<Sudoku, GenerateError>;
+
+    fn generate(self, config: SudokuConfig) -> Sudoku {
+        self.generate_with_rng(config, &mut config.rng())
+    }
+
+    fn generate_with_rng(self, config: SudokuConfig, rng: &mut impl Rng) -> Sudoku {
+        self.try_generate_with_rng(config, rng)
+            .unwrap_or_else(|error| panic!("can't generate a sudoku: {error}"))
+    }
+
+    fn try_generate(self, config: SudokuConfig) -> Result<Sudoku, GenerateError> {
+        self.try_generate_with_rng(config, &mut config.rng())
+    }
+
+    fn generate_filled(self, config: SudokuConfig) -> Sudoku {
+        self.generate_filled_with_rng(config, &mut config.rng())
+    }
+
+    fn generate_from(self, sudoku: Sudoku, config: SudokuConfig) -> Sudoku {
+        self.generate_from_with_rng(sudoku, config, &mut config.rng())
+    }
+
+    fn generate_from_with_rng(
+        self,
+        sudoku: Sudoku,
+        config: SudokuConfig,
+        rng: &mut impl Rng,
+    ) -> Sudoku {
+        self.try_generate_from_with_rng(sudoku, config, rng)
+            .unwrap_or_else(|error| panic!("can't generate a sudoku: {error}"))
+    }
+
+    fn try_generate_from(
+        self,
+        sudoku: Sudoku,
+        config: SudokuConfig,
+    ) -> Result<Sudoku, GenerateError> {
+        self.try_generate_from_with_rng(sudoku, config, &mut config.rng())
+    }
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct SudokuConfig {
+    /// The number of givens to keep. When a difficulty or techniques are requested, this is the
+    /// minimum number of givens instead.
     pub cells: usize,
+    pub difficulty: Option<(Difficulty, Difficulty)>,
+    pub required_techniques: Techniques,
+    pub forbidden_techniques: Techniques,
+    pub seed: Option<u64>,
+    /// The height and width of each box, so `(2, 2)` generates 4x4 grids and `(2, 3)` generates
+    /// 6x6 grids.
+    pub box_size: (usize, usize),
+    /// Whether both main diagonals must also contain every digit, as in X-Sudoku.
+    pub diagonals: bool,
+}
+
+impl SudokuConfig {
+    #[must_use]
+    pub const fn new(cells: usize) -> Self {
+        Self {
+            cells,
+            difficulty: None,
+            required_techniques: Techniques::empty(),
+            forbidden_techniques: Techniques::empty(),
+            seed: None,
+            box_size: (SQUARE_SIZE, SQUARE_SIZE),
+            diagonals: false,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_square_size(self, square_size: usize) -> Self {
+        self.with_box_size(square_size, square_size)
+    }
+
+    #[must_use]
+    pub const fn with_box_size(mut self, box_height: usize, box_width: usize) -> Self {
+        self.box_size = (box_height, box_width);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_diagonals(mut self) -> Self {
+        self.diagonals = true;
+        self
+    }
+
+    #[must_use]
+    pub fn layout(&self) -> Layout {
+        let (box_height, box_width) = self.box_size;
+        self.with_extra_houses(Layout::new_rectangular(box_height, box_width))
+    }
+
+    // Adds the extra houses requested by the config to `layout`
+    #[must_use]
+    pub fn with_extra_houses(&self, layout: Layout) -> Layout {
+        if self.diagonals {
+            layout.with_diagonals()
+        } else {
+            layout
+        }
+    }
+
+    #[must_use]
+    pub const fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    // ChaCha is used over `StdRng`, as its output is guaranteed to stay the same across platforms
+    // and versions
+    #[must_use]
+    pub fn rng(&self) -> ChaCha8Rng {
+        self.seed
+            .map_or_else(ChaCha8Rng::from_entropy, ChaCha8Rng::seed_from_u64)
+    }
+
+    #[must_use]
+    pub const fn with_difficulty(mut self, difficulty: RangeInclusive<Difficulty>) -> Self {
+        self.difficulty = Some((*difficulty.start(), *difficulty.end()));
+        self
+    }
+
+    #[must_use]
+    pub const fn with_required_technique(mut self, technique: Technique) -> Self {
+        self.required_techniques = self.required_techniques.with(technique);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_forbidden_technique(mut self, technique: Technique) -> Self {
+        self.forbidden_techniques = self.forbidden_techniques.with(technique);
+        self
+    }
+
+    #[must_use]
+    pub const fn is_rated(&self) -> bool {
+        self.difficulty.is_some()
+            || !self.required_techniques.is_empty()
+            || !self.forbidden_techniques.is_empty()
+    }
+
+    #[must_use]
+    pub fn accepts(&self, rating: &Rating) -> bool {
+        let in_band = self
+            .difficulty
+            .is_none_or(|(min, max)| (min..=max).contains(&rating.difficulty));
+
+        in_band
+            && rating.techniques.contains_all(self.required_techniques)
+            && !rating.techniques.intersects(self.forbidden_techniques)
+    }
+
+    // Rejects the requirements that no rating can meet. Ratings are at least as hard as their
+    // hardest technique, so a required technique can't be harder than the difficulty range allows.
+    pub fn check(&self) -> Result<(), GenerateError> {
+        if let Some(technique) = self
+            .required_techniques
+            .iter()
+            .find(|&technique| self.forbidden_techniques.contains(technique))
+        {
+            return Err(GenerateError::RequiredAndForbidden { technique });
+        }
+
+        if let Some((_, max)) = self.difficulty {
+            if let Some(technique) = self
+                .required_techniques
+                .iter()
+                .find(|technique| Difficulty::from_score(technique.score()) > max)
+            {
+                return Err(GenerateError::TooHard { technique, max });
+            }
+        }
+
+        Ok(())
+    }
+
+    // Removing more givens rarely makes a puzzle easier, so generation backs off from ratings
+    // that are already too hard.
+    #[must_use]
+    pub fn is_too_hard(&self, rating: &Rating) -> bool {
+        let above_band = self
+            .difficulty
+            .is_some_and(|(_, max)| rating.difficulty > max);
+
+        above_band || rating.techniques.intersects(self.forbidden_techniques)
+    }
+}
+
+// Fills any layout by solving an empty grid with a random first row, which gives every layout many
+// different solutions. Not every layout can be filled, such as some jigsaws, in which case `None`
+// is returned.
+pub fn generate_filled_with_layout(layout: Layout, rng: &mut impl Rng) -> Option<Sudoku> {
+    let mut digits = layout.digits().collect::<Vec<_>>();
+    digits.shuffle(rng);
+
+    let mut sudoku = Sudoku::new_empty_with_layout(layout);
+
+    for (col, digit) in digits.into_iter().enumerate() {
+        sudoku.set_cell(Coord(0, col), digit);
+    }
+
+    DlxSolver::new().solutions(&sudoku).next()
+}
+
+#[derive(Error, Debug)]
+pub enum GenerateError {
+    #[error("{technique} is both required and forbidden")]
+    RequiredAndForbidden { technique: Technique },
+    #[error("{technique} is harder than {max}")]
+    TooHard {
+        technique: Technique,
+        max: Difficulty,
+    },
+    #[error("no puzzle matching the config was found in {attempts} attempts")]
+    NotFound { attempts: usize },
+    #[error("{generator} generation doesn't support {option}")]
+    Unsupported {
+        generator: &'static str,
+        option: &'static str,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::MultiSudoku;
+
+    #[test]
+    fn seeded_generation_is_reproducible() {
+        let config = SudokuConfig::new(30).with_seed(42);
+
+        for config in [config, config.with_box_size(2, 3), config.with_diagonals()] {
+            let sudoku = LatinSquares.generate(config);
+
+            assert_eq!(
+                LatinSquares.generate_filled(config),
+                LatinSquares.generate_filled(config)
+            );
+            assert_eq!(LatinSquares.generate(config), sudoku);
+            assert_ne!(LatinSquares.generate(config.with_seed(43)), sudoku);
+            assert!(sudoku.is_unique());
+        }
+
+        assert_eq!(Jigsaw.generate(config), Jigsaw.generate(config));
+        assert_eq!(Killer.generate(config), Killer.generate(config));
+
+        let twin = MultiSudoku::twin();
+        assert_eq!(
+            Gattai.generate(&twin, config).to_str_lines(),
+            Gattai.generate(&twin, config).to_str_lines()
+        );
+    }
+
+    #[test]
+    fn rejects_unmeetable_configs() {
+        let config = SudokuConfig::new(0)
+            .with_seed(0)
+            .with_required_technique(Technique::XWing)
+            .with_forbidden_technique(Technique::XWing);
+
+        assert!(matches!(
+            LatinSquares.try_generate(config),
+            Err(GenerateError::RequiredAndForbidden {
+                technique: Technique::XWing
+            })
+        ));
+
+        let config = SudokuConfig::new(0)
+            .with_seed(0)
+            .with_difficulty(Difficulty::Easy..=Difficulty::Easy)
+            .with_required_technique(Technique::XWing);
+
+        assert!(matches!(
+            LatinSquares.try_generate(config),
+            Err(GenerateError::TooHard {
+                technique: Technique::XWing,
+                max: Difficulty::Easy
+            })
+        ));
+
+        // Keeping most givens never leaves anything hard to deduce
+        let config = SudokuConfig::new(80)
+            .with_seed(0)
+            .with_difficulty(Difficulty::Diabolical..=Difficulty::Diabolical);
+
+        assert!(matches!(
+            LatinSquares.try_generate(config),
+            Err(GenerateError::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "X-Wing is both required and forbidden")]
+    fn panics_on_unmeetable_configs() {
+        let config = SudokuConfig::new(0)
+            .with_required_technique(Technique::XWing)
+            .with_forbidden_technique(Technique::XWing);
+
+        let _ = Sudoku::new_unique(config);
+    }
+
+    #[test]
+    fn generates_rated_puzzles() {
+        let config = SudokuConfig::new(0)
+            .with_seed(0)
+            .with_difficulty(Difficulty::Medium..=Difficulty::Hard)
+            .with_forbidden_technique(Technique::NakedPair);
+
+        let sudoku = LatinSquares.try_generate(config).unwrap();
+        let rating = sudoku.rate().unwrap();
+
+        assert!(sudoku.is_unique());
+        assert!(config.accepts(&rating));
+        assert!((Difficulty::Medium..=Difficulty::Hard).contains(&rating.difficulty));
+        assert!(!rating.techniques.contains(Technique::NakedPair));
+    }
 }
@@ -0,0 +1,176 @@
+use rand::{seq::SliceRandom, Rng};
+
+use crate::prelude::{generate_filled_with_layout, GenerateError, MultiSudoku};
+
+use super::SudokuConfig;
+
+// TODO: docs
+
+// Generates overlapping sudokus, known as gattai in Japan, such as Samurais. Digits are removed
+// from the combined puzzle as a whole, so that a grid may only be unique along with the others.
+pub struct Gattai;
+
+impl Gattai {
+    // Only the arrangement of `multi` is used, its digits are ignored
+    pub fn generate(self, multi: &MultiSudoku, config: SudokuConfig) -> MultiSudoku {
+        self.generate_with_rng(multi, config, &mut config.rng())
+    }
+
+    pub fn generate_with_rng(
+        self,
+        multi: &MultiSudoku,
+        config: SudokuConfig,
+        rng: &mut impl Rng,
+    ) -> MultiSudoku {
+        self.try_generate_with_rng(multi, config, rng)
+            .unwrap_or_else(|error| panic!("can't generate a sudoku: {error}"))
+    }
+
+    pub fn try_generate(
+        self,
+        multi: &MultiSudoku,
+        config: SudokuConfig,
+    ) -> Result<MultiSudoku, GenerateError> {
+        self.try_generate_with_rng(multi, config, &mut config.rng())
+    }
+
+    // The config is checked before filling, which takes far longer than rejecting it
+    pub fn try_generate_with_rng(
+        self,
+        multi: &MultiSudoku,
+        config: SudokuConfig,
+        rng: &mut impl Rng,
+    ) -> Result<MultiSudoku, GenerateError> {
+        Self::check(multi, config)?;
+
+        let filled_multi = Self.generate_filled_with_rng(multi, rng);
+        Self.try_generate_from_with_rng(filled_multi, config, rng)
+    }
+
+    // Fills the first grid on its own and lets the solver fill the others around it, starting over
+    // on the rare arrangements where it can't be extended
+    pub fn generate_filled_with_rng(self, multi: &MultiSudoku, rng: &mut impl Rng) -> MultiSudoku {
+        loop {
+            let mut filled_multi =
+                MultiSudoku::new(multi.layout().clone(), multi.offsets().to_vec())
+                    .expect("offsets are already valid");
+
+            let Some(first) = generate_filled_with_layout(multi.layout().clone(), rng) else {
+                continue;
+            };
+
+            for (i, cell) in first.cells().enumerate() {
+                if let Some(digit) = cell.digit {
+                    filled_multi.set_cell(0, i, digit);
+                }
+            }
+
+            if filled_multi.solve() {
+                return filled_multi;
+            }
+        }
+    }
+
+    pub fn generate_from_with_rng(
+        self,
+        filled_multi: MultiSudoku,
+        config: SudokuConfig,
+        rng: &mut impl Rng,
+    ) -> MultiSudoku {
+        self.try_generate_from_with_rng(filled_multi, config, rng)
+            .unwrap_or_else(|error| panic!("can't generate a sudoku: {error}"))
+    }
+
+    // Removes digits in a random order as long as the puzzle stays unique, down to `config.cells`
+    // givens across all grids
+    pub fn try_generate_from_with_rng(
+        self,
+        mut filled_multi: MultiSudoku,
+        config: SudokuConfig,
+        rng: &mut impl Rng,
+    ) -> Result<MultiSudoku, GenerateError> {
+        Self::check(&filled_multi, config)?;
+
+        let mut indices = (0..filled_multi.size()).collect::<Vec<_>>();
+        indices.shuffle(rng);
+
+        let mut filled_cells = filled_multi.size();
+
+        for index in indices {
+            if filled_cells <= config.cells {
+                break;
+            }
+
+            let Some(digit) = filled_multi.digit(index) else {
+                continue;
+            };
+
+            filled_multi.clear_digit(index);
+
+            if filled_multi.is_unique() {
+                filled_cells -= 1;
+            } else {
+                filled_multi.set_digit(index, digit);
+            }
+        }
+
+        filled_multi.mark_givens();
+        Ok(filled_multi)
+    }
+
+    // The grids keep the layout of `multi`, so the config can't change their boxes or add
+    // diagonals. Ratings can't be honoured either, as logical techniques work on single grids.
+    fn check(multi: &MultiSudoku, config: SudokuConfig) -> Result<(), GenerateError> {
+        let option = if config.is_rated() {
+            "difficulties or techniques"
+        } else if config.diagonals {
+            "diagonals"
+        } else if multi.layout().box_size() != Some(config.box_size) {
+            "box sizes other than the arrangement's"
+        } else {
+            return Ok(());
+        };
+
+        Err(GenerateError::Unsupported {
+            generator: "gattai",
+            option,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{Difficulty, Layout};
+
+    #[test]
+    fn rejects_unsupported_configs() {
+        let twin = MultiSudoku::twin();
+        let config = SudokuConfig::new(0).with_seed(0);
+
+        for (config, option) in [
+            (
+                config.with_difficulty(Difficulty::Easy..=Difficulty::Hard),
+                "difficulties or techniques",
+            ),
+            (config.with_diagonals(), "diagonals"),
+            (
+                config.with_box_size(2, 3),
+                "box sizes other than the arrangement's",
+            ),
+        ] {
+            let error = Gattai.try_generate(&twin, config).unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                format!("gattai generation doesn't support {option}")
+            );
+        }
+
+        // The box size only needs to match the grids of the arrangement
+        let twin = MultiSudoku::twin_of(Layout::new_rectangular(2, 3)).unwrap();
+        let multi = Gattai
+            .try_generate(&twin, config.with_box_size(2, 3))
+            .unwrap();
+        assert!(multi.is_unique());
+    }
+}
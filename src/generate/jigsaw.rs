@@ -0,0 +1,99 @@
+use rand::{seq::SliceRandom, Rng};
+
+use crate::prelude::{
+    generate_filled_with_layout, Generate, GenerateError, LatinSquares, Layout, Sudoku,
+};
+
+use super::SudokuConfig;
+
+// TODO: tests
+// TODO: docs
+
+// Generates jigsaw sudokus, with random regions of the config's house size
+pub struct Jigsaw;
+
+impl Generate for Jigsaw {
+    fn try_generate_with_rng(
+        self,
+        config: SudokuConfig,
+        rng: &mut impl Rng,
+    ) -> Result<Sudoku, GenerateError> {
+        let filled_sudoku = Self.generate_filled_with_rng(config, rng);
+        Self.try_generate_from_with_rng(filled_sudoku, config, rng)
+    }
+
+    fn generate_filled_with_rng(self, config: SudokuConfig, rng: &mut impl Rng) -> Sudoku {
+        loop {
+            let layout =
+                config.with_extra_houses(Self::random_layout(config.layout().house_size(), rng));
+
+            if let Some(sudoku) = generate_filled_with_layout(layout, rng) {
+                return sudoku;
+            }
+        }
+    }
+
+    // Removing givens doesn't depend on the shape of the regions
+    fn try_generate_from_with_rng(
+        self,
+        filled_sudoku: Sudoku,
+        config: SudokuConfig,
+        rng: &mut impl Rng,
+    ) -> Result<Sudoku, GenerateError> {
+        LatinSquares.try_generate_from_with_rng(filled_sudoku, config, rng)
+    }
+}
+
+impl Jigsaw {
+    // Starts from the regular boxes of the house size, or from rows when there are none, and
+    // reshapes the regions by repeatedly trading cells between neighboring regions while keeping
+    // them connected
+    pub fn random_layout(house_size: usize, rng: &mut impl Rng) -> Layout {
+        let grid_size = house_size * house_size;
+
+        let mut regions = Layout::from_house_size(house_size).map_or_else(
+            || (0..grid_size).map(|index| index / house_size).collect(),
+            |layout| layout.regions(),
+        );
+
+        let mut cells = (0..grid_size).collect::<Vec<_>>();
+
+        for _ in 0..grid_size * 50 {
+            let cell = *cells.choose(rng).unwrap();
+
+            let Some(&neighbor) = Layout::neighbors(house_size, cell)
+                .filter(|&neighbor| regions[neighbor] != regions[cell])
+                .collect::<Vec<_>>()
+                .choose(rng)
+            else {
+                continue;
+            };
+
+            let (from, to) = (regions[cell], regions[neighbor]);
+            regions[cell] = to;
+
+            // `to` now has an extra cell, so one of its cells that touches `from` moves back
+            cells.shuffle(rng);
+
+            let Some(&traded) = cells.iter().find(|&&traded| {
+                traded != cell
+                    && regions[traded] == to
+                    && Layout::neighbors(house_size, traded).any(|n| regions[n] == from)
+            }) else {
+                regions[cell] = from;
+                continue;
+            };
+
+            regions[traded] = from;
+
+            if !Layout::is_connected(house_size, &regions, from)
+                || !Layout::is_connected(house_size, &regions, to)
+            {
+                regions[traded] = to;
+                regions[cell] = from;
+            }
+        }
+
+        Layout::new_jigsaw(&regions).expect("regions are always valid")
+    }
+}
@@ -0,0 +1,288 @@
+use rand::{seq::SliceRandom, Rng};
+
+use crate::prelude::{Cage, Candidates, Generate, GenerateError, LatinSquares, Layout, Sudoku};
+
+use super::SudokuConfig;
+
+// TODO: docs
+
+// Generates killer sudokus, by partitioning a filled grid into cages
+pub struct Killer;
+
+const MIN_CAGE_SIZE: usize = 2;
+const MAX_CAGE_SIZE: usize = 4;
+
+impl Generate for Killer {
+    fn try_generate_with_rng(
+        self,
+        config: SudokuConfig,
+        rng: &mut impl Rng,
+    ) -> Result<Sudoku, GenerateError> {
+        let filled_sudoku = Self.generate_filled_with_rng(config, rng);
+        Self.try_generate_from_with_rng(filled_sudoku, config, rng)
+    }
+
+    fn generate_filled_with_rng(self, config: SudokuConfig, rng: &mut impl Rng) -> Sudoku {
+        LatinSquares.generate_filled_with_rng(config, rng)
+    }
+
+    // Givens are only kept where the cages alone leave more than one solution, and then up to
+    // `config.cells`. Rated configs are rejected, as cages narrow candidates down without counting
+    // as a technique, so ratings don't tell how hard a killer is.
+    fn try_generate_from_with_rng(
+        self,
+        filled_sudoku: Sudoku,
+        config: SudokuConfig,
+        rng: &mut impl Rng,
+    ) -> Result<Sudoku, GenerateError> {
+        if config.is_rated() {
+            return Err(GenerateError::Unsupported {
+                generator: "killer",
+                option: "difficulties or techniques",
+            });
+        }
+
+        let mut sudoku = Sudoku::new_empty_with_layout(filled_sudoku.layout().clone());
+
+        for cage in Self::random_cages(&filled_sudoku, rng) {
+            sudoku
+                .add_cage(cage)
+                .expect("cages of a filled sudoku are always valid");
+        }
+
+        // Two solutions differ in some cell, and giving it rules out at least one of them
+        loop {
+            let mut solutions = sudoku.solutions();
+
+            let (Some(first), Some(second)) = (solutions.next(), solutions.next()) else {
+                break;
+            };
+
+            let index = (0..sudoku.size())
+                .find(|&index| first.cell(index) != second.cell(index))
+                .expect("different solutions differ in some cell");
+
+            Self::add_given(&mut sudoku, &filled_sudoku, index);
+        }
+
+        let mut empty = (0..sudoku.size())
+            .filter(|&index| sudoku.cell(index).is_some_and(|cell| cell.digit.is_none()))
+            .collect::<Vec<_>>();
+        empty.shuffle(rng);
+
+        let missing = config.cells.saturating_sub(sudoku.count_filled_cells());
+
+        for index in empty.into_iter().take(missing) {
+            Self::add_given(&mut sudoku, &filled_sudoku, index);
+        }
+
+        Ok(sudoku)
+    }
+}
+
+impl Killer {
+    // Grows each cage from a random cell into random neighboring cells, never repeating a digit
+    // within a cage. Cells left without a free neighbor join a neighboring cage instead, as a cage
+    // of a single cell is just a given, and the cages are drawn again when none can take them.
+    pub fn random_cages(filled_sudoku: &Sudoku, rng: &mut impl Rng) -> Vec<Cage> {
+        loop {
+            if let Some(cages) = Self::try_random_cages(filled_sudoku, rng) {
+                return cages;
+            }
+        }
+    }
+
+    fn try_random_cages(filled_sudoku: &Sudoku, rng: &mut impl Rng) -> Option<Vec<Cage>> {
+        let layout = filled_sudoku.layout();
+        let house_size = layout.house_size();
+        let max_size = MAX_CAGE_SIZE.min(house_size);
+
+        let digit = |index: usize| {
+            filled_sudoku
+                .cell(index)
+                .and_then(|cell| cell.digit)
+                .expect("sudoku is filled")
+        };
+
+        let digits = |cells: &[usize]| {
+            cells.iter().fold(Candidates::empty(), |acc, &index| {
+                acc.union(digit(index).into())
+            })
+        };
+
+        // The cells of each cage, and the cage of each cell
+        let mut cages = vec![];
+        let mut cage_of = vec![None; layout.grid_size()];
+
+        let mut starts = (0..layout.grid_size()).collect::<Vec<_>>();
+        starts.shuffle(rng);
+
+        for start in starts {
+            if cage_of[start].is_some() {
+                continue;
+            }
+
+            let size = rng.gen_range(MIN_CAGE_SIZE..=max_size);
+            let mut cells = vec![start];
+            let mut cage_digits = Candidates::from(digit(start));
+            cage_of[start] = Some(cages.len());
+
+            while cells.len() < size {
+                let neighbors = cells
+                    .iter()
+                    .flat_map(|&index| Layout::neighbors(house_size, index))
+                    .filter(|&neighbor| {
+                        cage_of[neighbor].is_none() && !cage_digits.contains(digit(neighbor))
+                    })
+                    .collect::<Vec<_>>();
+
+                let Some(&neighbor) = neighbors.choose(rng) else {
+                    break;
+                };
+
+                cells.push(neighbor);
+                cage_digits.add(digit(neighbor));
+                cage_of[neighbor] = Some(cages.len());
+            }
+
+            cages.push(cells);
+        }
+
+        for small in 0..cages.len() {
+            if cages[small].is_empty() || cages[small].len() >= MIN_CAGE_SIZE {
+                continue;
+            }
+
+            let small_digits = digits(&cages[small]);
+
+            let mut neighbors = cages[small]
+                .iter()
+                .flat_map(|&index| Layout::neighbors(house_size, index))
+                .filter_map(|neighbor| cage_of[neighbor])
+                .filter(|&other| {
+                    other != small
+                        && cages[other].len() + cages[small].len() <= max_size
+                        && digits(&cages[other]).intersection(small_digits).is_empty()
+                })
+                .collect::<Vec<_>>();
+            neighbors.sort_unstable();
+            neighbors.dedup();
+
+            let &other = neighbors.choose(rng)?;
+            let cells = std::mem::take(&mut cages[small]);
+
+            for &index in &cells {
+                cage_of[index] = Some(other);
+            }
+
+            cages[other].extend(cells);
+        }
+
+        let cages = cages
+            .into_iter()
+            .filter(|cells| !cells.is_empty())
+            .map(|cells| {
+                let sum = digits(&cells)
+                    .digits()
+                    .map(|digit| u32::from(digit.0))
+                    .sum();
+                Cage::new(sum, cells.into_iter().map(|index| layout.coord(index)))
+            })
+            .collect();
+
+        Some(cages)
+    }
+
+    fn add_given(sudoku: &mut Sudoku, filled_sudoku: &Sudoku, index: usize) {
+        let digit = filled_sudoku.cell(index).and_then(|cell| cell.digit);
+
+        if let (Some(cell), Some(digit)) = (sudoku.cell_mut(index), digit) {
+            cell.digit = Some(digit);
+            cell.is_given = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{BruteForceSolver, Difficulty, DlxSolver, Technique};
+
+    #[test]
+    fn partitions_filled_grid_into_cages() {
+        for seed in 0..10 {
+            let config = SudokuConfig::new(0).with_seed(seed);
+            let mut rng = config.rng();
+            let filled_sudoku = Killer.generate_filled_with_rng(config, &mut rng);
+            let cages = Killer::random_cages(&filled_sudoku, &mut rng);
+
+            let mut sudoku = filled_sudoku.clone();
+            let mut caged = 0;
+
+            for cage in cages {
+                assert!((MIN_CAGE_SIZE..=MAX_CAGE_SIZE).contains(&cage.len()));
+                caged += cage.len();
+                sudoku.add_cage(cage).unwrap();
+            }
+
+            assert_eq!(caged, sudoku.size());
+            assert!(sudoku.is_valid());
+        }
+    }
+
+    #[test]
+    fn generates_unique_killers() {
+        for seed in 0..3 {
+            let config = SudokuConfig::new(0).with_seed(seed);
+            let sudoku = Killer.generate(config);
+            let filled_sudoku = Killer.generate_filled_with_rng(config, &mut config.rng());
+
+            let solutions = BruteForceSolver
+                .solutions(&sudoku)
+                .take(2)
+                .collect::<Vec<_>>();
+            let dlx_solutions = DlxSolver.solutions(&sudoku).take(2).collect::<Vec<_>>();
+
+            assert_eq!(solutions.len(), 1);
+            assert_eq!(dlx_solutions, solutions);
+            assert_eq!(solutions[0].to_str_line(), filled_sudoku.to_str_line());
+        }
+    }
+
+    #[test]
+    fn rejects_rated_configs() {
+        let config = SudokuConfig::new(0)
+            .with_seed(0)
+            .with_difficulty(Difficulty::Easy..=Difficulty::Hard);
+
+        let error = Killer.try_generate(config).unwrap_err();
+        assert!(matches!(error, GenerateError::Unsupported { .. }));
+        assert_eq!(
+            error.to_string(),
+            "killer generation doesn't support difficulties or techniques"
+        );
+
+        let config = SudokuConfig::new(0).with_required_technique(Technique::XWing);
+        assert!(Killer.try_generate(config).is_err());
+    }
+
+    #[test]
+    fn counts_agree_with_dlx() {
+        for seed in 0..3 {
+            let config = SudokuConfig::new(0).with_seed(seed);
+            let sudoku = Killer.generate(config);
+
+            // Dropping ten cages leaves several solutions
+            let cages = sudoku.to_str_cages();
+            let cages = cages.lines().skip(10).collect::<Vec<_>>();
+
+            let mut dropped = sudoku.to_str_line().parse::<Sudoku>().unwrap();
+            dropped.add_cages_from_str(&cages.join("\n")).unwrap();
+
+            let count = BruteForceSolver.count_solutions(&dropped, 100);
+
+            assert!(count > 1);
+            assert_eq!(count, DlxSolver.count_solutions(&dropped, 100));
+        }
+    }
+}
@@ -1,9 +1,11 @@
 use std::fmt::Display;
 
 use derive_more::{Deref, DerefMut};
-use rand::{seq::SliceRandom, thread_rng, Rng};
+use rand::{seq::SliceRandom, Rng};
 
-use crate::prelude::{Coord, Digit, Generate, Sudoku, DIGIT_INDICES, GRID_SIZE, SQUARE_SIZE};
+use crate::prelude::{
+    generate_filled_with_layout, Coord, Digit, Generate, GenerateError, Layout, Sudoku,
+};
 
 use super::SudokuConfig;
 
@@ -12,41 +14,52 @@ use super::SudokuConfig;
 
 pub struct LatinSquares;
 
+// Each attempt removes givens from the filled grid anew, in another order. Diabolical puzzles take
+// up to a few thousand attempts.
+const RATED_ATTEMPTS: usize = 5000;
+
 impl Generate for LatinSquares {
-    fn generate(self, config: SudokuConfig) -> Sudoku {
-        let filled_sudoku = Self.generate_filled(config);
-        Self.generate_from(filled_sudoku, config)
+    fn try_generate_with_rng(
+        self,
+        config: SudokuConfig,
+        rng: &mut impl Rng,
+    ) -> Result<Sudoku, GenerateError> {
+        let filled_sudoku = Self.generate_filled_with_rng(config, rng);
+        Self.try_generate_from_with_rng(filled_sudoku, config, rng)
     }
 
-    fn generate_filled(self, _config: SudokuConfig) -> Sudoku {
-        Self::generate_filled_sudoku()
+    fn generate_filled_with_rng(self, config: SudokuConfig, rng: &mut impl Rng) -> Sudoku {
+        Self::generate_filled_sudoku(config.layout(), rng)
     }
 
-    fn generate_from(self, filled_sudoku: Sudoku, config: SudokuConfig) -> Sudoku {
-        let mut rng = thread_rng();
-
+    fn try_generate_from_with_rng(
+        self,
+        filled_sudoku: Sudoku,
+        config: SudokuConfig,
+        rng: &mut impl Rng,
+    ) -> Result<Sudoku, GenerateError> {
+        let grid_size = filled_sudoku.size();
         let target_cells = config.cells;
-        debug_assert!((0..=81).contains(&target_cells));
+        debug_assert!((0..=grid_size).contains(&target_cells));
+
+        if config.is_rated() {
+            return Self::generate_rated(&filled_sudoku, rng, config);
+        }
 
         loop {
             let mut sudoku = Self::with_n_random_cells(
                 filled_sudoku.clone(),
-                &mut rng,
-                target_cells.clamp(GRID_SIZE / 2, GRID_SIZE),
+                rng,
+                target_cells.clamp(grid_size / 2, grid_size),
             );
 
             if sudoku.count_filled_cells() == target_cells && sudoku.is_unique() {
-                return sudoku;
+                Self::mark_givens(&mut sudoku);
+                return Ok(sudoku);
             }
 
-            let mut given_coords = sudoku
-                .cells()
-                .enumerate()
-                .filter(|(_, cell)| cell.digit.is_some())
-                .map(|(i, _)| Coord::from_index(i))
-                .collect::<Vec<_>>();
-
-            given_coords.shuffle(&mut rng);
+            let mut given_coords = Self::given_coords(&sudoku);
+            given_coords.shuffle(rng);
 
             for coord in given_coords {
                 let digit = sudoku.cell(coord).unwrap().digit.unwrap();
@@ -55,10 +68,8 @@ impl Generate for LatinSquares {
 
                 if sudoku.is_unique() {
                     if sudoku.count_filled_cells() == target_cells {
-                        for cell in sudoku.cells_mut() {
-                            cell.is_given = true;
-                        }
-                        return sudoku;
+                        Self::mark_givens(&mut sudoku);
+                        return Ok(sudoku);
                     }
                 } else {
                     sudoku.set_cell(coord, digit);
@@ -69,64 +80,111 @@ impl Generate for LatinSquares {
 }
 
 impl LatinSquares {
-    fn generate_filled_sudoku() -> Sudoku {
-        const fn convert_base_3_to_10(mut num: u8) -> u8 {
-            let mut i = 0u8;
-            let mut result = 0;
-
-            while num > 0 {
-                let exponent = 3u8.pow(i as _);
-                result += (num % 10) * exponent;
-                num /= 10;
-                i += 1;
+    fn generate_rated(
+        filled_sudoku: &Sudoku,
+        rng: &mut impl Rng,
+        config: SudokuConfig,
+    ) -> Result<Sudoku, GenerateError> {
+        config.check()?;
+
+        for _ in 0..RATED_ATTEMPTS {
+            let mut sudoku = filled_sudoku.clone();
+
+            let mut given_coords = Self::given_coords(&sudoku);
+            given_coords.shuffle(rng);
+
+            for coord in given_coords {
+                if sudoku.count_filled_cells() <= config.cells {
+                    break;
+                }
+
+                let digit = sudoku.cell(coord).unwrap().digit.unwrap();
+
+                sudoku.clear_cell(coord);
+
+                let too_hard = !sudoku.is_unique()
+                    || sudoku
+                        .rate()
+                        .is_none_or(|rating| config.is_too_hard(&rating));
+
+                if too_hard {
+                    sudoku.set_cell(coord, digit);
+                }
+            }
+
+            if sudoku.rate().is_some_and(|rating| config.accepts(&rating)) {
+                Self::mark_givens(&mut sudoku);
+                return Ok(sudoku);
             }
+        }
+
+        Err(GenerateError::NotFound {
+            attempts: RATED_ATTEMPTS,
+        })
+    }
+
+    fn given_coords(sudoku: &Sudoku) -> Vec<Coord> {
+        sudoku
+            .cells()
+            .enumerate()
+            .filter(|(_, cell)| cell.digit.is_some())
+            .map(|(i, _)| sudoku.layout().coord(i))
+            .collect()
+    }
+
+    fn mark_givens(sudoku: &mut Sudoku) {
+        for cell in sudoku.cells_mut() {
+            cell.is_given = cell.digit.is_some();
+        }
+    }
+
+    fn generate_filled_sudoku(layout: Layout, rng: &mut impl Rng) -> Sudoku {
+        let Some((size, box_width)) = layout.box_size() else {
+            unreachable!("the layout of a config always has boxes")
+        };
+
+        // The patterns below don't respect extra houses, so the grid is solved for instead
+        if !layout.extra_houses().is_empty() {
+            return generate_filled_with_layout(layout, rng)
+                .expect("layouts with boxes and extra houses can always be filled");
+        }
 
-            result
+        if size != box_width {
+            return Self::generate_filled_rectangular_sudoku(layout, rng);
         }
 
         // We follow the algorithm from this paper: https://sites.math.washington.edu/~morrow/mcm/team2280.pdf
-        // Select nine 3x3 random latin squares
-        let mut squares = std::iter::repeat_with(LatinSquare::new_random)
-            .take(9)
+        // Select a random latin square for each square of the grid
+        let mut squares = std::iter::repeat_with(|| LatinSquare::new_random(rng, size))
+            .take(size * size)
             .collect::<Vec<_>>();
 
         // Create another latin square, which corresponds to each square in the previous vec
-        let big_square = LatinSquare::new_random();
+        let big_square = LatinSquare::new_random(rng, size);
 
         // For each digit in each square, pair it with the digit of the corresponding big_square digit.
-        // Treat this paired number as base 3 and convert to base 10, adding 1
-        let mut row = 0;
-        let mut col = 0;
+        // Treat this paired number as a two digit number in base `size` and convert it to base 10,
+        // adding 1
+        #[allow(clippy::cast_possible_truncation)]
+        let base = size as u8;
 
-        for square in &mut squares {
-            let big_square_value = big_square[row][col] * 10;
+        for (index, square) in squares.iter_mut().enumerate() {
+            let Coord(row, col) = Coord::from_index_of(index, size);
+            let big_square_value = big_square[row][col];
 
             for row in square.iter_mut() {
                 for digit in row.iter_mut() {
-                    // pair the digit with the big_square value
-                    let paired = *digit + big_square_value;
-
-                    // convert the paired numbers from base 3 to base 10
-                    let base_10 = convert_base_3_to_10(paired);
-
-                    *digit = base_10 + 1;
+                    *digit = big_square_value * base + *digit + 1;
                 }
             }
-
-            if col == 2 {
-                col = 0;
-                row += 1;
-            } else {
-                col += 1;
-            }
         }
 
         // Fill a sudoku board with the generated squares
-        let mut sudoku = Sudoku::new_empty();
+        let mut sudoku = Sudoku::new_empty_with_layout(layout);
 
         for (index, latin_square) in squares.iter().enumerate() {
             for (cell_index, cell) in sudoku.square_mut(index).enumerate() {
-                let Coord(row, col) = Coord::from_index_of(cell_index, SQUARE_SIZE);
+                let Coord(row, col) = Coord::from_index_of(cell_index, size);
 
                 let latin_square_digit = latin_square[row][col];
                 let digit = Digit::new_unchecked(latin_square_digit);
@@ -135,14 +193,16 @@ impl LatinSquares {
             }
         }
 
-        Self::swap_rows(&mut sudoku);
+        Self::swap_rows(&mut sudoku, size);
 
         sudoku
     }
 
-    fn swap_rows(sudoku: &mut Sudoku) {
+    // Moves the row at `square_row * size + row` to `row * size + square_row`. For 9x9 grids,
+    // this swaps rows 1 and 3, 2 and 6, and 5 and 7.
+    fn swap_rows(sudoku: &mut Sudoku, size: usize) {
         fn inner(sudoku: &mut Sudoku, r1: usize, r2: usize) {
-            for col in DIGIT_INDICES {
+            for col in 0..sudoku.house_size() {
                 let r1_coord = Coord(r1, col);
                 let r2_coord = Coord(r2, col);
 
@@ -152,24 +212,67 @@ impl LatinSquares {
             }
         }
 
-        inner(sudoku, 1, 3);
-        inner(sudoku, 2, 6);
-        inner(sudoku, 5, 7);
+        for square_row in 0..size {
+            for row in square_row + 1..size {
+                inner(sudoku, square_row * size + row, row * size + square_row);
+            }
+        }
+    }
+
+    // Latin squares can't fill rectangular boxes, so these grids start from a valid pattern, whose
+    // digits, rows within each band, bands, columns within each stack and stacks are shuffled
+    fn generate_filled_rectangular_sudoku(layout: Layout, rng: &mut impl Rng) -> Sudoku {
+        fn shuffled_lines(rng: &mut impl Rng, groups: usize, group_size: usize) -> Vec<usize> {
+            let mut group_order = (0..groups).collect::<Vec<_>>();
+            group_order.shuffle(rng);
+
+            group_order
+                .into_iter()
+                .flat_map(|group| {
+                    let mut lines =
+                        (group * group_size..(group + 1) * group_size).collect::<Vec<_>>();
+                    lines.shuffle(rng);
+                    lines
+                })
+                .collect()
+        }
+
+        let Some((box_height, box_width)) = layout.box_size() else {
+            unreachable!("the layout of a config always has boxes")
+        };
+        let house_size = layout.house_size();
+
+        let mut digits = layout.digits().collect::<Vec<_>>();
+        digits.shuffle(rng);
+
+        let rows = shuffled_lines(rng, box_width, box_height);
+        let cols = shuffled_lines(rng, box_height, box_width);
+
+        let mut sudoku = Sudoku::new_empty_with_layout(layout);
+
+        for (row, &pattern_row) in rows.iter().enumerate() {
+            for (col, &pattern_col) in cols.iter().enumerate() {
+                let pattern =
+                    box_width * (pattern_row % box_height) + pattern_row / box_height + pattern_col;
+
+                sudoku.set_cell(Coord(row, col), digits[pattern % house_size]);
+            }
+        }
+
+        sudoku
     }
 
     fn with_n_random_cells(sudoku: Sudoku, rng: &mut impl Rng, to_keep: usize) -> Sudoku {
+        // Shuffling is used instead of picking random coordinates, as it yields the same cells for
+        // the same seed on every platform
         fn inner(mut sudoku: Sudoku, rng: &mut impl Rng, to_keep: usize) -> Sudoku {
-            let mut cell_count = sudoku.count_filled_cells();
+            let mut given_coords = LatinSquares::given_coords(&sudoku);
+            given_coords.shuffle(rng);
 
-            while cell_count > to_keep {
-                let random_coord = Coord::random(rng);
+            let to_remove = given_coords.len().saturating_sub(to_keep);
 
-                let cell = sudoku.cell_mut(random_coord).expect("cell to exist");
-
-                if cell.digit.is_some() {
-                    cell.digit = None;
-                    cell_count -= 1;
-                }
+            for coord in given_coords.into_iter().take(to_remove) {
+                sudoku.clear_cell(coord);
             }
 
             sudoku
@@ -186,7 +289,7 @@ impl LatinSquares {
 }
 
 #[derive(Debug, Default, Deref, DerefMut)]
-struct LatinSquare([[u8; SQUARE_SIZE]; SQUARE_SIZE]);
+struct LatinSquare(Vec<Vec<u8>>);
 
 impl Display for LatinSquare {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -202,26 +305,18 @@ impl Display for LatinSquare {
 }
 
 impl LatinSquare {
-    fn new_random() -> Self {
-        let mut rng = thread_rng();
-        let mut square = Self::default();
-
-        let size = square.len();
-
+    fn new_random(rng: &mut impl Rng, size: usize) -> Self {
         // Initialize square
-        for row in square.iter_mut() {
-            for (i, cell) in row.iter_mut().enumerate() {
-                *cell = i.try_into().unwrap();
-            }
-        }
+        let row = (0..size).map(|i| i.try_into().unwrap()).collect::<Vec<_>>();
+        let mut square = Self(vec![row; size]);
 
         // First row
-        square[0].shuffle(&mut rng);
+        square[0].shuffle(rng);
 
         // Middle row(s)
         for i in 1..size - 1 {
             'shuffling: loop {
-                square[i].shuffle(&mut rng);
+                square[i].shuffle(rng);
 
                 for k in 0..i {
                     for j in 0..size {
@@ -237,7 +332,7 @@ impl LatinSquare {
 
         // Last row
         for j in 0..size {
-            let mut used = [false; SQUARE_SIZE];
+            let mut used = vec![false; size];
 
             for i in 0..size - 1 {
                 used[square[i][j] as usize] = true;
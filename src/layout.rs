@@ -0,0 +1,781 @@
+use std::{
+    fmt::Display,
+    str::FromStr,
+    sync::{Arc, OnceLock},
+};
+
+use rand::Rng;
+use thiserror::Error;
+
+use crate::prelude::{
+    Candidates, Coord, Digit, SudokuIndex, SudokuResult, MAX_HOUSE_SIZE, MAX_SQUARE_SIZE,
+    MIN_SQUARE_SIZE, SQUARE_SIZE,
+};
+
+// TODO: docs
+
+// The geometry of a grid: its size, its houses, and the peers of each cell. Layouts are cheap to
+// clone, as every sudoku of the same layout shares the same precomputed data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout(Arc<LayoutInner>);
+
+#[derive(Debug, PartialEq, Eq)]
+struct LayoutInner {
+    // `None` for jigsaw layouts, whose squares are irregular regions instead of boxes
+    box_size: Option<(usize, usize)>,
+    house_size: usize,
+    houses: Vec<Vec<usize>>,
+    cell_houses: Vec<Vec<usize>>,
+    peers: Vec<Vec<usize>>,
+}
+
+impl Layout {
+    #[must_use]
+    pub fn new(square_size: usize) -> Self {
+        Self::new_rectangular(square_size, square_size)
+    }
+
+    // Boxes are `box_height` rows tall and `box_width` columns wide, so a 6x6 grid with 2x3 boxes
+    // is `new_rectangular(2, 3)`
+    #[must_use]
+    pub fn new_rectangular(box_height: usize, box_width: usize) -> Self {
+        for (name, size) in [("height", box_height), ("width", box_width)] {
+            assert!(
+                (MIN_SQUARE_SIZE..=MAX_SQUARE_SIZE).contains(&size),
+                "box {name} must be between {MIN_SQUARE_SIZE} and {MAX_SQUARE_SIZE}, got {size} instead",
+            );
+        }
+
+        if (box_height, box_width) == (SQUARE_SIZE, SQUARE_SIZE) {
+            if let Some(classic) = CLASSIC.get() {
+                return classic.clone();
+            }
+        }
+
+        let house_size = box_height * box_width;
+
+        // There are `box_height` squares in each band of rows
+        let regions = (0..house_size * house_size)
+            .map(|index| {
+                let Coord(row, col) = Coord::from_index_of(index, house_size);
+                (row / box_height) * box_height + col / box_width
+            })
+            .collect::<Vec<_>>();
+
+        Self::from_regions(Some((box_height, box_width)), house_size, &regions, vec![])
+    }
+
+    // Creates a jigsaw layout, where `regions` holds the region of each cell in row-major order.
+    // Regions can be labeled with any numbers, and are renumbered by the order of their first cell.
+    pub fn new_jigsaw(regions: &[usize]) -> Result<Self, LayoutError> {
+        let house_size = (2..=MAX_HOUSE_SIZE)
+            .find(|size| size * size == regions.len())
+            .ok_or(LayoutError::UnsupportedSize {
+                found: regions.len(),
+            })?;
+
+        let mut labels = vec![];
+
+        let regions = regions
+            .iter()
+            .map(|label| {
+                labels.iter().position(|l| l == label).unwrap_or_else(|| {
+                    labels.push(*label);
+                    labels.len() - 1
+                })
+            })
+            .collect::<Vec<_>>();
+
+        if labels.len() != house_size {
+            return Err(LayoutError::InvalidRegionCount {
+                expected: house_size,
+                found: labels.len(),
+            });
+        }
+
+        for region in 0..house_size {
+            let cells = regions.iter().filter(|&&r| r == region).count();
+
+            if cells != house_size {
+                return Err(LayoutError::InvalidRegionSize {
+                    region: region + 1,
+                    expected: house_size,
+                    found: cells,
+                });
+            }
+
+            if !Self::is_connected(house_size, &regions, region) {
+                return Err(LayoutError::DisconnectedRegion { region: region + 1 });
+            }
+        }
+
+        Ok(Self::from_regions(None, house_size, &regions, vec![]))
+    }
+
+    // Adds houses that must also contain every digit exactly once, on top of the rows, columns and
+    // squares, such as the diagonals of X-Sudoku. Each house is a list of cell indices.
+    pub fn with_extra_houses(&self, extra_houses: Vec<Vec<usize>>) -> Result<Self, LayoutError> {
+        let house_size = self.house_size();
+        let grid_size = self.grid_size();
+
+        for (house, indices) in extra_houses.iter().enumerate() {
+            let house = self.extra_houses().len() + house + 1;
+
+            if indices.len() != house_size {
+                return Err(LayoutError::InvalidHouseSize {
+                    house,
+                    expected: house_size,
+                    found: indices.len(),
+                });
+            }
+
+            for (i, &index) in indices.iter().enumerate() {
+                if index >= grid_size || indices[..i].contains(&index) {
+                    return Err(LayoutError::InvalidHouseCell { house, index });
+                }
+            }
+        }
+
+        let extra_houses = self.extra_houses().iter().cloned().chain(extra_houses);
+
+        Ok(Self::from_regions(
+            self.box_size(),
+            house_size,
+            &self.regions(),
+            extra_houses.collect(),
+        ))
+    }
+
+    // Both main diagonals become extra houses, as in X-Sudoku
+    #[must_use]
+    pub fn with_diagonals(&self) -> Self {
+        if self.is_diagonal() {
+            return self.clone();
+        }
+
+        self.with_extra_houses(Self::diagonals(self.house_size()).to_vec())
+            .expect("diagonals are always valid houses")
+    }
+
+    // The main diagonal, from the top left corner, and the anti-diagonal, from the top right corner
+    #[must_use]
+    pub fn diagonals(house_size: usize) -> [Vec<usize>; 2] {
+        let index = |row, col| Coord(row, col).into_index_of(house_size);
+
+        [
+            (0..house_size).map(|i| index(i, i)).collect(),
+            (0..house_size)
+                .map(|i| index(i, house_size - 1 - i))
+                .collect(),
+        ]
+    }
+
+    #[must_use]
+    pub fn classic() -> Self {
+        CLASSIC.get_or_init(|| Self::new(SQUARE_SIZE)).clone()
+    }
+
+    // Prefers square boxes, and otherwise boxes that are as close to square as possible, and wider
+    // than they are tall
+    #[must_use]
+    pub fn from_house_size(house_size: usize) -> Option<Self> {
+        (MIN_SQUARE_SIZE..=MAX_SQUARE_SIZE)
+            .rev()
+            .filter(|&box_height| house_size.is_multiple_of(box_height))
+            .map(|box_height| (box_height, house_size / box_height))
+            .find(|&(box_height, box_width)| {
+                box_height <= box_width && box_width <= MAX_SQUARE_SIZE
+            })
+            .map(|(box_height, box_width)| Self::new_rectangular(box_height, box_width))
+    }
+
+    #[must_use]
+    pub fn from_grid_size(grid_size: usize) -> Option<Self> {
+        let house_size = (1..=MAX_HOUSE_SIZE).find(|size| size * size == grid_size)?;
+        Self::from_house_size(house_size)
+    }
+
+    // The number of cells of every grid size that has a layout, in ascending order
+    pub fn grid_sizes() -> impl Iterator<Item = usize> {
+        (1..=MAX_HOUSE_SIZE)
+            .filter(|&house_size| Self::from_house_size(house_size).is_some())
+            .map(|house_size| house_size * house_size)
+    }
+
+    // Whether the cells of `region` are all reachable from each other, moving orthogonally
+    #[must_use]
+    pub fn is_connected(house_size: usize, regions: &[usize], region: usize) -> bool {
+        let Some(start) = regions.iter().position(|&r| r == region) else {
+            return true;
+        };
+
+        let mut visited = vec![false; regions.len()];
+        let mut stack = vec![start];
+        visited[start] = true;
+
+        while let Some(index) = stack.pop() {
+            for neighbor in Self::neighbors(house_size, index) {
+                if regions[neighbor] == region && !visited[neighbor] {
+                    visited[neighbor] = true;
+                    stack.push(neighbor);
+                }
+            }
+        }
+
+        regions
+            .iter()
+            .zip(visited)
+            .all(|(&r, visited)| r != region || visited)
+    }
+
+    // The orthogonally adjacent cells of `index`
+    pub fn neighbors(house_size: usize, index: usize) -> impl Iterator<Item = usize> {
+        let Coord(row, col) = Coord::from_index_of(index, house_size);
+
+        [
+            (row > 0).then(|| index - house_size),
+            (row + 1 < house_size).then(|| index + house_size),
+            (col > 0).then(|| index - 1),
+            (col + 1 < house_size).then(|| index + 1),
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    fn from_regions(
+        box_size: Option<(usize, usize)>,
+        house_size: usize,
+        regions: &[usize],
+        extra_houses: Vec<Vec<usize>>,
+    ) -> Self {
+        let grid_size = house_size * house_size;
+        let index = |row, col| Coord(row, col).into_index_of(house_size);
+
+        let rows = (0..house_size).map(|row| (0..house_size).map(|col| index(row, col)).collect());
+
+        let cols = (0..house_size).map(|col| (0..house_size).map(|row| index(row, col)).collect());
+
+        let squares = (0..house_size).map(|region| {
+            (0..grid_size)
+                .filter(|&index| regions[index] == region)
+                .collect()
+        });
+
+        let houses = rows
+            .chain(cols)
+            .chain(squares)
+            .chain(extra_houses)
+            .collect::<Vec<Vec<_>>>();
+
+        let mut cell_houses = vec![vec![]; grid_size];
+
+        for (house, indices) in houses.iter().enumerate() {
+            for &index in indices {
+                cell_houses[index].push(house);
+            }
+        }
+
+        let peers = cell_houses
+            .iter()
+            .enumerate()
+            .map(|(index, cell_houses)| {
+                let mut peers = cell_houses
+                    .iter()
+                    .flat_map(|&house| houses[house].iter().copied())
+                    .filter(|&peer| peer != index)
+                    .collect::<Vec<_>>();
+
+                peers.sort_unstable();
+                peers.dedup();
+                peers
+            })
+            .collect();
+
+        Self(Arc::new(LayoutInner {
+            box_size,
+            house_size,
+            houses,
+            cell_houses,
+            peers,
+        }))
+    }
+
+    // The height and width of each box, or `None` for jigsaw layouts
+    #[inline]
+    #[must_use]
+    pub fn box_size(&self) -> Option<(usize, usize)> {
+        self.0.box_size
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn is_jigsaw(&self) -> bool {
+        self.0.box_size.is_none()
+    }
+
+    // Whether both main diagonals are extra houses
+    #[must_use]
+    pub fn is_diagonal(&self) -> bool {
+        let extra_houses = self.extra_houses();
+
+        Self::diagonals(self.house_size())
+            .iter()
+            .all(|diagonal| extra_houses.contains(diagonal))
+    }
+
+    // The cells of each house beyond the rows, columns and squares
+    #[inline]
+    #[must_use]
+    pub fn extra_houses(&self) -> &[Vec<usize>] {
+        &self.0.houses[self.house_size() * 3..]
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn house_size(&self) -> usize {
+        self.0.house_size
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn grid_size(&self) -> usize {
+        self.0.house_size * self.0.house_size
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn coord(&self, index: usize) -> Coord {
+        Coord::from_index_of(index, self.house_size())
+    }
+
+    // Whether the coordinate is within the grid, as coordinates past its width would otherwise wrap
+    // around to the next row
+    #[inline]
+    #[must_use]
+    pub fn contains(&self, coord: Coord) -> bool {
+        coord.0 < self.house_size() && coord.1 < self.house_size()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn index<I: SudokuIndex>(&self, i: I) -> usize {
+        i.into_index_of(self.house_size())
+    }
+
+    #[must_use]
+    pub fn candidates(&self) -> Candidates {
+        Candidates::all_of(self.house_size())
+    }
+
+    pub fn digits(&self) -> impl Iterator<Item = Digit> {
+        self.candidates().digits()
+    }
+
+    #[must_use]
+    pub fn is_valid_digit(&self, digit: Digit) -> bool {
+        Digit::is_valid_of(*digit, self.house_size())
+    }
+
+    pub fn digit(&self, value: u8) -> SudokuResult<Digit> {
+        Digit::new_of(value, self.house_size())
+    }
+
+    #[must_use]
+    pub fn random_coord(&self, rng: &mut impl Rng) -> Coord {
+        Coord::random_of(rng, self.house_size())
+    }
+
+    pub fn houses(&self) -> impl Iterator<Item = House> + '_ {
+        (0..self.0.houses.len()).map(|id| self.house(id))
+    }
+
+    #[must_use]
+    pub fn house_indices(&self, house: House) -> &[usize] {
+        &self.0.houses[self.house_id(house)]
+    }
+
+    // Houses are numbered in the order of `houses`, so that data about each house can be kept in
+    // a flat list
+    #[inline]
+    #[must_use]
+    pub fn house_count(&self) -> usize {
+        self.0.houses.len()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn house_indices_by_id(&self, id: usize) -> &[usize] {
+        &self.0.houses[id]
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn house_ids_of_cell<I: SudokuIndex>(&self, i: I) -> &[usize] {
+        &self.0.cell_houses[self.index(i)]
+    }
+
+    pub fn houses_of_cell<I: SudokuIndex>(&self, i: I) -> impl Iterator<Item = House> + '_ {
+        self.0.cell_houses[self.index(i)]
+            .iter()
+            .map(|&id| self.house(id))
+    }
+
+    // Every cell is in exactly one row, one column and one square, in that order, followed by the
+    // extra houses it belongs to
+    #[must_use]
+    pub fn square_of_cell<I: SudokuIndex>(&self, i: I) -> usize {
+        self.0.cell_houses[self.index(i)][2] - self.house_size() * 2
+    }
+
+    // The square of each cell, in row-major order
+    #[must_use]
+    pub fn regions(&self) -> Vec<usize> {
+        (0..self.grid_size())
+            .map(|index| self.square_of_cell(index))
+            .collect()
+    }
+
+    #[must_use]
+    pub fn peers<I: SudokuIndex>(&self, i: I) -> &[usize] {
+        &self.0.peers[self.index(i)]
+    }
+
+    // Writes the region map with one line per row, labeling regions `A`, `B`, `C` and so on
+    #[must_use]
+    pub fn to_str_regions(&self) -> String {
+        let mut regions = String::new();
+
+        for (index, region) in self.regions().into_iter().enumerate() {
+            if index > 0 && index % self.house_size() == 0 {
+                regions.push('\n');
+            }
+
+            #[allow(clippy::cast_possible_truncation)]
+            regions.push(char::from(b'A' + region as u8));
+        }
+
+        regions.push('\n');
+        regions
+    }
+
+    fn house(&self, id: usize) -> House {
+        let house_size = self.house_size();
+
+        match id / house_size {
+            0 => House::Row(id),
+            1 => House::Col(id - house_size),
+            2 => House::Square(id - house_size * 2),
+            _ => House::Extra(id - house_size * 3),
+        }
+    }
+
+    fn house_id(&self, house: House) -> usize {
+        let house_size = self.house_size();
+
+        let (kind, index, count) = match house {
+            House::Row(row) => (0, row, house_size),
+            House::Col(col) => (1, col, house_size),
+            House::Square(square) => (2, square, house_size),
+            House::Extra(extra) => (3, extra, self.extra_houses().len()),
+        };
+
+        assert!(
+            index < count,
+            "{house} doesn't exist in a grid with houses of size {house_size}"
+        );
+
+        kind * house_size + index
+    }
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self::classic()
+    }
+}
+
+// Parses a jigsaw region map, where each non-whitespace char labels the region of a cell, such as
+// the output of `to_str_regions`
+impl FromStr for Layout {
+    type Err = LayoutError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let regions = s
+            .chars()
+            .filter(|char| !char.is_whitespace())
+            .map(|char| char as usize)
+            .collect::<Vec<_>>();
+
+        Self::new_jigsaw(&regions)
+    }
+}
+
+static CLASSIC: OnceLock<Layout> = OnceLock::new();
+
+#[derive(Error, Debug)]
+pub enum LayoutError {
+    #[error("{found} cells don't make a square grid")]
+    UnsupportedSize { found: usize },
+    #[error("expected {expected} regions, found {found}")]
+    InvalidRegionCount { expected: usize, found: usize },
+    #[error("region {region} has {found} cells instead of {expected}")]
+    InvalidRegionSize {
+        region: usize,
+        expected: usize,
+        found: usize,
+    },
+    #[error("region {region} isn't connected")]
+    DisconnectedRegion { region: usize },
+    #[error("extra house {house} has {found} cells instead of {expected}")]
+    InvalidHouseSize {
+        house: usize,
+        expected: usize,
+        found: usize,
+    },
+    #[error("extra house {house} has an invalid or repeated cell {index}")]
+    InvalidHouseCell { house: usize, index: usize },
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum House {
+    Row(usize),
+    Col(usize),
+    Square(usize),
+    Extra(usize),
+}
+
+impl Display for House {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Row(row) => write!(f, "row {}", row + 1),
+            Self::Col(col) => write!(f, "column {}", col + 1),
+            Self::Square(square) => write!(f, "box {}", square + 1),
+            Self::Extra(extra) => write!(f, "extra house {}", extra + 1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{Jigsaw, SudokuConfig};
+
+    #[test]
+    fn numbers_houses_of_classic_layout() {
+        let layout = Layout::classic();
+
+        assert_eq!(layout.house_size(), 9);
+        assert_eq!(layout.grid_size(), 81);
+        assert_eq!(layout.house_count(), 27);
+        assert_eq!(layout.box_size(), Some((3, 3)));
+
+        // Rows come first, then columns, then squares
+        assert_eq!(layout.house_ids_of_cell(Coord(4, 7)), [4, 16, 23]);
+        assert_eq!(
+            layout.houses_of_cell(Coord(4, 7)).collect::<Vec<_>>(),
+            [House::Row(4), House::Col(7), House::Square(5)]
+        );
+        assert_eq!(layout.square_of_cell(Coord(4, 7)), 5);
+        assert_eq!(
+            layout.house_indices(House::Square(5)),
+            [33, 34, 35, 42, 43, 44, 51, 52, 53]
+        );
+        assert_eq!(
+            layout.house_indices_by_id(23),
+            layout.house_indices(House::Square(5))
+        );
+        assert_eq!(layout.houses().nth(23), Some(House::Square(5)));
+    }
+
+    #[test]
+    fn finds_peers_of_classic_layout() {
+        let layout = Layout::classic();
+
+        assert_eq!(
+            layout.peers(0),
+            [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 18, 19, 20, 27, 36, 45, 54, 63, 72]
+        );
+
+        for index in 0..layout.grid_size() {
+            let peers = layout.peers(index);
+
+            assert_eq!(peers.len(), 20);
+            assert!(!peers.contains(&index));
+            assert!(peers
+                .iter()
+                .all(|&peer| layout.peers(peer).contains(&index)));
+        }
+    }
+
+    #[test]
+    fn adds_diagonals_as_extra_houses() {
+        let layout = Layout::classic().with_diagonals();
+
+        assert!(layout.is_diagonal());
+        assert_eq!(layout.house_count(), 29);
+        assert_eq!(
+            layout.extra_houses()[1],
+            [8, 16, 24, 32, 40, 48, 56, 64, 72]
+        );
+        assert_eq!(layout.house_ids_of_cell(40), [4, 13, 22, 27, 28]);
+        assert_eq!(layout.house_ids_of_cell(1), [0, 10, 18]);
+
+        // The center sees the twelve diagonal cells outside of its box, on top of its 20 peers
+        assert_eq!(layout.peers(40).len(), 32);
+        assert_eq!(layout.with_diagonals(), layout);
+    }
+
+    #[test]
+    fn rejects_invalid_extra_houses() {
+        let layout = Layout::classic();
+
+        assert!(matches!(
+            layout.with_extra_houses(vec![(0..8).collect()]),
+            Err(LayoutError::InvalidHouseSize {
+                house: 1,
+                expected: 9,
+                found: 8
+            })
+        ));
+        assert!(matches!(
+            layout.with_extra_houses(vec![vec![0, 1, 2, 3, 4, 5, 6, 7, 7]]),
+            Err(LayoutError::InvalidHouseCell { house: 1, index: 7 })
+        ));
+        assert!(matches!(
+            layout.with_extra_houses(vec![(73..82).collect()]),
+            Err(LayoutError::InvalidHouseCell {
+                house: 1,
+                index: 81
+            })
+        ));
+    }
+
+    #[test]
+    fn picks_layouts_by_size() {
+        assert_eq!(Layout::from_house_size(9), Some(Layout::classic()));
+        assert_eq!(Layout::from_grid_size(16), Some(Layout::new(2)));
+        assert_eq!(Layout::from_house_size(18), None);
+        assert_eq!(Layout::from_grid_size(80), None);
+
+        assert_eq!(
+            Layout::grid_sizes().collect::<Vec<_>>(),
+            [16, 36, 64, 81, 100, 144, 225, 256, 400, 625]
+        );
+    }
+
+    #[test]
+    fn builds_rectangular_boxes() {
+        let layout = Layout::new_rectangular(2, 3);
+
+        assert_eq!(layout.house_size(), 6);
+        assert_eq!(layout.grid_size(), 36);
+        assert_eq!(layout.box_size(), Some((2, 3)));
+        assert!(!layout.is_jigsaw());
+
+        // Boxes are two rows tall and three columns wide, two to a band
+        assert_eq!(
+            layout.to_str_regions(),
+            "AAABBB\nAAABBB\nCCCDDD\nCCCDDD\nEEEFFF\nEEEFFF\n"
+        );
+        assert_eq!(layout.square_of_cell(Coord(2, 3)), 3);
+        assert_eq!(
+            layout.house_indices(House::Square(3)),
+            [15, 16, 17, 21, 22, 23]
+        );
+        assert_eq!(layout.house_ids_of_cell(Coord(2, 3)), [2, 9, 15]);
+
+        assert_eq!(layout.peers(0), [1, 2, 3, 4, 5, 6, 7, 8, 12, 18, 24, 30]);
+        assert!((0..36).all(|index| layout.peers(index).len() == 12));
+
+        assert_eq!(Layout::from_house_size(6), Some(layout));
+        assert_eq!(
+            Layout::from_house_size(12).and_then(|layout| layout.box_size()),
+            Some((3, 4))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "box width must be between 2 and 5, got 6 instead")]
+    fn rejects_oversized_boxes() {
+        let _ = Layout::new_rectangular(2, 6);
+    }
+
+    #[test]
+    fn builds_jigsaw_regions() {
+        let layout = "xxxy xzyy zzyw zwww".parse::<Layout>().unwrap();
+
+        assert!(layout.is_jigsaw());
+        assert_eq!(layout.box_size(), None);
+        assert_eq!(layout.house_size(), 4);
+
+        // Labels are renumbered in the order of their first cell
+        assert_eq!(layout.to_str_regions(), "AAAB\nACBB\nCCBD\nCDDD\n");
+        assert_eq!(
+            layout.regions(),
+            [0, 0, 0, 1, 0, 2, 1, 1, 2, 2, 1, 3, 2, 3, 3, 3]
+        );
+        assert_eq!(layout.house_indices(House::Square(2)), [5, 8, 9, 12]);
+        assert_eq!(layout.house_ids_of_cell(Coord(1, 1)), [1, 5, 10]);
+        assert_eq!(layout.peers(Coord(1, 1)), [1, 4, 6, 7, 8, 9, 12, 13]);
+    }
+
+    #[test]
+    fn round_trips_region_maps() {
+        let mut rng = SudokuConfig::new(0).with_seed(0).rng();
+
+        for house_size in [4, 6, 9] {
+            let layout = Jigsaw::random_layout(house_size, &mut rng);
+            let parsed = layout.to_str_regions().parse::<Layout>().unwrap();
+
+            assert_eq!(parsed, layout);
+        }
+
+        // Boxes read back as a jigsaw with the same regions
+        let classic = Layout::classic();
+        let parsed = classic.to_str_regions().parse::<Layout>().unwrap();
+
+        assert!(parsed.is_jigsaw());
+        assert_eq!(parsed.regions(), classic.regions());
+        assert_eq!(parsed.peers(40), classic.peers(40));
+    }
+
+    #[test]
+    fn rejects_invalid_regions() {
+        assert!(matches!(
+            "AAAB ACBB CCBD CDD".parse::<Layout>(),
+            Err(LayoutError::UnsupportedSize { found: 15 })
+        ));
+        assert!(matches!(
+            "AAAA BBBB CCCC CCCC".parse::<Layout>(),
+            Err(LayoutError::InvalidRegionCount {
+                expected: 4,
+                found: 3
+            })
+        ));
+        assert!(matches!(
+            "AAAA ABBB CCCB DDDD".parse::<Layout>(),
+            Err(LayoutError::InvalidRegionSize {
+                region: 1,
+                expected: 4,
+                found: 5
+            })
+        ));
+
+        let error = "ABBA ABBA CCDD CCDD".parse::<Layout>().unwrap_err();
+        assert!(matches!(
+            error,
+            LayoutError::DisconnectedRegion { region: 1 }
+        ));
+        assert_eq!(error.to_string(), "region 1 isn't connected");
+    }
+
+    #[test]
+    fn contains_only_coords_within_the_grid() {
+        let layout = Layout::new(2);
+
+        assert!(layout.contains(Coord(3, 3)));
+        assert!(!layout.contains(Coord(0, 4)));
+        assert!(!layout.contains(Coord(4, 0)));
+    }
+}
@@ -10,15 +10,29 @@
 )]
 #![allow(clippy::module_name_repetitions, clippy::multiple_crate_versions)]
 
+mod bitboard;
+mod cage;
+mod constraint;
 mod consts;
 mod generate;
+mod layout;
 mod macros;
+mod multi;
+mod rate;
+#[cfg(feature = "serde")]
+mod serialization;
 mod solve;
 mod sudoku;
 
 pub mod prelude {
+    pub use crate::bitboard::*;
+    pub use crate::cage::*;
+    pub use crate::constraint::*;
     pub use crate::consts::*;
     pub use crate::generate::*;
+    pub use crate::layout::*;
+    pub use crate::multi::*;
+    pub use crate::rate::*;
     pub use crate::solve::*;
     pub use crate::sudoku::*;
 }
@@ -0,0 +1,517 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    sync::Arc,
+};
+
+use thiserror::Error;
+
+use crate::prelude::{
+    Candidates, Coord, Digit, Layout, MultiSolver, ParseError, Sudoku, SudokuIndex,
+};
+
+// TODO: tests
+// TODO: docs
+
+// Several grids of the same layout that overlap on whole boxes, such as Samurai sudokus. Each grid
+// sits at an offset on a larger canvas, and grids covering the same canvas cell share it: placing
+// a digit in one of them places it in all of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiSudoku {
+    shape: Arc<Shape>,
+    grids: Vec<Sudoku>,
+}
+
+// Cells are numbered once across the whole canvas, so that solvers can treat the grids as a
+// single puzzle. Every clone of a multi sudoku shares the same shape.
+#[derive(Debug, PartialEq, Eq)]
+struct Shape {
+    layout: Layout,
+    offsets: Vec<Coord>,
+    // The canvas coordinates of each cell, and the cell of each index of each grid
+    coords: Vec<Coord>,
+    grid_cells: Vec<Vec<usize>>,
+    // Every grid and index that covers each cell
+    occurrences: Vec<Vec<(usize, usize)>>,
+    // Shared boxes appear once
+    houses: Vec<Vec<usize>>,
+    peers: Vec<Vec<usize>>,
+}
+
+impl MultiSudoku {
+    // Grids are placed with their top left cell at each offset of the canvas, which must line up
+    // with the boxes of the layout
+    pub fn new(
+        layout: Layout,
+        offsets: impl IntoIterator<Item = Coord>,
+    ) -> Result<Self, MultiSudokuError> {
+        let offsets = offsets.into_iter().collect::<Vec<_>>();
+        let (box_height, box_width) = layout.box_size().ok_or(MultiSudokuError::NoBoxes)?;
+
+        if offsets.is_empty() {
+            return Err(MultiSudokuError::NoGrids);
+        }
+
+        for (grid, offset) in offsets.iter().enumerate() {
+            if !offset.row().is_multiple_of(box_height) || !offset.col().is_multiple_of(box_width) {
+                return Err(MultiSudokuError::MisalignedGrid { grid });
+            }
+
+            if offsets[..grid].contains(offset) {
+                return Err(MultiSudokuError::DuplicateGrid { grid });
+            }
+        }
+
+        let grids = offsets
+            .iter()
+            .map(|_| Sudoku::new_empty_with_layout(layout.clone()))
+            .collect();
+
+        Ok(Self {
+            shape: Arc::new(Shape::new(layout, offsets)),
+            grids,
+        })
+    }
+
+    // Fails when the grids don't share a layout, or disagree on the digits of their shared cells
+    pub fn from_grids(
+        grids: Vec<Sudoku>,
+        offsets: impl IntoIterator<Item = Coord>,
+    ) -> Result<Self, MultiSudokuError> {
+        let layout = grids.first().ok_or(MultiSudokuError::NoGrids)?.layout();
+        let mut multi = Self::new(layout.clone(), offsets)?;
+
+        if grids.len() != multi.grids.len() {
+            return Err(MultiSudokuError::GridCount {
+                expected: multi.grids.len(),
+                found: grids.len(),
+            });
+        }
+
+        if let Some(grid) = grids.iter().position(|grid| grid.layout() != layout) {
+            return Err(MultiSudokuError::DifferentLayout { grid });
+        }
+
+        for (index, occurrences) in multi.shape.occurrences.iter().enumerate() {
+            let mut digits = occurrences
+                .iter()
+                .filter_map(|&(grid, i)| grids[grid].cell(i).and_then(|cell| cell.digit));
+
+            if let Some(digit) = digits.next() {
+                if digits.any(|other| other != digit) {
+                    return Err(MultiSudokuError::ConflictingCell {
+                        coord: multi.shape.coords[index],
+                    });
+                }
+            }
+        }
+
+        let shape = Arc::clone(&multi.shape);
+        multi.grids = grids;
+
+        // Cells that are only filled in some of the grids are copied over to the others
+        for (index, occurrences) in shape.occurrences.iter().enumerate() {
+            let cell = occurrences
+                .iter()
+                .filter_map(|&(grid, i)| multi.grids[grid].cell(i))
+                .find(|cell| cell.digit.is_some())
+                .copied();
+
+            if let Some(cell) = cell {
+                for &(grid, i) in &shape.occurrences[index] {
+                    if let Some(other) = multi.grids[grid].cell_mut(i) {
+                        other.digit = cell.digit;
+                        other.is_given = cell.is_given;
+                    }
+                }
+            }
+        }
+
+        Ok(multi)
+    }
+
+    // Five grids, four of which share a corner box with the one in the middle
+    #[must_use]
+    pub fn samurai() -> Self {
+        Self::samurai_of(Layout::classic()).expect("classic layouts have boxes")
+    }
+
+    pub fn samurai_of(layout: Layout) -> Result<Self, MultiSudokuError> {
+        let (row, col) = Self::corner_offset(&layout)?;
+
+        Self::new(
+            layout,
+            [
+                Coord(0, 0),
+                Coord(0, col * 2),
+                Coord(row, col),
+                Coord(row * 2, 0),
+                Coord(row * 2, col * 2),
+            ],
+        )
+    }
+
+    // Two grids sharing a corner box
+    #[must_use]
+    pub fn twin() -> Self {
+        Self::twin_of(Layout::classic()).expect("classic layouts have boxes")
+    }
+
+    pub fn twin_of(layout: Layout) -> Result<Self, MultiSudokuError> {
+        let (row, col) = Self::corner_offset(&layout)?;
+        Self::new(layout, [Coord(0, 0), Coord(row, col)])
+    }
+
+    // Four grids, each one box away from its neighbors, so that every grid overlaps all of the
+    // others
+    #[must_use]
+    pub fn butterfly() -> Self {
+        Self::butterfly_of(Layout::classic()).expect("classic layouts have boxes")
+    }
+
+    pub fn butterfly_of(layout: Layout) -> Result<Self, MultiSudokuError> {
+        let (box_height, box_width) = layout.box_size().ok_or(MultiSudokuError::NoBoxes)?;
+
+        Self::new(
+            layout,
+            [
+                Coord(0, 0),
+                Coord(0, box_width),
+                Coord(box_height, 0),
+                Coord(box_height, box_width),
+            ],
+        )
+    }
+
+    // The offset at which a grid overlaps another by exactly its bottom right box
+    fn corner_offset(layout: &Layout) -> Result<(usize, usize), MultiSudokuError> {
+        let (box_height, box_width) = layout.box_size().ok_or(MultiSudokuError::NoBoxes)?;
+        let house_size = layout.house_size();
+
+        Ok((house_size - box_height, house_size - box_width))
+    }
+
+    // Each line is a grid in the format of `Sudoku::to_str_line`, in the order of `offsets`
+    pub fn from_str_lines(
+        s: &str,
+        layout: Layout,
+        offsets: impl IntoIterator<Item = Coord>,
+    ) -> Result<Self, MultiSudokuError> {
+        let grids = s
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| Sudoku::from_str_with_layout(line, layout.clone()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::from_grids(grids, offsets)
+    }
+
+    #[must_use]
+    pub fn to_str_lines(&self) -> String {
+        self.grids
+            .iter()
+            .map(|grid| grid.to_str_line() + "\n")
+            .collect()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn layout(&self) -> &Layout {
+        &self.shape.layout
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn offsets(&self) -> &[Coord] {
+        &self.shape.offsets
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn grids(&self) -> &[Sudoku] {
+        &self.grids
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn grid(&self, grid: usize) -> Option<&Sudoku> {
+        self.grids.get(grid)
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn into_grids(self) -> Vec<Sudoku> {
+        self.grids
+    }
+
+    // The number of distinct cells, counting shared cells once
+    #[inline]
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.shape.coords.len()
+    }
+
+    // The canvas height and width, in cells
+    #[must_use]
+    pub fn canvas_size(&self) -> (usize, usize) {
+        self.shape
+            .coords
+            .iter()
+            .fold((0, 0), |(height, width), coord| {
+                (height.max(coord.row() + 1), width.max(coord.col() + 1))
+            })
+    }
+
+    // Cells are numbered by their position on the canvas, row by row
+    #[inline]
+    #[must_use]
+    pub fn coord(&self, index: usize) -> Coord {
+        self.shape.coords[index]
+    }
+
+    #[must_use]
+    pub fn index_at(&self, coord: Coord) -> Option<usize> {
+        self.shape.coords.binary_search(&coord).ok()
+    }
+
+    // The cell that index `i` of `grid` is, or `None` when there's no such grid
+    #[must_use]
+    pub fn index_of<I: SudokuIndex>(&self, grid: usize, i: I) -> Option<usize> {
+        let cells = self.shape.grid_cells.get(grid)?;
+        cells.get(self.layout().index(i)).copied()
+    }
+
+    // Every grid that covers the cell, along with the cell's index within it
+    #[inline]
+    #[must_use]
+    pub fn occurrences(&self, index: usize) -> &[(usize, usize)] {
+        &self.shape.occurrences[index]
+    }
+
+    // The houses of every grid in terms of cells, where shared boxes appear once
+    #[inline]
+    #[must_use]
+    pub fn houses(&self) -> &[Vec<usize>] {
+        &self.shape.houses
+    }
+
+    // The cells that share a house with the cell in any of its grids
+    #[inline]
+    #[must_use]
+    pub fn peers(&self, index: usize) -> &[usize] {
+        &self.shape.peers[index]
+    }
+
+    #[must_use]
+    pub fn digit(&self, index: usize) -> Option<Digit> {
+        let &(grid, i) = self.shape.occurrences.get(index)?.first()?;
+        self.grids[grid].cell(i).and_then(|cell| cell.digit)
+    }
+
+    // Places the digit in every grid that covers the cell
+    pub fn set_digit(&mut self, index: usize, digit: Digit) {
+        for &(grid, i) in &self.shape.occurrences[index] {
+            self.grids[grid].set_cell(i, digit);
+        }
+    }
+
+    pub fn clear_digit(&mut self, index: usize) {
+        for &(grid, i) in &self.shape.occurrences[index] {
+            self.grids[grid].clear_cell(i);
+        }
+    }
+
+    // Returns `false` when there's no such grid
+    pub fn set_cell<I: SudokuIndex>(&mut self, grid: usize, i: I, digit: Digit) -> bool {
+        self.index_of(grid, i)
+            .map(|index| self.set_digit(index, digit))
+            .is_some()
+    }
+
+    pub fn clear_cell<I: SudokuIndex>(&mut self, grid: usize, i: I) -> bool {
+        self.index_of(grid, i)
+            .map(|index| self.clear_digit(index))
+            .is_some()
+    }
+
+    // Turns every filled cell into a given, as generators do once they're done removing digits
+    pub fn mark_givens(&mut self) {
+        for cell in self.grids.iter_mut().flat_map(Sudoku::cells_mut) {
+            cell.is_given = cell.digit.is_some();
+        }
+    }
+
+    // The digits that the cell can take in every grid that covers it
+    #[must_use]
+    pub fn cell_candidates(&self, index: usize) -> Candidates {
+        self.shape.occurrences[index]
+            .iter()
+            .fold(self.layout().candidates(), |candidates, &(grid, i)| {
+                candidates.intersection(self.grids[grid].cell_candidates(i))
+            })
+    }
+
+    #[must_use]
+    pub fn count_filled_cells(&self) -> usize {
+        (0..self.size())
+            .filter(|&index| self.digit(index).is_some())
+            .count()
+    }
+
+    #[must_use]
+    pub fn is_filled(&self) -> bool {
+        self.grids.iter().all(Sudoku::is_filled)
+    }
+
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        self.grids.iter().all(Sudoku::is_valid)
+    }
+
+    pub fn solve(&mut self) -> bool {
+        MultiSolver.solve(self)
+    }
+
+    pub fn solutions(&self) -> impl Iterator<Item = Self> {
+        MultiSolver.solutions(self)
+    }
+
+    #[must_use]
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        MultiSolver.count_solutions(self, limit)
+    }
+
+    #[must_use]
+    pub fn is_unique(&self) -> bool {
+        self.count_solutions(2) == 1
+    }
+}
+
+impl Shape {
+    fn new(layout: Layout, offsets: Vec<Coord>) -> Self {
+        let house_size = layout.house_size();
+
+        let canvas_coord = |offset: Coord, index: usize| {
+            let Coord(row, col) = layout.coord(index);
+            Coord(offset.row() + row, offset.col() + col)
+        };
+
+        let mut coords = offsets
+            .iter()
+            .flat_map(|&offset| (0..layout.grid_size()).map(move |index| (offset, index)))
+            .map(|(offset, index)| canvas_coord(offset, index))
+            .collect::<Vec<_>>();
+        coords.sort_unstable();
+        coords.dedup();
+
+        let indices = coords
+            .iter()
+            .enumerate()
+            .map(|(index, &coord)| (coord, index))
+            .collect::<HashMap<_, _>>();
+
+        let grid_cells = offsets
+            .iter()
+            .map(|&offset| {
+                (0..layout.grid_size())
+                    .map(|index| indices[&canvas_coord(offset, index)])
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut occurrences = vec![vec![]; coords.len()];
+
+        for (grid, cells) in grid_cells.iter().enumerate() {
+            for (i, &index) in cells.iter().enumerate() {
+                occurrences[index].push((grid, i));
+            }
+        }
+
+        let mut seen = HashSet::new();
+        let mut houses = vec![];
+
+        for cells in &grid_cells {
+            for house in layout.houses() {
+                let mut house = layout
+                    .house_indices(house)
+                    .iter()
+                    .map(|&i| cells[i])
+                    .collect::<Vec<_>>();
+                house.sort_unstable();
+
+                if seen.insert(house.clone()) {
+                    houses.push(house);
+                }
+            }
+        }
+
+        let mut peers = vec![HashSet::with_capacity(house_size * 3); coords.len()];
+
+        for house in &houses {
+            for &index in house {
+                peers[index].extend(house.iter().filter(|&&peer| peer != index));
+            }
+        }
+
+        let peers = peers
+            .into_iter()
+            .map(|peers| {
+                let mut peers = peers.into_iter().collect::<Vec<_>>();
+                peers.sort_unstable();
+                peers
+            })
+            .collect();
+
+        Self {
+            layout,
+            offsets,
+            coords,
+            grid_cells,
+            occurrences,
+            houses,
+            peers,
+        }
+    }
+}
+
+// Draws the canvas row by row, leaving the cells that no grid covers blank
+impl Display for MultiSudoku {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (height, width) = self.canvas_size();
+
+        for row in 0..height {
+            let line = (0..width)
+                .map(|col| {
+                    self.index_at(Coord(row, col))
+                        .map_or(' ', |index| self.digit(index).map_or('.', Digit::to_char))
+                })
+                .flat_map(|char| [char, ' '])
+                .collect::<String>();
+
+            writeln!(f, "{}", line.trim_end())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum MultiSudokuError {
+    #[error("expected at least one grid")]
+    NoGrids,
+    #[error("grids can only overlap on layouts with boxes")]
+    NoBoxes,
+    #[error("grid {} isn't aligned with the boxes of the canvas", .grid + 1)]
+    MisalignedGrid { grid: usize },
+    #[error("grid {} is at the same offset as another grid", .grid + 1)]
+    DuplicateGrid { grid: usize },
+    #[error("expected {expected} grids, found {found}")]
+    GridCount { expected: usize, found: usize },
+    #[error("grid {} has a different layout than the first grid", .grid + 1)]
+    DifferentLayout { grid: usize },
+    #[error("grids disagree on the digit at row {}, column {} of the canvas", .coord.0 + 1, .coord.1 + 1)]
+    ConflictingCell { coord: Coord },
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+}
@@ -0,0 +1,209 @@
+use std::fmt::Display;
+
+use crate::prelude::{LogicalOutcome, LogicalSolver, Step, Sudoku, Technique, Techniques};
+
+// TODO: docs
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+    Diabolical,
+}
+
+impl Difficulty {
+    pub const ALL: [Self; 5] = [
+        Self::Easy,
+        Self::Medium,
+        Self::Hard,
+        Self::Expert,
+        Self::Diabolical,
+    ];
+
+    #[must_use]
+    pub fn from_score(score: f32) -> Self {
+        match score {
+            s if s <= 2.3 => Self::Easy,
+            s if s <= 3.4 => Self::Medium,
+            s if s <= 4.6 => Self::Hard,
+            s if s <= 6.5 => Self::Expert,
+            _ => Self::Diabolical,
+        }
+    }
+
+    #[must_use]
+    pub const fn harder(self) -> Self {
+        match self {
+            Self::Easy => Self::Medium,
+            Self::Medium => Self::Hard,
+            Self::Hard => Self::Expert,
+            Self::Expert | Self::Diabolical => Self::Diabolical,
+        }
+    }
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Easy => "Easy",
+            Self::Medium => "Medium",
+            Self::Hard => "Hard",
+            Self::Expert => "Expert",
+            Self::Diabolical => "Diabolical",
+        })
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rating {
+    pub difficulty: Difficulty,
+    pub score: f32,
+    pub hardest: Option<Technique>,
+    pub techniques: Techniques,
+    pub steps: usize,
+    pub requires_guessing: bool,
+}
+
+impl Rating {
+    // Puzzles that can't be solved without guessing are rated above every known technique
+    pub const GUESSING_SCORE: f32 = 10.0;
+
+    // Puzzles needing their hardest technique this many times are bumped to the next difficulty
+    const REPEATED_HARDEST_STEPS: usize = 5;
+
+    #[must_use]
+    pub fn new(sudoku: &Sudoku) -> Option<Self> {
+        Self::with_solver(sudoku, LogicalSolver::new())
+    }
+
+    // Rates by the steps of the given solver, such as one using uniqueness techniques for puzzles
+    // known to have a single solution
+    #[must_use]
+    pub fn with_solver(sudoku: &Sudoku, solver: LogicalSolver) -> Option<Self> {
+        let mut sudoku = sudoku.clone();
+        let (outcome, steps) = solver.solve_with_steps(&mut sudoku);
+
+        let requires_guessing = match outcome {
+            LogicalOutcome::Solved => false,
+            LogicalOutcome::Stuck => true,
+            LogicalOutcome::Invalid => return None,
+        };
+
+        let hardest = steps.iter().max_by(|a, b| a.score().total_cmp(&b.score()));
+
+        let score = if requires_guessing {
+            Self::GUESSING_SCORE
+        } else {
+            hardest.map_or(0.0, Step::score)
+        };
+
+        let mut difficulty = Difficulty::from_score(score);
+
+        if !requires_guessing {
+            let hardest_steps = steps.iter().filter(|step| step.score() >= score).count();
+
+            if score > Technique::NakedSingle.score()
+                && hardest_steps >= Self::REPEATED_HARDEST_STEPS
+            {
+                difficulty = difficulty.harder();
+            }
+        }
+
+        Some(Self {
+            difficulty,
+            score,
+            hardest: hardest.map(|step| step.technique),
+            techniques: steps.iter().map(|step| step.technique).collect(),
+            steps: steps.len(),
+            requires_guessing,
+        })
+    }
+}
+
+impl Display for Rating {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({:.1})", self.difficulty, self.score)?;
+
+        if let Some(hardest) = self.hardest {
+            write!(f, ", hardest technique: {hardest}")?;
+        }
+
+        if self.requires_guessing {
+            f.write_str(", requires guessing")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{Coord, Digit};
+
+    const PUZZLE: &str =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+
+    #[test]
+    fn maps_scores_to_difficulties() {
+        for (score, difficulty) in [
+            (0.0, Difficulty::Easy),
+            (2.3, Difficulty::Easy),
+            (2.4, Difficulty::Medium),
+            (3.4, Difficulty::Medium),
+            (3.5, Difficulty::Hard),
+            (4.6, Difficulty::Hard),
+            (4.7, Difficulty::Expert),
+            (6.5, Difficulty::Expert),
+            (6.6, Difficulty::Diabolical),
+            (Rating::GUESSING_SCORE, Difficulty::Diabolical),
+        ] {
+            assert_eq!(Difficulty::from_score(score), difficulty, "{score}");
+        }
+
+        assert_eq!(Difficulty::Easy.harder(), Difficulty::Medium);
+        assert_eq!(Difficulty::Diabolical.harder(), Difficulty::Diabolical);
+    }
+
+    #[test]
+    fn rates_by_hardest_technique() {
+        let rating = PUZZLE.parse::<Sudoku>().unwrap().rate().unwrap();
+
+        assert_eq!(rating.difficulty, Difficulty::Easy);
+        assert_eq!(rating.hardest, Some(Technique::HiddenSingle));
+        assert!(rating.score <= Technique::NakedSingle.score());
+        assert!(!rating.requires_guessing);
+        assert_eq!(rating.steps, 51);
+        assert_eq!(
+            rating.to_string(),
+            "Easy (1.2), hardest technique: Hidden Single"
+        );
+    }
+
+    #[test]
+    fn rates_guessing_above_every_technique() {
+        // Easter Monster, which no technique of the solver makes progress on
+        let sudoku =
+            "1.......2.9.4...5...6...7...5.9.3.......7.......85..4.7.....6...3...9.8...2.....1"
+                .parse::<Sudoku>()
+                .unwrap();
+        let rating = sudoku.rate().unwrap();
+
+        assert!(rating.requires_guessing);
+        assert_eq!(rating.score, Rating::GUESSING_SCORE);
+        assert_eq!(rating.difficulty, Difficulty::Diabolical);
+        assert_eq!(rating.to_string(), "Diabolical (10.0), requires guessing");
+    }
+
+    #[test]
+    fn rates_broken_grids_as_none() {
+        let mut sudoku = PUZZLE.parse::<Sudoku>().unwrap();
+        sudoku.set_cell(Coord(0, 2), Digit(5));
+
+        assert_eq!(sudoku.rate(), None);
+    }
+}
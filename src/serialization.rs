@@ -0,0 +1,285 @@
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::prelude::{
+    Cage, Candidates, Cell, Digit, Layout, Sudoku, Technique, Techniques, MAX_HOUSE_SIZE,
+    MAX_SQUARE_SIZE, MIN_SQUARE_SIZE,
+};
+
+// Sudokus are serialized as a flat sequence of cells, in row-major order, when their layout can
+// be inferred from the number of cells. Otherwise, their layout and cages are serialized along
+// with them.
+impl Serialize for Sudoku {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // Constraints are trait objects, so only cages can be serialized
+        if self.constraints().count() > self.cages().len() {
+            return Err(serde::ser::Error::custom(
+                "only cages can be serialized out of all constraints",
+            ));
+        }
+
+        if !self.is_killer() && Layout::from_grid_size(self.size()).as_ref() == Some(self.layout())
+        {
+            serializer.collect_seq(self.cells())
+        } else {
+            SudokuRepr::WithLayout {
+                layout: self.layout().clone(),
+                cells: self.as_slice().to_vec(),
+                cages: self.cages().to_vec(),
+            }
+            .serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Sudoku {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let sudoku = match SudokuRepr::deserialize(deserializer)? {
+            SudokuRepr::Cells(cells) => {
+                let found = cells.len();
+
+                Self::try_from(cells).map_err(|_| {
+                    let expected = Layout::grid_sizes()
+                        .map(|grid_size| grid_size.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ");
+
+                    D::Error::invalid_length(found, &format!("one of {expected} cells").as_str())
+                })?
+            }
+            SudokuRepr::WithLayout {
+                layout,
+                cells,
+                cages,
+            } => {
+                let found = cells.len();
+                let expected = layout.grid_size();
+
+                let mut sudoku = Self::from_cells_with_layout(cells, layout).map_err(|_| {
+                    D::Error::invalid_length(found, &format!("{expected} cells").as_str())
+                })?;
+
+                for cage in cages {
+                    sudoku.add_cage(cage).map_err(D::Error::custom)?;
+                }
+
+                sudoku
+            }
+        };
+
+        let invalid_digit = sudoku
+            .cells()
+            .filter_map(|cell| cell.digit)
+            .find(|&digit| !sudoku.layout().is_valid_digit(digit));
+
+        match invalid_digit {
+            Some(digit) => Err(D::Error::custom(format!(
+                "digit must be between 1 and {}, got {digit}",
+                sudoku.house_size()
+            ))),
+            None => Ok(sudoku),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum SudokuRepr {
+    Cells(Vec<Cell>),
+    WithLayout {
+        layout: Layout,
+        cells: Vec<Cell>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        cages: Vec<Cage>,
+    },
+}
+
+// Layouts are serialized as either the size of their boxes, or the region of each cell, along with
+// their extra houses if they have any
+impl Serialize for Layout {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let squares = match self.box_size() {
+            Some((box_height, box_width)) => SquaresRepr::Boxes(box_height, box_width),
+            None => SquaresRepr::Regions(self.regions()),
+        };
+
+        LayoutRepr {
+            squares,
+            extra_houses: self.extra_houses().to_vec(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Layout {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let LayoutRepr {
+            squares,
+            extra_houses,
+        } = LayoutRepr::deserialize(deserializer)?;
+
+        let layout = match squares {
+            SquaresRepr::Boxes(box_height, box_width) => {
+                let valid = MIN_SQUARE_SIZE..=MAX_SQUARE_SIZE;
+
+                if valid.contains(&box_height) && valid.contains(&box_width) {
+                    Ok(Self::new_rectangular(box_height, box_width))
+                } else {
+                    Err(D::Error::custom(format!(
+                        "box sizes must be between {MIN_SQUARE_SIZE} and {MAX_SQUARE_SIZE}, got {box_height}x{box_width}"
+                    )))
+                }
+            }
+            SquaresRepr::Regions(regions) => Self::new_jigsaw(&regions).map_err(D::Error::custom),
+        }?;
+
+        if extra_houses.is_empty() {
+            Ok(layout)
+        } else {
+            layout
+                .with_extra_houses(extra_houses)
+                .map_err(D::Error::custom)
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct LayoutRepr {
+    #[serde(flatten)]
+    squares: SquaresRepr,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    extra_houses: Vec<Vec<usize>>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum SquaresRepr {
+    Boxes(usize, usize),
+    Regions(Vec<usize>),
+}
+
+// Digits of every supported size are accepted here, and checked against the layout of the sudoku
+// they belong to
+impl Serialize for Digit {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Digit {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = u8::deserialize(deserializer)?;
+        Self::new_of(value, MAX_HOUSE_SIZE).map_err(D::Error::custom)
+    }
+}
+
+// Candidates are serialized as the digits they contain, rather than their bits
+impl Serialize for Candidates {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.digits())
+    }
+}
+
+impl<'de> Deserialize<'de> for Candidates {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let digits = Vec::<Digit>::deserialize(deserializer)?;
+
+        Ok(digits
+            .into_iter()
+            .fold(Self::empty(), |acc, digit| acc.union(digit.into())))
+    }
+}
+
+// Techniques are serialized by name, so that adding techniques doesn't change existing data
+impl Serialize for Techniques {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de> Deserialize<'de> for Techniques {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Vec::<Technique>::deserialize(deserializer)?
+            .into_iter()
+            .collect())
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+    use crate::prelude::{
+        generate_filled_with_layout, AntiKnight, Generate, Jigsaw, Killer, LatinSquares,
+        SudokuConfig,
+    };
+
+    fn round_trip(sudoku: &Sudoku) -> serde_json::Value {
+        let json = serde_json::to_value(sudoku).unwrap();
+        assert_eq!(
+            &serde_json::from_value::<Sudoku>(json.clone()).unwrap(),
+            sudoku
+        );
+        json
+    }
+
+    #[test]
+    fn round_trips_classic_sudokus_as_cells() {
+        let sudoku = LatinSquares.generate(SudokuConfig::new(30).with_seed(0));
+        let json = round_trip(&sudoku);
+
+        assert_eq!(json.as_array().map(Vec::len), Some(81));
+    }
+
+    #[test]
+    fn round_trips_killers_with_cages() {
+        let sudoku = Killer.generate(SudokuConfig::new(0).with_seed(0));
+        let json = round_trip(&sudoku);
+
+        assert_eq!(json["layout"]["boxes"], serde_json::json!([3, 3]));
+        assert_eq!(
+            json["cages"].as_array().map(Vec::len),
+            Some(sudoku.cages().len())
+        );
+    }
+
+    #[test]
+    fn round_trips_jigsaw_and_diagonal_layouts() {
+        let mut rng = SudokuConfig::new(0).with_seed(0).rng();
+        let layout = Jigsaw::random_layout(9, &mut rng);
+        let sudoku = generate_filled_with_layout(layout.clone(), &mut rng).unwrap();
+        let json = round_trip(&sudoku);
+
+        assert_eq!(
+            json["layout"]["regions"],
+            serde_json::to_value(layout.regions()).unwrap()
+        );
+
+        let sudoku = LatinSquares.generate(SudokuConfig::new(30).with_seed(0).with_diagonals());
+        let json = round_trip(&sudoku);
+
+        assert_eq!(
+            json["layout"]["extra_houses"],
+            serde_json::to_value(Layout::diagonals(9)).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_out_of_range_digits() {
+        let sudoku = LatinSquares.generate_filled(SudokuConfig::new(0).with_square_size(2));
+        let mut json = round_trip(&sudoku);
+        json[0]["digit"] = 5.into();
+
+        let error = serde_json::from_value::<Sudoku>(json).unwrap_err();
+        assert_eq!(error.to_string(), "digit must be between 1 and 4, got 5");
+    }
+
+    #[test]
+    fn fails_on_constraints_other_than_cages() {
+        let sudoku = Sudoku::new_empty().with_constraint(AntiKnight).unwrap();
+
+        let error = serde_json::to_string(&sudoku).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "only cages can be serialized out of all constraints"
+        );
+    }
+}
@@ -1,6 +1,13 @@
 mod brute_force;
+mod dlx;
+mod logical;
+mod multi;
+mod search;
 
-pub use brute_force::BruteForceSolver;
+pub use brute_force::{BruteForceSolutions, BruteForceSolver};
+pub use dlx::{DlxSolutions, DlxSolver};
+pub use logical::{LogicalOutcome, LogicalSolver, Step, Technique, Techniques};
+pub use multi::{MultiSolutions, MultiSolver};
 
 use crate::prelude::Sudoku;
 
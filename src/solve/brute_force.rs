@@ -1,98 +1,266 @@
-use crate::prelude::{Candidates, Coord, Digit, Sudoku, SudokuIndex, HOUSE_INDICES};
+use crate::prelude::{Bitboard, Candidates, Digit, Sudoku};
 
-use super::Solve;
+use super::{
+    search::{Search, SearchSpace},
+    Solve,
+};
 
-// TODO: tests
 // TODO: docs
 
+#[derive(Default)]
 pub struct BruteForceSolver;
 
 impl BruteForceSolver {
     pub const fn new() -> Self {
         Self
     }
+
+    pub fn solutions(self, sudoku: &Sudoku) -> BruteForceSolutions {
+        BruteForceSolutions::new(sudoku.clone())
+    }
+
+    pub fn count_solutions(self, sudoku: &Sudoku, limit: usize) -> usize {
+        self.solutions(sudoku).take(limit).count()
+    }
 }
 
 impl Solve for BruteForceSolver {
     fn solve(self, sudoku: &mut Sudoku) -> bool {
-        let all_candidates = sudoku.all_candidates();
-        Self::solve_inner(sudoku, all_candidates)
+        let Some(solution) = self.solutions(sudoku).next() else {
+            return false;
+        };
+
+        *sudoku = solution;
+        true
     }
 }
 
-impl BruteForceSolver {
-    fn solve_inner(sudoku: &mut Sudoku, mut all_candidates: Vec<Candidates>) -> bool {
-        if let Some((index, candidates)) = Self::cell_with_least_candidates(sudoku, &all_candidates)
-        {
-            for digit in candidates.digits() {
-                sudoku.set_cell(index, digit);
+// Lazily enumerates every solution, searching over a bitboard. Candidates are recomputed from its
+// house masks, so going deeper doesn't allocate.
+pub struct BruteForceSolutions(Search<Grid>);
+
+impl BruteForceSolutions {
+    fn new(sudoku: Sudoku) -> Self {
+        Self(Search::new(Grid {
+            board: Bitboard::from_sudoku(&sudoku),
+            constrained: sudoku.has_constraints(),
+            sudoku,
+        }))
+    }
+}
 
-                Self::recalculate_all_candidates(&mut all_candidates, index, digit);
+impl Iterator for BruteForceSolutions {
+    type Item = Sudoku;
 
-                if Self::solve_inner(sudoku, all_candidates.clone()) {
-                    return true;
-                }
-            }
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
+
+struct Grid {
+    sudoku: Sudoku,
+    board: Bitboard,
+    constrained: bool,
+}
+
+impl SearchSpace for Grid {
+    type Solution = Sudoku;
+
+    fn size(&self) -> usize {
+        self.sudoku.size()
+    }
+
+    fn digit(&self, index: usize) -> Option<Digit> {
+        self.board.digit(index)
+    }
 
-            sudoku.clear_cell(index);
+    // Constrained puzzles keep the sudoku in sync, as constraints look at its digits
+    fn place(&mut self, index: usize, digit: Digit) {
+        self.board.place(index, digit);
 
-            false
-        } else {
-            true
+        if self.constrained {
+            self.sudoku.set_cell(index, digit);
         }
     }
 
-    fn cell_with_least_candidates(
-        sudoku: &Sudoku,
-        all_candidates: &[Candidates],
-    ) -> Option<(usize, Candidates)> {
-        let mut best_candidates_count = usize::MAX;
-        let mut result = None;
-
-        for (i, _) in sudoku
-            .cells()
-            .enumerate()
-            .filter(|(_, cell)| cell.digit.is_none())
-        {
-            let candidates = all_candidates[i];
-            let candidates_count = candidates.count();
-
-            if candidates_count < best_candidates_count {
-                best_candidates_count = candidates_count;
-                result = Some((i, candidates));
-
-                if best_candidates_count <= 1 {
-                    return result;
-                }
-            }
+    fn clear(&mut self, index: usize) {
+        self.board.clear(index);
+
+        if self.constrained {
+            self.sudoku.clear_cell(index);
         }
+    }
 
-        result
+    // Constraints prune beyond what the house masks tell, so constrained puzzles keep the
+    // candidates of each guess instead
+    fn initial_candidates(&self) -> Option<Vec<Candidates>> {
+        self.constrained.then(|| {
+            let mut all_candidates = self.sudoku.all_candidates();
+            self.restrict_candidates(&mut all_candidates);
+            all_candidates
+        })
     }
 
-    fn recalculate_all_candidates(all_candidates: &mut [Candidates], index: usize, digit: Digit) {
-        let coord @ Coord(row, col) = Coord::from_index(index);
+    fn candidates(&self, index: usize) -> Candidates {
+        self.board.candidates(index)
+    }
+
+    fn narrow_candidates(&self, _index: usize, _digit: Digit, all_candidates: &mut [Candidates]) {
+        self.restrict_candidates(all_candidates);
+    }
+
+    fn house_count(&self) -> usize {
+        self.board.layout().house_count()
+    }
+
+    fn house_indices(&self, id: usize) -> &[usize] {
+        self.board.layout().house_indices_by_id(id)
+    }
+
+    fn missing_digits(&self, id: usize) -> Candidates {
+        self.board
+            .layout()
+            .candidates()
+            .difference(self.board.house_digits(id))
+    }
+
+    fn is_valid(&self) -> bool {
+        self.sudoku.is_valid()
+    }
+
+    // The sudoku is only in sync when constrained, and the bitboard never breaks a house
+    fn is_solution(&self) -> bool {
+        !self.constrained || self.sudoku.is_valid()
+    }
+
+    fn solution(&self) -> Sudoku {
+        let mut solution = self.sudoku.clone();
+
+        if !self.constrained {
+            self.board.write_to(&mut solution);
+        }
 
-        // Remove digit from column
-        for r in HOUSE_INDICES {
-            if r != row {
-                let index = Coord(r, col).into_index();
-                all_candidates[index].remove(digit);
-            }
+        solution
+    }
+}
+
+impl Grid {
+    // Drops the candidates that the house masks rule out, then lets the constraints prune further
+    fn restrict_candidates(&self, all_candidates: &mut [Candidates]) {
+        for (index, candidates) in all_candidates.iter_mut().enumerate() {
+            *candidates = candidates.intersection(self.board.candidates(index));
         }
 
-        // Remove digit from column
-        for c in HOUSE_INDICES {
-            if c != col {
-                let index = Coord(row, c).into_index();
-                all_candidates[index].remove(digit);
-            }
+        for constraint in self.sudoku.constraints() {
+            constraint.restrict_candidates(&self.sudoku, all_candidates);
         }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::prelude::{Coord, Layout};
+
+    const PUZZLE: &str =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+
+    // The solution of `PUZZLE`, without the 1s and 3s of r4c6, r4c9, r5c6 and r5c9, which can be
+    // swapped with each other
+    const TWO_SOLUTIONS: &str =
+        "534678912672195348198342567859760420426850790713924856961537284287419635345286179";
+
+    #[test]
+    fn counts_unique_puzzles_once() {
+        let sudoku = PUZZLE.parse::<Sudoku>().unwrap();
+
+        assert_eq!(BruteForceSolver.count_solutions(&sudoku, 10), 1);
+        assert!(sudoku.is_unique());
+    }
+
+    #[test]
+    fn counts_every_solution() {
+        let sudoku = TWO_SOLUTIONS.parse::<Sudoku>().unwrap();
+
+        assert_eq!(BruteForceSolver.count_solutions(&sudoku, 10), 2);
+        assert!(!sudoku.is_unique());
+
+        // There are 288 filled 4x4 grids
+        let sudoku = Sudoku::new_empty_with_layout(Layout::new(2));
+        assert_eq!(BruteForceSolver.count_solutions(&sudoku, usize::MAX), 288);
+    }
+
+    #[test]
+    fn stops_counting_at_the_limit() {
+        let sudoku = Sudoku::new_empty_with_layout(Layout::new(2));
 
-        for i in Sudoku::square_indices_of_cell(coord) {
-            if i != index {
-                all_candidates[i].remove(digit);
-            }
+        assert_eq!(BruteForceSolver.count_solutions(&sudoku, 100), 100);
+        assert_eq!(BruteForceSolver.count_solutions(&sudoku, 1), 1);
+        assert_eq!(BruteForceSolver.count_solutions(&sudoku, 0), 0);
+    }
+
+    #[test]
+    fn counts_no_solutions_of_broken_grids() {
+        let mut sudoku = PUZZLE.parse::<Sudoku>().unwrap();
+        sudoku.set_cell(Coord(0, 2), Digit(5));
+
+        assert_eq!(BruteForceSolver.count_solutions(&sudoku, 10), 0);
+
+        // Valid so far, but r1c1 sees every digit
+        let sudoku = ".2341...........".parse::<Sudoku>().unwrap();
+        assert_eq!(BruteForceSolver.count_solutions(&sudoku, 10), 0);
+    }
+
+    #[test]
+    fn yields_each_solution_once() {
+        let sudoku = TWO_SOLUTIONS.parse::<Sudoku>().unwrap();
+        let mut solutions = BruteForceSolver.solutions(&sudoku);
+
+        let first = solutions.next().unwrap();
+        let second = solutions.next().unwrap();
+        assert!(solutions.next().is_none());
+        assert!(solutions.next().is_none());
+
+        assert_ne!(first, second);
+
+        for solution in [first, second] {
+            assert!(solution.is_filled() && solution.is_valid());
+            assert!(sudoku
+                .cells()
+                .zip(solution.cells())
+                .all(|(cell, solved)| cell.digit.is_none() || cell.digit == solved.digit));
         }
+
+        let sudoku = Sudoku::new_empty_with_layout(Layout::new(2));
+        let solutions = BruteForceSolver
+            .solutions(&sudoku)
+            .map(|solution| solution.to_str_line())
+            .collect::<HashSet<_>>();
+        assert_eq!(solutions.len(), 288);
+    }
+
+    #[test]
+    fn searches_lazily() {
+        // An empty grid has far too many solutions to find them all first
+        let sudoku = Sudoku::new_empty();
+        let solutions = sudoku.solutions().take(3).collect::<Vec<_>>();
+
+        assert_eq!(solutions.len(), 3);
+        assert!(solutions.iter().all(Sudoku::is_valid));
+        assert!(solutions[0] != solutions[1] && solutions[1] != solutions[2]);
+    }
+
+    #[test]
+    fn solves_to_the_first_solution() {
+        let mut sudoku = TWO_SOLUTIONS.parse::<Sudoku>().unwrap();
+        let first = sudoku.solutions().next().unwrap();
+
+        assert!(BruteForceSolver.solve(&mut sudoku));
+        assert_eq!(sudoku, first);
+
+        let mut sudoku = ".2341...........".parse::<Sudoku>().unwrap();
+        assert!(!BruteForceSolver.solve(&mut sudoku));
     }
 }
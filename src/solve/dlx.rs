@@ -0,0 +1,508 @@
+use crate::prelude::{Candidates, Digit, Sudoku};
+
+use super::Solve;
+
+// TODO: docs
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DlxSolver;
+
+impl DlxSolver {
+    pub const fn new() -> Self {
+        Self
+    }
+
+    pub fn solutions(self, sudoku: &Sudoku) -> DlxSolutions {
+        DlxSolutions::new(sudoku.clone())
+    }
+
+    pub fn count_solutions(self, sudoku: &Sudoku, limit: usize) -> usize {
+        self.solutions(sudoku).take(limit).count()
+    }
+}
+
+impl Solve for DlxSolver {
+    fn solve(self, sudoku: &mut Sudoku) -> bool {
+        let Some(solution) = self.solutions(sudoku).next() else {
+            return false;
+        };
+
+        *sudoku = solution;
+        true
+    }
+}
+
+const ROOT: usize = 0;
+
+// Lazily enumerates every solution with Knuth's Algorithm X, using dancing links
+pub struct DlxSolutions {
+    sudoku: Sudoku,
+    links: Links,
+    stack: Vec<usize>,
+    forward: bool,
+    constrained: bool,
+    // The cells filled to begin with, which the search leaves alone
+    filled: Vec<bool>,
+    // Constraints prune beyond what the columns tell, so constrained puzzles keep the candidates
+    // left after each selected row
+    candidates: Vec<Vec<Candidates>>,
+}
+
+impl DlxSolutions {
+    // Each cell needs a digit, and each house needs each digit exactly once
+    fn new(sudoku: Sudoku) -> Self {
+        let layout = sudoku.layout();
+        let house_size = layout.house_size();
+        let grid_size = layout.grid_size();
+
+        let mut cell_houses = vec![vec![]; grid_size];
+
+        for (house_index, house) in layout.houses().enumerate() {
+            for &index in layout.house_indices(house) {
+                cell_houses[index].push(house_index);
+            }
+        }
+
+        let house_count = layout.houses().count();
+        let mut links = Links::new(grid_size + house_count * house_size);
+
+        // Digits ruled out by the givens or by cages never make it into the matrix
+        let all_candidates = sudoku.all_candidates();
+
+        for (index, cell) in sudoku.cells().enumerate() {
+            for digit in layout.digits() {
+                if cell.digit.map_or_else(
+                    || all_candidates[index].contains(digit),
+                    |given| given == digit,
+                ) {
+                    let digit_index = digit.0 as usize - 1;
+
+                    // Column headers start at 1, as 0 is the root
+                    let columns = std::iter::once(1 + index)
+                        .chain(cell_houses[index].iter().map(|&house_index| {
+                            1 + grid_size + house_index * house_size + digit_index
+                        }))
+                        .collect::<Vec<_>>();
+
+                    links.add_row(index * house_size + digit_index, &columns);
+                }
+            }
+        }
+
+        let constrained = sudoku.has_constraints();
+
+        Self {
+            candidates: if constrained {
+                vec![all_candidates]
+            } else {
+                vec![]
+            },
+            constrained,
+            filled: sudoku.cells().map(|cell| cell.digit.is_some()).collect(),
+            sudoku,
+            links,
+            stack: vec![],
+            forward: true,
+        }
+    }
+
+    fn solution(&self) -> Sudoku {
+        let mut sudoku = self.sudoku.clone();
+
+        for &node in &self.stack {
+            let (index, digit) = self.placement(node);
+            sudoku.set_cell(index, digit);
+        }
+
+        sudoku
+    }
+
+    // The cell and digit of a node's row
+    fn placement(&self, node: usize) -> (usize, Digit) {
+        let house_size = self.sudoku.house_size();
+        let row_id = self.links.row[node];
+        #[allow(clippy::cast_possible_truncation)]
+        let digit = Digit::new_unchecked((row_id % house_size) as u8 + 1);
+        (row_id / house_size, digit)
+    }
+
+    // Constraints other than houses aren't exact cover constraints, so rows are checked against
+    // the candidates they leave before being selected. This keeps the search from walking every
+    // completion of the houses.
+    fn first_allowed(&self, mut node: usize, column: usize) -> Option<usize> {
+        while node != column {
+            if !self.constrained || self.allows(node) {
+                return Some(node);
+            }
+
+            node = self.links.down[node];
+        }
+
+        None
+    }
+
+    // The column with the fewest rows left, along with their count. For constrained puzzles, only
+    // the rows that the constraints allow are counted, so that a column without any is a dead end
+    // right away rather than once it's reached.
+    fn next_column(&self) -> (usize, usize) {
+        if !self.constrained {
+            let column = self.links.smallest_column();
+            return (column, self.links.size[column]);
+        }
+
+        let mut best = (ROOT, usize::MAX);
+        let mut column = self.links.right[ROOT];
+
+        while column != ROOT {
+            let mut count = 0;
+            let mut node = self.links.down[column];
+
+            // Counting past the best column so far doesn't change anything
+            while node != column && count < best.1 {
+                if self.allows(node) {
+                    count += 1;
+                }
+
+                node = self.links.down[node];
+            }
+
+            if count < best.1 {
+                best = (column, count);
+
+                if count == 0 {
+                    break;
+                }
+            }
+
+            column = self.links.right[column];
+        }
+
+        best
+    }
+
+    fn allows(&self, node: usize) -> bool {
+        let (index, digit) = self.placement(node);
+
+        self.filled[index]
+            || self
+                .candidates
+                .last()
+                .is_some_and(|candidates| candidates[index].contains(digit))
+    }
+
+    // Constrained puzzles keep the sudoku in sync, as constraints look at its digits
+    fn select(&mut self, node: usize) {
+        self.links.select(node);
+        self.stack.push(node);
+
+        if !self.constrained {
+            return;
+        }
+
+        let (index, digit) = self.placement(node);
+        let mut candidates = self.candidates.last().expect("initial candidates").clone();
+
+        if !self.filled[index] {
+            self.sudoku.set_cell(index, digit);
+            candidates[index] = Candidates::empty();
+
+            for &peer in self.sudoku.layout().peers(index) {
+                candidates[peer].remove(digit);
+            }
+
+            for constraint in self.sudoku.constraints() {
+                constraint.restrict_candidates(&self.sudoku, &mut candidates);
+            }
+        }
+
+        self.candidates.push(candidates);
+    }
+
+    fn unselect(&mut self) -> Option<usize> {
+        let node = self.stack.pop()?;
+        self.links.unselect(node);
+
+        if !self.constrained {
+            return Some(node);
+        }
+
+        let (index, _) = self.placement(node);
+
+        if !self.filled[index] {
+            self.sudoku.clear_cell(index);
+        }
+
+        self.candidates.pop();
+
+        Some(node)
+    }
+}
+
+impl Iterator for DlxSolutions {
+    type Item = Sudoku;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.forward {
+                if self.links.right[ROOT] == ROOT {
+                    self.forward = false;
+
+                    // Constraints only prune soundly, so full grids are checked against them
+                    let solution = self.solution();
+
+                    if !solution.has_constraints() || solution.is_valid() {
+                        return Some(solution);
+                    }
+
+                    continue;
+                }
+
+                let (column, size) = self.next_column();
+
+                if size == 0 {
+                    self.forward = false;
+                    continue;
+                }
+
+                self.links.cover(column);
+
+                match self.first_allowed(self.links.down[column], column) {
+                    Some(node) => self.select(node),
+                    None => {
+                        self.links.uncover(column);
+                        self.forward = false;
+                    }
+                }
+            } else {
+                let node = self.unselect()?;
+                let column = self.links.column[node];
+
+                match self.first_allowed(self.links.down[node], column) {
+                    Some(next) => {
+                        self.select(next);
+                        self.forward = true;
+                    }
+                    None => self.links.uncover(column),
+                }
+            }
+        }
+    }
+}
+
+// A toroidal doubly-linked matrix, where node 0 is the root and nodes 1..=columns are headers
+struct Links {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    column: Vec<usize>,
+    row: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl Links {
+    fn new(columns: usize) -> Self {
+        let headers = columns + 1;
+
+        let mut links = Self {
+            left: (0..headers).map(|i| (i + headers - 1) % headers).collect(),
+            right: (0..headers).map(|i| (i + 1) % headers).collect(),
+            up: (0..headers).collect(),
+            down: (0..headers).collect(),
+            column: (0..headers).collect(),
+            row: vec![usize::MAX; headers],
+            size: vec![0; headers],
+        };
+
+        links.size[ROOT] = usize::MAX;
+        links
+    }
+
+    fn add_row(&mut self, row_id: usize, columns: &[usize]) {
+        let first = self.left.len();
+
+        for (i, &column) in columns.iter().enumerate() {
+            let node = first + i;
+
+            self.left.push(if i == 0 {
+                first + columns.len() - 1
+            } else {
+                node - 1
+            });
+            self.right.push(if i == columns.len() - 1 {
+                first
+            } else {
+                node + 1
+            });
+
+            self.up.push(self.up[column]);
+            self.down.push(column);
+            self.down[self.up[column]] = node;
+            self.up[column] = node;
+
+            self.column.push(column);
+            self.row.push(row_id);
+            self.size[column] += 1;
+        }
+    }
+
+    fn smallest_column(&self) -> usize {
+        let mut best = ROOT;
+        let mut column = self.right[ROOT];
+
+        while column != ROOT {
+            if self.size[column] < self.size[best] {
+                best = column;
+            }
+
+            column = self.right[column];
+        }
+
+        best
+    }
+
+    fn cover(&mut self, column: usize) {
+        self.right[self.left[column]] = self.right[column];
+        self.left[self.right[column]] = self.left[column];
+
+        let mut i = self.down[column];
+        while i != column {
+            let mut j = self.right[i];
+            while j != i {
+                self.down[self.up[j]] = self.down[j];
+                self.up[self.down[j]] = self.up[j];
+                self.size[self.column[j]] -= 1;
+                j = self.right[j];
+            }
+            i = self.down[i];
+        }
+    }
+
+    fn uncover(&mut self, column: usize) {
+        let mut i = self.up[column];
+        while i != column {
+            let mut j = self.left[i];
+            while j != i {
+                self.size[self.column[j]] += 1;
+                self.down[self.up[j]] = j;
+                self.up[self.down[j]] = j;
+                j = self.left[j];
+            }
+            i = self.up[i];
+        }
+
+        self.right[self.left[column]] = column;
+        self.left[self.right[column]] = column;
+    }
+
+    fn select(&mut self, node: usize) {
+        let mut j = self.right[node];
+        while j != node {
+            self.cover(self.column[j]);
+            j = self.right[j];
+        }
+    }
+
+    fn unselect(&mut self, node: usize) {
+        let mut j = self.left[node];
+        while j != node {
+            self.uncover(self.column[j]);
+            j = self.left[j];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::prelude::{BruteForceSolver, Coord, Layout};
+
+    const PUZZLE: &str =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+
+    const SOLUTION: &str =
+        "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+    // Both solvers and the iterator find the same solutions, each keeping the givens
+    fn assert_same_solutions(sudoku: &Sudoku, count: usize) {
+        let lines = |solutions: &[Sudoku]| {
+            solutions
+                .iter()
+                .map(Sudoku::to_str_line)
+                .collect::<HashSet<_>>()
+        };
+
+        let dlx = DlxSolver.solutions(sudoku).collect::<Vec<_>>();
+        let brute_force = BruteForceSolver.solutions(sudoku).collect::<Vec<_>>();
+
+        assert_eq!(dlx.len(), count);
+        assert_eq!(brute_force.len(), count);
+        assert_eq!(sudoku.solutions().count(), count);
+        assert_eq!(lines(&dlx).len(), count);
+        assert_eq!(lines(&dlx), lines(&brute_force));
+
+        for solution in &dlx {
+            assert!(solution.is_filled());
+            assert!(solution.is_valid());
+
+            for (cell, given) in solution.cells().zip(sudoku.cells()) {
+                assert!(given.digit.is_none_or(|digit| cell.digit == Some(digit)));
+            }
+        }
+    }
+
+    #[test]
+    fn solves_unique_puzzle() {
+        let mut sudoku = PUZZLE.parse::<Sudoku>().unwrap();
+
+        assert_same_solutions(&sudoku, 1);
+        assert!(DlxSolver.solve(&mut sudoku));
+        assert_eq!(sudoku.to_str_line(), SOLUTION);
+    }
+
+    #[test]
+    fn counts_agree_with_brute_force() {
+        let mut sudoku = PUZZLE.parse::<Sudoku>().unwrap();
+
+        for row in 0..2 {
+            for col in 0..9 {
+                sudoku.clear_cell(Coord(row, col));
+            }
+        }
+
+        assert_same_solutions(&sudoku, 2484);
+    }
+
+    #[test]
+    fn counts_small_grids() {
+        assert_same_solutions(&Sudoku::new_empty_with_layout(Layout::new(2)), 288);
+
+        let mut sudoku = Sudoku::new_empty_with_layout(Layout::new_rectangular(2, 3));
+        let first_row = [1, 2, 3, 4, 5, 6];
+        let first_col = [1, 4, 2, 5, 3, 6];
+
+        for (i, (&row_digit, &col_digit)) in first_row.iter().zip(&first_col).enumerate() {
+            sudoku.set_cell(Coord(0, i), Digit(row_digit));
+            sudoku.set_cell(Coord(i, 0), Digit(col_digit));
+        }
+
+        assert_same_solutions(&sudoku, 488);
+    }
+
+    #[test]
+    fn finds_no_solutions() {
+        // The givens don't repeat, but the solution has a 4 there
+        let mut sudoku = PUZZLE.parse::<Sudoku>().unwrap();
+        sudoku.set_cell(Coord(0, 2), Digit(1));
+
+        assert_same_solutions(&sudoku, 0);
+
+        // The givens repeat a 5 in the first row
+        let mut sudoku = PUZZLE.parse::<Sudoku>().unwrap();
+        sudoku.set_cell(Coord(0, 2), Digit(5));
+
+        assert_same_solutions(&sudoku, 0);
+    }
+}
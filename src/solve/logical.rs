@@ -0,0 +1,973 @@
+mod chains;
+mod coloring;
+mod fish;
+mod forcing;
+mod intersections;
+mod links;
+mod singles;
+mod subsets;
+mod uniqueness;
+mod wings;
+
+use std::fmt::Display;
+
+use crate::prelude::{Candidates, Coord, Digit, House, Layout, Sudoku};
+
+use super::Solve;
+use fish::Fins;
+
+// TODO: docs
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LogicalSolver {
+    uniqueness: bool,
+}
+
+impl LogicalSolver {
+    pub const fn new() -> Self {
+        Self { uniqueness: false }
+    }
+
+    /// Also uses the techniques that assume the puzzle has a single solution, such as Unique
+    /// Rectangles. Their deductions are only sound for puzzles verified with `Sudoku::is_unique`.
+    #[must_use]
+    pub const fn with_uniqueness(mut self) -> Self {
+        self.uniqueness = true;
+        self
+    }
+
+    // Constraints such as cages can tell the digits of a deadly pattern apart, so uniqueness
+    // techniques are left to puzzles without any
+    fn find_step(self, sudoku: &Sudoku, candidates: &[Candidates]) -> Option<Step> {
+        let uniqueness = self.uniqueness && !sudoku.has_constraints();
+
+        Technique::ALL
+            .into_iter()
+            .filter(|technique| uniqueness || !technique.assumes_uniqueness())
+            .find_map(|technique| technique.find(sudoku.layout(), candidates))
+    }
+
+    /// Finds the simplest deduction available from the sudoku's current candidates.
+    pub fn next_step(self, sudoku: &Sudoku) -> Option<Step> {
+        let candidates = sudoku.current_candidates();
+
+        if is_contradiction(sudoku, &candidates) {
+            None
+        } else {
+            self.find_step(sudoku, &candidates)
+        }
+    }
+
+    /// Applies deductions until the sudoku is solved or no technique makes progress.
+    /// When stuck, the remaining candidates are written to each empty cell's `candidates`.
+    pub fn solve_logically(self, sudoku: &mut Sudoku) -> LogicalOutcome {
+        self.solve_with_steps(sudoku).0
+    }
+
+    pub fn solve_with_steps(self, sudoku: &mut Sudoku) -> (LogicalOutcome, Vec<Step>) {
+        let mut steps = vec![];
+
+        if !sudoku.is_valid() {
+            return (LogicalOutcome::Invalid, steps);
+        }
+
+        let mut candidates = sudoku.all_candidates();
+
+        loop {
+            if sudoku.is_filled() {
+                return (LogicalOutcome::Solved, steps);
+            }
+
+            if is_contradiction(sudoku, &candidates) {
+                return (LogicalOutcome::Invalid, steps);
+            }
+
+            match self.find_step(sudoku, &candidates) {
+                Some(step) => {
+                    step.apply(sudoku, &mut candidates);
+                    steps.push(step);
+                }
+                None => {
+                    sudoku
+                        .cells_mut()
+                        .zip(candidates)
+                        .for_each(|(cell, candidates)| cell.candidates = candidates);
+
+                    return (LogicalOutcome::Stuck, steps);
+                }
+            }
+        }
+    }
+}
+
+impl Solve for LogicalSolver {
+    fn solve(self, sudoku: &mut Sudoku) -> bool {
+        self.solve_logically(sudoku) == LogicalOutcome::Solved
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogicalOutcome {
+    Solved,
+    Stuck,
+    Invalid,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Technique {
+    HiddenSingle,
+    NakedSingle,
+    PointingPair,
+    BoxLineReduction,
+    NakedPair,
+    XWing,
+    HiddenPair,
+    FinnedXWing,
+    SashimiXWing,
+    NakedTriple,
+    Swordfish,
+    HiddenTriple,
+    Skyscraper,
+    TwoStringKite,
+    XYWing,
+    FinnedSwordfish,
+    TurbotFish,
+    SashimiSwordfish,
+    XYZWing,
+    WWing,
+    SimpleColoring,
+    UniqueRectangleType1,
+    WXYZWing,
+    UniqueRectangleType2,
+    UniqueRectangleType4,
+    UniqueRectangleType5,
+    MultiColoring,
+    UniqueRectangleType3,
+    HiddenRectangle,
+    UniqueRectangleType6,
+    NakedQuad,
+    Jellyfish,
+    XChain,
+    HiddenQuad,
+    XYChain,
+    BugPlusOne,
+    FinnedJellyfish,
+    SashimiJellyfish,
+    NiceLoop,
+    AlternatingInferenceChain,
+    CellForcingChain,
+    UnitForcingChain,
+}
+
+impl Technique {
+    // Ordered by score, so that the simplest deduction is found first
+    pub const ALL: [Self; 42] = [
+        Self::HiddenSingle,
+        Self::NakedSingle,
+        Self::PointingPair,
+        Self::BoxLineReduction,
+        Self::NakedPair,
+        Self::XWing,
+        Self::HiddenPair,
+        Self::FinnedXWing,
+        Self::SashimiXWing,
+        Self::NakedTriple,
+        Self::Swordfish,
+        Self::HiddenTriple,
+        Self::Skyscraper,
+        Self::TwoStringKite,
+        Self::XYWing,
+        Self::FinnedSwordfish,
+        Self::TurbotFish,
+        Self::SashimiSwordfish,
+        Self::XYZWing,
+        Self::WWing,
+        Self::SimpleColoring,
+        Self::UniqueRectangleType1,
+        Self::WXYZWing,
+        Self::UniqueRectangleType2,
+        Self::UniqueRectangleType4,
+        Self::UniqueRectangleType5,
+        Self::MultiColoring,
+        Self::UniqueRectangleType3,
+        Self::HiddenRectangle,
+        Self::UniqueRectangleType6,
+        Self::NakedQuad,
+        Self::Jellyfish,
+        Self::XChain,
+        Self::HiddenQuad,
+        Self::XYChain,
+        Self::BugPlusOne,
+        Self::FinnedJellyfish,
+        Self::SashimiJellyfish,
+        Self::NiceLoop,
+        Self::AlternatingInferenceChain,
+        Self::CellForcingChain,
+        Self::UnitForcingChain,
+    ];
+
+    #[must_use]
+    pub const fn score(self) -> f32 {
+        match self {
+            Self::HiddenSingle => 1.5,
+            Self::NakedSingle => 2.3,
+            Self::PointingPair => 2.6,
+            Self::BoxLineReduction => 2.8,
+            Self::NakedPair => 3.0,
+            Self::XWing => 3.2,
+            Self::HiddenPair => 3.4,
+            Self::FinnedXWing => 3.5,
+            Self::SashimiXWing => 3.6,
+            Self::NakedTriple => 3.6,
+            Self::Swordfish => 3.8,
+            Self::HiddenTriple => 4.0,
+            Self::FinnedSwordfish => 4.2,
+            Self::XYWing => 4.2,
+            Self::SashimiSwordfish => 4.3,
+            Self::XYZWing => 4.4,
+            Self::WWing => 4.5,
+            Self::WXYZWing => 4.6,
+            Self::Skyscraper => 4.0,
+            Self::TwoStringKite => 4.1,
+            Self::TurbotFish => 4.2,
+            Self::SimpleColoring => 4.5,
+            Self::MultiColoring => 4.8,
+            Self::XChain => 5.3,
+            Self::XYChain => 5.5,
+            Self::NiceLoop => 5.8,
+            Self::AlternatingInferenceChain => 6.0,
+            Self::CellForcingChain => 7.0,
+            Self::UnitForcingChain => 7.2,
+            Self::UniqueRectangleType1 => 4.5,
+            Self::UniqueRectangleType2 | Self::UniqueRectangleType4 => 4.6,
+            Self::UniqueRectangleType5 => 4.7,
+            Self::UniqueRectangleType3 | Self::HiddenRectangle => 4.8,
+            Self::UniqueRectangleType6 => 4.9,
+            Self::BugPlusOne => 5.6,
+            Self::NakedQuad => 5.0,
+            Self::Jellyfish => 5.2,
+            Self::HiddenQuad => 5.4,
+            Self::FinnedJellyfish => 5.6,
+            Self::SashimiJellyfish => 5.7,
+        }
+    }
+
+    // Deductions that would otherwise leave the puzzle with several solutions, which only hold
+    // for puzzles with a single one
+    #[must_use]
+    pub const fn assumes_uniqueness(self) -> bool {
+        matches!(
+            self,
+            Self::UniqueRectangleType1
+                | Self::UniqueRectangleType2
+                | Self::UniqueRectangleType3
+                | Self::UniqueRectangleType4
+                | Self::UniqueRectangleType5
+                | Self::UniqueRectangleType6
+                | Self::HiddenRectangle
+                | Self::BugPlusOne
+        )
+    }
+
+    pub(crate) fn find(self, layout: &Layout, candidates: &[Candidates]) -> Option<Step> {
+        match self {
+            Self::HiddenSingle => singles::hidden_single(layout, candidates),
+            Self::NakedSingle => singles::naked_single(layout, candidates),
+            Self::PointingPair => intersections::pointing(layout, candidates),
+            Self::BoxLineReduction => intersections::box_line_reduction(layout, candidates),
+            Self::NakedPair => subsets::naked_subset(layout, candidates, self, 2),
+            Self::HiddenPair => subsets::hidden_subset(layout, candidates, self, 2),
+            Self::NakedTriple => subsets::naked_subset(layout, candidates, self, 3),
+            Self::HiddenTriple => subsets::hidden_subset(layout, candidates, self, 3),
+            Self::NakedQuad => subsets::naked_subset(layout, candidates, self, 4),
+            Self::HiddenQuad => subsets::hidden_subset(layout, candidates, self, 4),
+            Self::XWing => fish::fish(layout, candidates, self, 2, Fins::None),
+            Self::Swordfish => fish::fish(layout, candidates, self, 3, Fins::None),
+            Self::Jellyfish => fish::fish(layout, candidates, self, 4, Fins::None),
+            Self::FinnedXWing => fish::fish(layout, candidates, self, 2, Fins::Finned),
+            Self::FinnedSwordfish => fish::fish(layout, candidates, self, 3, Fins::Finned),
+            Self::FinnedJellyfish => fish::fish(layout, candidates, self, 4, Fins::Finned),
+            Self::SashimiXWing => fish::fish(layout, candidates, self, 2, Fins::Sashimi),
+            Self::SashimiSwordfish => fish::fish(layout, candidates, self, 3, Fins::Sashimi),
+            Self::SashimiJellyfish => fish::fish(layout, candidates, self, 4, Fins::Sashimi),
+            Self::XYWing => wings::xy_wing(layout, candidates),
+            Self::XYZWing => wings::xyz_wing(layout, candidates),
+            Self::WWing => wings::w_wing(layout, candidates),
+            Self::WXYZWing => wings::wxyz_wing(layout, candidates),
+            Self::Skyscraper | Self::TwoStringKite | Self::TurbotFish => {
+                chains::turbot_fish(layout, candidates, self)
+            }
+            Self::SimpleColoring => coloring::simple_coloring(layout, candidates),
+            Self::MultiColoring => coloring::multi_coloring(layout, candidates),
+            Self::XChain => chains::x_chain(layout, candidates),
+            Self::XYChain => chains::xy_chain(layout, candidates),
+            Self::NiceLoop => chains::nice_loop(layout, candidates),
+            Self::AlternatingInferenceChain => {
+                chains::alternating_inference_chain(layout, candidates)
+            }
+            Self::CellForcingChain => forcing::cell_forcing_chain(layout, candidates),
+            Self::UnitForcingChain => forcing::unit_forcing_chain(layout, candidates),
+            Self::UniqueRectangleType1 => uniqueness::type_1(layout, candidates),
+            Self::UniqueRectangleType2 => uniqueness::type_2(layout, candidates),
+            Self::UniqueRectangleType3 => uniqueness::type_3(layout, candidates),
+            Self::UniqueRectangleType4 => uniqueness::type_4(layout, candidates),
+            Self::UniqueRectangleType5 => uniqueness::type_5(layout, candidates),
+            Self::UniqueRectangleType6 => uniqueness::type_6(layout, candidates),
+            Self::HiddenRectangle => uniqueness::hidden_rectangle(layout, candidates),
+            Self::BugPlusOne => uniqueness::bug_plus_one(layout, candidates),
+        }
+    }
+}
+
+impl Display for Technique {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::HiddenSingle => "Hidden Single",
+            Self::NakedSingle => "Naked Single",
+            Self::PointingPair => "Pointing Pair",
+            Self::BoxLineReduction => "Box/Line Reduction",
+            Self::NakedPair => "Naked Pair",
+            Self::HiddenPair => "Hidden Pair",
+            Self::NakedTriple => "Naked Triple",
+            Self::HiddenTriple => "Hidden Triple",
+            Self::NakedQuad => "Naked Quad",
+            Self::HiddenQuad => "Hidden Quad",
+            Self::XWing => "X-Wing",
+            Self::Swordfish => "Swordfish",
+            Self::Jellyfish => "Jellyfish",
+            Self::FinnedXWing => "Finned X-Wing",
+            Self::FinnedSwordfish => "Finned Swordfish",
+            Self::FinnedJellyfish => "Finned Jellyfish",
+            Self::SashimiXWing => "Sashimi X-Wing",
+            Self::SashimiSwordfish => "Sashimi Swordfish",
+            Self::SashimiJellyfish => "Sashimi Jellyfish",
+            Self::XYWing => "XY-Wing",
+            Self::XYZWing => "XYZ-Wing",
+            Self::WWing => "W-Wing",
+            Self::WXYZWing => "WXYZ-Wing",
+            Self::Skyscraper => "Skyscraper",
+            Self::TwoStringKite => "2-String Kite",
+            Self::TurbotFish => "Turbot Fish",
+            Self::SimpleColoring => "Simple Coloring",
+            Self::MultiColoring => "Multi-Coloring",
+            Self::XChain => "X-Chain",
+            Self::XYChain => "XY-Chain",
+            Self::NiceLoop => "Nice Loop",
+            Self::AlternatingInferenceChain => "Alternating Inference Chain",
+            Self::CellForcingChain => "Cell Forcing Chain",
+            Self::UnitForcingChain => "Unit Forcing Chain",
+            Self::UniqueRectangleType1 => "Unique Rectangle Type 1",
+            Self::UniqueRectangleType2 => "Unique Rectangle Type 2",
+            Self::UniqueRectangleType3 => "Unique Rectangle Type 3",
+            Self::UniqueRectangleType4 => "Unique Rectangle Type 4",
+            Self::UniqueRectangleType5 => "Unique Rectangle Type 5",
+            Self::UniqueRectangleType6 => "Unique Rectangle Type 6",
+            Self::HiddenRectangle => "Hidden Rectangle",
+            Self::BugPlusOne => "BUG+1",
+        })
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Techniques(u64);
+
+impl Techniques {
+    #[must_use]
+    #[inline]
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    #[must_use]
+    pub fn all() -> Self {
+        Technique::ALL.into_iter().collect()
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn with(self, technique: Technique) -> Self {
+        Self(self.0 | Self::bit(technique))
+    }
+
+    pub const fn insert(&mut self, technique: Technique) {
+        self.0 |= Self::bit(technique);
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn contains(self, technique: Technique) -> bool {
+        self.0 & Self::bit(technique) != 0
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn contains_all(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn iter(self) -> impl Iterator<Item = Technique> {
+        Technique::ALL
+            .into_iter()
+            .filter(move |technique| self.contains(*technique))
+    }
+
+    const fn bit(technique: Technique) -> u64 {
+        1 << technique as u64
+    }
+}
+
+impl FromIterator<Technique> for Techniques {
+    fn from_iter<T: IntoIterator<Item = Technique>>(iter: T) -> Self {
+        iter.into_iter().fold(Self::empty(), Self::with)
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub technique: Technique,
+    pub houses: Vec<House>,
+    pub cells: Vec<Coord>,
+    pub placements: Vec<(Coord, Digit)>,
+    pub eliminations: Vec<(Coord, Digit)>,
+    pub pattern: Option<Pattern>,
+}
+
+// The parts of a deduction that its houses and cells alone don't tell apart, for drawing it
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Pattern {
+    // The digit is confined to the cover houses within the base houses, apart from the fins
+    Fish {
+        digit: Digit,
+        base: Vec<House>,
+        cover: Vec<House>,
+        fins: Vec<Coord>,
+    },
+    // One of the pincers holds the digit whatever the pivot takes. The pivot is a single cell,
+    // except for W-Wings, where it's the strong link joining the pincers.
+    Wing {
+        digit: Digit,
+        pivot: Vec<Coord>,
+        pincers: Vec<Coord>,
+    },
+    // Clusters of cells joined by strong links on the digit, split in two colors: within each
+    // cluster, either every cell of one color holds the digit or every cell of the other does
+    Coloring {
+        digit: Digit,
+        clusters: Vec<[Vec<Coord>; 2]>,
+    },
+    // Each candidate of the chain along with the link leading to it. The first one's is the link
+    // closing a loop, or the weak link from the eliminated candidates for a chain with two ends.
+    Chain {
+        nodes: Vec<ChainNode>,
+        is_loop: bool,
+    },
+    // The corners of a rectangle that can't be left with its two digits alone
+    Rectangle {
+        digits: Vec<Digit>,
+        corners: Vec<Coord>,
+    },
+    // A chain from each assumption to the same conclusion, one of the assumptions being true
+    Forcing {
+        chains: Vec<Vec<ChainNode>>,
+    },
+}
+
+pub type ChainNode = (Coord, Digit, Link);
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Link {
+    // At least one of the candidates is true
+    Strong,
+    // At most one of the candidates is true
+    Weak,
+}
+
+impl Step {
+    fn placement(
+        layout: &Layout,
+        technique: Technique,
+        houses: Vec<House>,
+        index: usize,
+        digit: Digit,
+    ) -> Self {
+        let coord = layout.coord(index);
+
+        Self {
+            technique,
+            houses,
+            cells: vec![coord],
+            placements: vec![(coord, digit)],
+            eliminations: vec![],
+            pattern: None,
+        }
+    }
+
+    fn elimination(
+        layout: &Layout,
+        technique: Technique,
+        houses: Vec<House>,
+        cells: &[usize],
+        eliminations: Vec<(usize, Digit)>,
+    ) -> Option<Self> {
+        if eliminations.is_empty() {
+            None
+        } else {
+            Some(Self {
+                technique,
+                houses,
+                cells: cells.iter().map(|&i| layout.coord(i)).collect(),
+                placements: vec![],
+                eliminations: eliminations
+                    .into_iter()
+                    .map(|(i, digit)| (layout.coord(i), digit))
+                    .collect(),
+                pattern: None,
+            })
+        }
+    }
+
+    fn with_pattern(mut self, pattern: Pattern) -> Self {
+        self.pattern = Some(pattern);
+        self
+    }
+
+    // Hidden singles within a box are easier to spot than within a line
+    #[must_use]
+    pub fn score(&self) -> f32 {
+        match (self.technique, self.houses.as_slice()) {
+            (Technique::HiddenSingle, [House::Square(_)]) => 1.2,
+            (technique, _) => technique.score(),
+        }
+    }
+
+    pub(crate) fn apply(&self, sudoku: &mut Sudoku, candidates: &mut [Candidates]) {
+        let layout = sudoku.layout().clone();
+
+        for &(coord, digit) in &self.placements {
+            sudoku.set_cell(coord, digit);
+            candidates[layout.index(coord)] = Candidates::empty();
+
+            for &i in layout.peers(coord) {
+                candidates[i].remove(digit);
+            }
+        }
+
+        for &(coord, digit) in &self.eliminations {
+            candidates[layout.index(coord)].remove(digit);
+        }
+
+        // Constraints such as cages prune further as digits are placed, just like `all_candidates`
+        for constraint in sudoku.constraints() {
+            constraint.restrict_candidates(sudoku, candidates);
+        }
+    }
+}
+
+impl Display for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.technique)?;
+
+        match &self.pattern {
+            Some(Pattern::Fish {
+                digit,
+                base,
+                cover,
+                fins,
+            }) => {
+                write!(f, " on {digit} in ")?;
+                write_list(f, base)?;
+                f.write_str(" / ")?;
+                write_list(f, cover)?;
+
+                if !fins.is_empty() {
+                    f.write_str(" with fins ")?;
+                    write_list(f, &coords(fins))?;
+                }
+            }
+            Some(Pattern::Wing {
+                digit,
+                pivot,
+                pincers,
+            }) => {
+                write!(f, " on {digit} with pivot ")?;
+                write_list(f, &coords(pivot))?;
+                f.write_str(" and pincers ")?;
+                write_list(f, &coords(pincers))?;
+            }
+            Some(Pattern::Coloring { digit, clusters }) => {
+                write!(f, " on {digit} with colors ")?;
+
+                for (i, [a, b]) in clusters.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(" and ")?;
+                    }
+
+                    write_list(f, &coords(a))?;
+                    f.write_str(" / ")?;
+                    write_list(f, &coords(b))?;
+                }
+            }
+            Some(Pattern::Chain { nodes, is_loop }) => {
+                f.write_str(" ")?;
+
+                // Loops end where they start, through the link leading to their first candidate
+                if *is_loop {
+                    let closed = nodes
+                        .iter()
+                        .chain(nodes.first())
+                        .copied()
+                        .collect::<Vec<_>>();
+                    write_chain(f, &closed)?;
+                } else {
+                    write_chain(f, nodes)?;
+                }
+            }
+            Some(Pattern::Rectangle { digits, corners }) => {
+                f.write_str(" on ")?;
+                write_list(f, digits)?;
+                f.write_str(" in ")?;
+                write_list(f, &coords(corners))?;
+            }
+            Some(Pattern::Forcing { chains }) => {
+                for (i, chain) in chains.iter().enumerate() {
+                    f.write_str(if i == 0 { " " } else { " | " })?;
+                    write_chain(f, chain)?;
+                }
+            }
+            None if !self.houses.is_empty() => {
+                f.write_str(" in ")?;
+                write_list(f, &self.houses)?;
+            }
+            None => {}
+        }
+
+        f.write_str(":")?;
+
+        for (Coord(row, col), digit) in &self.placements {
+            write!(f, " r{}c{}={digit}", row + 1, col + 1)?;
+        }
+
+        for (Coord(row, col), digit) in &self.eliminations {
+            write!(f, " r{}c{}<>{digit}", row + 1, col + 1)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn write_list(f: &mut std::fmt::Formatter<'_>, items: &[impl Display]) -> std::fmt::Result {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+
+        write!(f, "{item}")?;
+    }
+
+    Ok(())
+}
+
+// In Eureka notation, with `=` for strong links and `-` for weak ones
+fn write_chain(f: &mut std::fmt::Formatter<'_>, nodes: &[ChainNode]) -> std::fmt::Result {
+    for (i, &(Coord(row, col), digit, link)) in nodes.iter().enumerate() {
+        if i > 0 {
+            f.write_str(if link == Link::Strong { "=" } else { "-" })?;
+        }
+
+        write!(f, "({digit})r{}c{}", row + 1, col + 1)?;
+    }
+
+    Ok(())
+}
+
+fn coords(coords: &[Coord]) -> Vec<String> {
+    coords
+        .iter()
+        .map(|Coord(row, col)| format!("r{}c{}", row + 1, col + 1))
+        .collect()
+}
+
+fn sees(layout: &Layout, i: usize, j: usize) -> bool {
+    layout.peers(i).binary_search(&j).is_ok()
+}
+
+// The cells outside `cells` that have the digit and see every one of them
+fn common_peer_eliminations(
+    layout: &Layout,
+    candidates: &[Candidates],
+    cells: &[usize],
+    digit: Digit,
+) -> Vec<(usize, Digit)> {
+    layout
+        .peers(cells[0])
+        .iter()
+        .copied()
+        .filter(|&i| {
+            candidates[i].contains(digit)
+                && !cells.contains(&i)
+                && cells.iter().all(|&cell| sees(layout, i, cell))
+        })
+        .map(|i| (i, digit))
+        .collect()
+}
+
+pub fn is_contradiction(sudoku: &Sudoku, candidates: &[Candidates]) -> bool {
+    let layout = sudoku.layout();
+
+    let empty_cell_without_candidates = sudoku
+        .cells()
+        .zip(candidates)
+        .any(|(cell, candidates)| cell.digit.is_none() && candidates.is_empty());
+
+    empty_cell_without_candidates
+        || layout.houses().any(|house| {
+            layout.digits().any(|digit| {
+                layout.house_indices(house).iter().all(|&i| {
+                    sudoku.cell(i).unwrap().digit != Some(digit) && !candidates[i].contains(digit)
+                })
+            })
+        })
+}
+
+fn combinations<T: Copy>(items: &[T], size: usize) -> Vec<Vec<T>> {
+    if size == 0 {
+        return vec![vec![]];
+    }
+
+    let mut result = vec![];
+
+    for (i, &item) in items.iter().enumerate() {
+        for mut rest in combinations(&items[i + 1..], size - 1) {
+            rest.insert(0, item);
+            result.push(rest);
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::prelude::{Generate, Killer, SudokuConfig};
+
+    // Between them, these call for every technique at some point, apart from the rare Unique
+    // Rectangle Type 5
+    const FIXTURES: [&str; 2] = [
+        "002089000400020700980005200000008400050003000000000103010000060000000809700400000",
+        "400000201800201000030500000000800000070003060002040890640090002000000400000000700",
+    ];
+
+    // Enough steps of each technique to check, without searching for the slower ones all the way
+    const STEPS_PER_TECHNIQUE: usize = 3;
+
+    // Walks through the solver's steps, looking for every technique along the way. Each step found
+    // has to agree with the solution: its placements are the solution's digits, and its
+    // eliminations aren't.
+    #[test]
+    fn steps_agree_with_solution() {
+        let mut found = HashMap::<Technique, usize>::new();
+
+        for fixture in FIXTURES {
+            let puzzle = fixture.parse::<Sudoku>().unwrap();
+            let solution = puzzle.solutions().next().unwrap();
+            let digit = |coord: Coord| solution.cell(coord).unwrap().digit.unwrap();
+
+            let mut sudoku = puzzle.clone();
+            let (outcome, steps) = LogicalSolver::new()
+                .with_uniqueness()
+                .solve_with_steps(&mut sudoku);
+
+            assert_eq!(outcome, LogicalOutcome::Solved);
+            assert_eq!(sudoku, solution);
+
+            let mut sudoku = puzzle;
+            let mut candidates = sudoku.all_candidates();
+
+            for step in steps {
+                for technique in Technique::ALL {
+                    if found.get(&technique).copied().unwrap_or_default() >= STEPS_PER_TECHNIQUE {
+                        continue;
+                    }
+
+                    let Some(step) = technique.find(sudoku.layout(), &candidates) else {
+                        continue;
+                    };
+
+                    assert_eq!(step.technique, technique);
+                    assert!(!step.placements.is_empty() || !step.eliminations.is_empty());
+
+                    for &(coord, placed) in &step.placements {
+                        assert_eq!(placed, digit(coord), "{step}");
+                    }
+
+                    for &(coord, eliminated) in &step.eliminations {
+                        assert!(candidates[sudoku.layout().index(coord)].contains(eliminated));
+                        assert_ne!(eliminated, digit(coord), "{step}");
+                    }
+
+                    *found.entry(technique).or_default() += 1;
+                }
+
+                step.apply(&mut sudoku, &mut candidates);
+            }
+        }
+
+        for technique in Technique::ALL {
+            if technique != Technique::UniqueRectangleType5 {
+                assert!(found.contains_key(&technique), "{technique}");
+            }
+        }
+    }
+
+    #[test]
+    fn solves_without_guessing() {
+        let puzzle =
+            "530070000600195000098000060800060003400803001700020006060000280000419005000080079"
+                .parse::<Sudoku>()
+                .unwrap();
+
+        let step = LogicalSolver::new().next_step(&puzzle).unwrap();
+        assert!(matches!(
+            step.technique,
+            Technique::HiddenSingle | Technique::NakedSingle
+        ));
+
+        let mut sudoku = puzzle.clone();
+        assert_eq!(
+            LogicalSolver::new().solve_logically(&mut sudoku),
+            LogicalOutcome::Solved
+        );
+        assert_eq!(Some(sudoku), puzzle.solutions().next());
+    }
+
+    // Solving in one go and applying one hint at a time both narrow candidates with the cages. The
+    // givens kept aren't enough without them, but spare searching for chains.
+    #[test]
+    fn steps_agree_on_killers() {
+        for seed in 0..4 {
+            let puzzle = Killer.generate(SudokuConfig::new(24).with_seed(seed));
+
+            let mut solved = puzzle.clone();
+            assert_eq!(
+                LogicalSolver::new().solve_logically(&mut solved),
+                LogicalOutcome::Solved
+            );
+            assert_eq!(Some(&solved), puzzle.solutions().next().as_ref());
+
+            let mut stepped = puzzle.clone();
+
+            while let Some(step) = stepped.next_step() {
+                stepped.apply_step(&step);
+            }
+
+            assert_eq!(stepped, solved);
+
+            let mut without_cages = puzzle;
+            without_cages.clear_cages();
+            assert!(!without_cages.is_unique());
+        }
+    }
+
+    #[test]
+    fn reports_stuck_and_invalid() {
+        // Clearing a deadly pattern leaves two solutions, which no deduction can tell apart
+        let mut sudoku =
+            "534678912672195348198342567859761423426853791713924856961537284287419635345286179"
+                .parse::<Sudoku>()
+                .unwrap();
+
+        let corners = [Coord(3, 5), Coord(3, 8), Coord(4, 5), Coord(4, 8)];
+
+        for coord in corners {
+            sudoku.clear_cell(coord);
+        }
+
+        assert_eq!(
+            LogicalSolver::new().solve_logically(&mut sudoku),
+            LogicalOutcome::Stuck
+        );
+
+        for coord in corners {
+            let candidates = sudoku.cell(coord).unwrap().candidates;
+            assert_eq!(
+                candidates,
+                Candidates::from(Digit(1)).union(Digit(3).into())
+            );
+        }
+
+        sudoku.set_cell(Coord(3, 5), Digit(5));
+        assert_eq!(
+            LogicalSolver::new().solve_logically(&mut sudoku),
+            LogicalOutcome::Invalid
+        );
+    }
+
+    // Every digit in every cell, apart from the cells listed, which keep their own. Lays out a
+    // pattern for a technique without a puzzle leading up to it.
+    pub(super) fn candidates_with(cells: &[(Coord, &[u8])]) -> Vec<Candidates> {
+        let layout = Layout::classic();
+        let mut candidates = vec![layout.candidates(); layout.grid_size()];
+
+        for &(coord, digits) in cells {
+            candidates[layout.index(coord)] =
+                digits.iter().fold(Candidates::empty(), |acc, &digit| {
+                    acc.union(Digit(digit).into())
+                });
+        }
+
+        candidates
+    }
+
+    // Every digit in every cell, apart from the digit in the houses listed, which only keep it in
+    // their two cells. Each house then strongly links its cells.
+    pub(super) fn candidates_linked(digit: u8, links: &[(House, [Coord; 2])]) -> Vec<Candidates> {
+        let layout = Layout::classic();
+        let mut candidates = candidates_with(&[]);
+
+        for &(house, cells) in links {
+            for &i in layout.house_indices(house) {
+                if !cells.contains(&layout.coord(i)) {
+                    candidates[i].remove(Digit(digit));
+                }
+            }
+        }
+
+        candidates
+    }
+
+    // Looks for the technique alone, as the solver only gets to the later ones after searching
+    // the whole grid for every fish first
+    pub(super) fn find(technique: Technique, candidates: &[Candidates]) -> Option<Step> {
+        technique.find(&Layout::classic(), candidates)
+    }
+
+    // The step the solver takes first on an empty grid whose cells hold the candidates
+    pub(super) fn first_step(candidates: &[Candidates], solver: LogicalSolver) -> Option<Step> {
+        let mut sudoku = Sudoku::new_empty();
+
+        for (cell, &candidates) in sudoku.cells_mut().zip(candidates) {
+            cell.candidates = candidates;
+        }
+
+        solver.next_step(&sudoku)
+    }
+}
@@ -0,0 +1,573 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::prelude::{Candidates, House, Layout};
+
+use super::{
+    common_peer_eliminations,
+    links::{Links, Node},
+    sees, Link, Pattern, Step, Technique,
+};
+
+// Two strong links on a digit, with an end of one seeing an end of the other. At most one of those
+// two ends holds the digit, so one of the far ends does, and it can be removed from the cells
+// seeing both far ends. Skyscrapers join two parallel lines, 2-String Kites join a row and a column
+// within a box, and Turbot Fish are whatever else is left, such as links within boxes.
+pub(super) fn turbot_fish(
+    layout: &Layout,
+    candidates: &[Candidates],
+    technique: Technique,
+) -> Option<Step> {
+    for digit in layout.digits() {
+        let pairs = layout
+            .houses()
+            .filter_map(|house| {
+                let cells = layout
+                    .house_indices(house)
+                    .iter()
+                    .copied()
+                    .filter(|&i| candidates[i].contains(digit))
+                    .collect::<Vec<_>>();
+
+                match cells[..] {
+                    [a, b] => Some((house, [a, b])),
+                    _ => None,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        for (n, &(first, [a1, a2])) in pairs.iter().enumerate() {
+            for &(second, [b1, b2]) in &pairs[n + 1..] {
+                for ([near_a, far_a], [near_b, far_b]) in [
+                    ([a1, a2], [b1, b2]),
+                    ([a1, a2], [b2, b1]),
+                    ([a2, a1], [b1, b2]),
+                    ([a2, a1], [b2, b1]),
+                ] {
+                    if [near_b, far_b].iter().any(|i| [a1, a2].contains(i))
+                        || !sees(layout, near_a, near_b)
+                        || turbot_kind(layout, [first, second], [near_a, near_b]) != technique
+                    {
+                        continue;
+                    }
+
+                    let eliminations =
+                        common_peer_eliminations(layout, candidates, &[far_a, far_b], digit);
+
+                    let chain = [far_a, near_a, near_b, far_b].map(|i| (i, digit));
+
+                    if let Some(step) = chain_step(
+                        layout,
+                        technique,
+                        vec![first, second],
+                        &chain,
+                        None,
+                        eliminations,
+                    ) {
+                        return Some(step);
+                    }
+                }
+            }
+        }
+    }
+
+    None
+}
+
+fn turbot_kind(layout: &Layout, houses: [House; 2], [a, b]: [usize; 2]) -> Technique {
+    let (coord_a, coord_b) = (layout.coord(a), layout.coord(b));
+
+    let same_box = || {
+        layout
+            .houses_of_cell(a)
+            .filter(|house| matches!(house, House::Square(_)))
+            .any(|square| layout.house_indices(square).contains(&b))
+    };
+
+    match houses {
+        [House::Row(_), House::Row(_)] if coord_a.1 == coord_b.1 => Technique::Skyscraper,
+        [House::Col(_), House::Col(_)] if coord_a.0 == coord_b.0 => Technique::Skyscraper,
+        [House::Row(_), House::Col(_)] | [House::Col(_), House::Row(_)] if same_box() => {
+            Technique::TwoStringKite
+        }
+        _ => Technique::TurbotFish,
+    }
+}
+
+// A chain of alternating strong and weak links on a digit, starting and ending with a strong link.
+// If the first cell doesn't hold the digit, the last one does, so it can be removed from the cells
+// seeing both ends.
+pub(super) fn x_chain(layout: &Layout, candidates: &[Candidates]) -> Option<Step> {
+    layout.digits().find_map(|digit| {
+        let links = Links::of_digit(layout, candidates, digit);
+
+        links
+            .nodes()
+            .find_map(|start| open_chain(layout, &links, start, Technique::XChain, |_, _, _| true))
+    })
+}
+
+// An alternating chain through cells with two candidates, strongly linked within each cell and
+// weakly linked between cells on a shared digit
+pub(super) fn xy_chain(layout: &Layout, candidates: &[Candidates]) -> Option<Step> {
+    let links = Links::new(layout, candidates);
+    let is_bivalue = |(i, _): Node| candidates[i].count() == 2;
+
+    links
+        .nodes()
+        .filter(|&node| is_bivalue(node))
+        .find_map(|start| {
+            open_chain(
+                layout,
+                &links,
+                start,
+                Technique::XYChain,
+                |(i, _), next, link| is_bivalue(next) && (link == Link::Strong) == (i == next.0),
+            )
+        })
+}
+
+// An alternating chain of any candidates, linked through houses and cells alike
+pub(super) fn alternating_inference_chain(
+    layout: &Layout,
+    candidates: &[Candidates],
+) -> Option<Step> {
+    let links = Links::new(layout, candidates);
+
+    links.nodes().find_map(|start| {
+        open_chain(
+            layout,
+            &links,
+            start,
+            Technique::AlternatingInferenceChain,
+            |_, _, _| true,
+        )
+    })
+}
+
+// An alternating chain leading back to the candidate it starts from, assumed false. Arriving
+// through a strong link, the candidate must be true after all. Arriving through a weak link, the
+// loop is continuous, and exactly one end of each of its links is true: the candidates weakly
+// linked to both ends of a weak link of the loop can be removed.
+pub(super) fn nice_loop(layout: &Layout, candidates: &[Candidates]) -> Option<Step> {
+    let links = Links::new(layout, candidates);
+
+    links.nodes().find_map(|start| {
+        search_chains(
+            &links,
+            start,
+            |_, _, _| true,
+            |chain| {
+                let (&end, nodes) = chain.split_last()?;
+
+                if end != start {
+                    return None;
+                }
+
+                // Odd loops close with a strong link, as links alternate from the start
+                if nodes.len() % 2 == 1 {
+                    let (index, digit) = start;
+                    let mut step =
+                        Step::placement(layout, Technique::NiceLoop, vec![], index, digit);
+                    step.cells = nodes.iter().map(|&(i, _)| layout.coord(i)).collect();
+                    return Some(step.with_pattern(chain_pattern(
+                        layout,
+                        nodes,
+                        Some(Link::Strong),
+                    )));
+                }
+
+                if nodes.len() < 4 {
+                    return None;
+                }
+
+                let mut eliminations = vec![];
+
+                for k in (1..nodes.len()).step_by(2) {
+                    let (a, b) = (nodes[k], nodes[(k + 1) % nodes.len()]);
+
+                    for node in links.weak(a).filter(|&node| links.is_weak(node, b)) {
+                        if !eliminations.contains(&node) {
+                            eliminations.push(node);
+                        }
+                    }
+                }
+
+                chain_step(
+                    layout,
+                    Technique::NiceLoop,
+                    vec![],
+                    nodes,
+                    Some(Link::Weak),
+                    eliminations,
+                )
+            },
+        )
+    })
+}
+
+// Searches for a chain from the start, assumed false, to a true candidate. One of its ends is then
+// true, so the candidates weakly linked to both can be removed.
+fn open_chain(
+    layout: &Layout,
+    links: &Links<'_>,
+    start: Node,
+    technique: Technique,
+    follow: impl Fn(Node, Node, Link) -> bool,
+) -> Option<Step> {
+    search_chains(links, start, follow, |chain| {
+        let &end = chain.last()?;
+
+        // Shorter chains are single links, which other techniques cover
+        if chain.len() < 4 || end == start {
+            return None;
+        }
+
+        let eliminations = links
+            .weak(start)
+            .filter(|&node| links.is_weak(node, end))
+            .collect();
+
+        chain_step(layout, technique, vec![], chain, None, eliminations)
+    })
+}
+
+// Follows the chains of alternating links from a candidate assumed false, shortest first: strong
+// links lead to candidates that are then true, and weak links to candidates that are then false.
+// Calls `found` with every chain reaching a true candidate, or leading back to the start, until it
+// returns a step. `follow` tells which links chains may take, and chains never run into themselves.
+fn search_chains(
+    links: &Links<'_>,
+    start: Node,
+    follow: impl Fn(Node, Node, Link) -> bool,
+    mut found: impl FnMut(&[Node]) -> Option<Step>,
+) -> Option<Step> {
+    let mut previous = HashMap::from([(start, start)]);
+    let mut queue = VecDeque::from([(start, false)]);
+
+    while let Some((node, is_on)) = queue.pop_front() {
+        let (next, link) = if is_on {
+            (links.weak(node).collect::<Vec<_>>(), Link::Weak)
+        } else {
+            (links.strong(node).to_vec(), Link::Strong)
+        };
+
+        for next in next {
+            if !follow(node, next, link) {
+                continue;
+            }
+
+            if next == start {
+                let mut chain = trace(&previous, start, node);
+                chain.push(start);
+
+                if let Some(step) = found(&chain) {
+                    return Some(step);
+                }
+
+                continue;
+            }
+
+            if previous.contains_key(&next) {
+                continue;
+            }
+
+            previous.insert(next, node);
+
+            if !is_on {
+                if let Some(step) = found(&trace(&previous, start, next)) {
+                    return Some(step);
+                }
+            }
+
+            queue.push_back((next, !is_on));
+        }
+    }
+
+    None
+}
+
+// The candidates from the start of a chain to its end, following the previous candidate of each
+fn trace(previous: &HashMap<Node, Node>, start: Node, end: Node) -> Vec<Node> {
+    let mut chain = vec![end];
+
+    while let Some(&last) = chain.last().filter(|&&last| last != start) {
+        chain.push(previous[&last]);
+    }
+
+    chain.reverse();
+    chain
+}
+
+// Chains alternate links from a weak one, or from the link closing them for loops
+fn chain_step(
+    layout: &Layout,
+    technique: Technique,
+    houses: Vec<House>,
+    chain: &[Node],
+    closing: Option<Link>,
+    eliminations: Vec<Node>,
+) -> Option<Step> {
+    let cells = chain.iter().map(|&(i, _)| i).collect::<Vec<_>>();
+
+    Step::elimination(layout, technique, houses, &cells, eliminations)
+        .map(|step| step.with_pattern(chain_pattern(layout, chain, closing)))
+}
+
+fn chain_pattern(layout: &Layout, chain: &[Node], closing: Option<Link>) -> Pattern {
+    Pattern::Chain {
+        nodes: chain
+            .iter()
+            .enumerate()
+            .map(|(k, &(i, digit))| {
+                let link = match k {
+                    0 => closing.unwrap_or(Link::Weak),
+                    _ if k % 2 == 1 => Link::Strong,
+                    _ => Link::Weak,
+                };
+
+                (layout.coord(i), digit, link)
+            })
+            .collect(),
+        is_loop: closing.is_some(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{Coord, Digit};
+    use crate::solve::logical::tests::{candidates_linked, candidates_with, find};
+
+    fn eliminations(coords: &[Coord]) -> Vec<(Coord, Digit)> {
+        coords.iter().map(|&coord| (coord, Digit(1))).collect()
+    }
+
+    // Alternates links from a weak one, as open chains and continuous loops do
+    fn chain(nodes: &[(Coord, u8)], is_loop: bool) -> Option<Pattern> {
+        Some(Pattern::Chain {
+            nodes: nodes
+                .iter()
+                .enumerate()
+                .map(|(k, &(coord, digit))| {
+                    let link = if k % 2 == 1 { Link::Strong } else { Link::Weak };
+                    (coord, Digit(digit), link)
+                })
+                .collect(),
+            is_loop,
+        })
+    }
+
+    fn chain_of_1(coords: &[Coord]) -> Option<Pattern> {
+        let nodes = coords.iter().map(|&coord| (coord, 1)).collect::<Vec<_>>();
+        chain(&nodes, false)
+    }
+
+    // r2c1 and r2c5 see each other, so r7c1 or r8c5 holds 1
+    #[test]
+    fn finds_skyscraper() {
+        let candidates = candidates_linked(
+            1,
+            &[
+                (House::Col(0), [Coord(1, 0), Coord(6, 0)]),
+                (House::Col(4), [Coord(1, 4), Coord(7, 4)]),
+            ],
+        );
+        let step = find(Technique::Skyscraper, &candidates).unwrap();
+
+        assert_eq!(
+            step.eliminations,
+            eliminations(&[Coord(6, 3), Coord(6, 5), Coord(7, 1), Coord(7, 2)])
+        );
+        assert_eq!(step.houses, [House::Col(0), House::Col(4)]);
+        assert_eq!(
+            step.pattern,
+            chain_of_1(&[Coord(6, 0), Coord(1, 0), Coord(1, 4), Coord(7, 4)])
+        );
+    }
+
+    // r1c2 and r3c1 share a box, so r1c7 or r8c1 holds 1
+    #[test]
+    fn finds_two_string_kite() {
+        let candidates = candidates_linked(
+            1,
+            &[
+                (House::Row(0), [Coord(0, 1), Coord(0, 6)]),
+                (House::Col(0), [Coord(2, 0), Coord(7, 0)]),
+            ],
+        );
+        assert_eq!(find(Technique::Skyscraper, &candidates), None);
+        let step = find(Technique::TwoStringKite, &candidates).unwrap();
+
+        assert_eq!(step.eliminations, eliminations(&[Coord(7, 6)]));
+        assert_eq!(step.houses, [House::Row(0), House::Col(0)]);
+    }
+
+    // A strong link within box 1, whose r3c3 sees r6c3 of the other link
+    #[test]
+    fn finds_turbot_fish() {
+        let candidates = candidates_linked(
+            1,
+            &[
+                (House::Square(0), [Coord(0, 0), Coord(2, 2)]),
+                (House::Row(5), [Coord(5, 2), Coord(5, 7)]),
+            ],
+        );
+        assert_eq!(find(Technique::Skyscraper, &candidates), None);
+        assert_eq!(find(Technique::TwoStringKite, &candidates), None);
+        let step = find(Technique::TurbotFish, &candidates).unwrap();
+
+        assert_eq!(step.eliminations, eliminations(&[Coord(0, 7)]));
+        assert_eq!(step.houses, [House::Row(5), House::Square(0)]);
+        assert_eq!(
+            step.pattern,
+            chain_of_1(&[Coord(5, 7), Coord(5, 2), Coord(2, 2), Coord(0, 0)])
+        );
+    }
+
+    // Three strong links, where neither pair of consecutive links removes anything: r1c1 or r5c3
+    // holds 1, and the rest of column 1 within box 4 sees both
+    #[test]
+    fn finds_x_chain() {
+        let candidates = candidates_linked(
+            1,
+            &[
+                (House::Row(0), [Coord(0, 0), Coord(0, 6)]),
+                (House::Col(7), [Coord(2, 7), Coord(7, 7)]),
+                (House::Row(7), [Coord(7, 7), Coord(7, 2)]),
+                (House::Col(2), [Coord(7, 2), Coord(4, 2)]),
+            ],
+        );
+
+        for technique in [
+            Technique::Skyscraper,
+            Technique::TwoStringKite,
+            Technique::TurbotFish,
+        ] {
+            assert_eq!(find(technique, &candidates), None);
+        }
+
+        let step = find(Technique::XChain, &candidates).unwrap();
+
+        assert_eq!(
+            step.eliminations,
+            eliminations(&[Coord(3, 0), Coord(4, 0), Coord(5, 0)])
+        );
+        assert_eq!(
+            step.pattern,
+            chain_of_1(&[
+                Coord(0, 0),
+                Coord(0, 6),
+                Coord(2, 7),
+                Coord(7, 7),
+                Coord(7, 2),
+                Coord(4, 2),
+            ])
+        );
+    }
+
+    // r1c1 is 1, or 2 forces 3 in r1c5, 4 in r5c5 and 1 in r5c9
+    #[test]
+    fn finds_xy_chain() {
+        let candidates = candidates_with(&[
+            (Coord(0, 0), &[1, 2]),
+            (Coord(0, 4), &[2, 3]),
+            (Coord(4, 4), &[3, 4]),
+            (Coord(4, 8), &[1, 4]),
+        ]);
+        assert_eq!(find(Technique::XChain, &candidates), None);
+        let step = find(Technique::XYChain, &candidates).unwrap();
+
+        assert_eq!(
+            step.eliminations,
+            [(Coord(0, 8), Digit(1)), (Coord(4, 0), Digit(1))]
+        );
+        assert_eq!(
+            step.pattern,
+            chain(
+                &[
+                    (Coord(0, 0), 1),
+                    (Coord(0, 0), 2),
+                    (Coord(0, 4), 2),
+                    (Coord(0, 4), 3),
+                    (Coord(4, 4), 3),
+                    (Coord(4, 4), 4),
+                    (Coord(4, 8), 4),
+                    (Coord(4, 8), 1),
+                ],
+                false
+            )
+        );
+    }
+
+    // Bivalue cells at the corners of a rectangle, each sharing a digit with the next, closed into
+    // a loop through column 1. One end of each of its weak links holds the digit, so the rest of the
+    // house loses it.
+    #[test]
+    fn finds_nice_loop() {
+        let candidates = candidates_with(&[
+            (Coord(0, 0), &[1, 2]),
+            (Coord(0, 6), &[2, 3]),
+            (Coord(6, 6), &[3, 4]),
+            (Coord(6, 0), &[1, 4]),
+        ]);
+        let step = find(Technique::NiceLoop, &candidates).unwrap();
+
+        let others = [1, 2, 3, 4, 5, 7, 8];
+        let eliminations = [
+            others.map(|col| (Coord(0, col), Digit(2))),
+            others.map(|row| (Coord(row, 6), Digit(3))),
+            others.map(|col| (Coord(6, col), Digit(4))),
+            others.map(|row| (Coord(row, 0), Digit(1))),
+        ]
+        .concat();
+
+        assert_eq!(step.eliminations, eliminations);
+        assert_eq!(
+            step.pattern,
+            chain(
+                &[
+                    (Coord(0, 0), 1),
+                    (Coord(0, 0), 2),
+                    (Coord(0, 6), 2),
+                    (Coord(0, 6), 3),
+                    (Coord(6, 6), 3),
+                    (Coord(6, 6), 4),
+                    (Coord(6, 0), 4),
+                    (Coord(6, 0), 1),
+                ],
+                true
+            )
+        );
+    }
+
+    // 1 only fits in r1c1 and r1c5 of row 1, and 2 in r1c5 and r1c6 of box 2. If r1c1 isn't 1,
+    // r1c5 is, and r1c6 is 2, so r1c1 can't be 2 either way.
+    #[test]
+    fn finds_alternating_inference_chain() {
+        let mut candidates = candidates_linked(1, &[(House::Row(0), [Coord(0, 0), Coord(0, 4)])]);
+
+        for (row, col) in [(0, 3), (1, 3), (1, 4), (1, 5), (2, 3), (2, 4), (2, 5)] {
+            candidates[row * 9 + col].remove(Digit(2));
+        }
+
+        for technique in [Technique::XChain, Technique::XYChain, Technique::NiceLoop] {
+            assert_eq!(find(technique, &candidates), None);
+        }
+
+        let step = find(Technique::AlternatingInferenceChain, &candidates).unwrap();
+
+        assert_eq!(step.eliminations, [(Coord(0, 0), Digit(2))]);
+        assert_eq!(
+            step.pattern,
+            chain(
+                &[
+                    (Coord(0, 0), 1),
+                    (Coord(0, 4), 1),
+                    (Coord(0, 4), 2),
+                    (Coord(0, 5), 2),
+                ],
+                false
+            )
+        );
+    }
+}
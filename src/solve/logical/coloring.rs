@@ -0,0 +1,257 @@
+use std::collections::VecDeque;
+
+use crate::prelude::{Candidates, Digit, Layout};
+
+use super::{combinations, links::Links, sees, Pattern, Step, Technique};
+
+// The cells joined by strong links on a digit, split by color. Colors alternate along the links, so
+// either every cell of one color holds the digit or every cell of the other does.
+type Cluster = [Vec<usize>; 2];
+
+// Two cells of the same color seeing each other rule their color out, and cells seeing both colors
+// can't hold the digit
+pub(super) fn simple_coloring(layout: &Layout, candidates: &[Candidates]) -> Option<Step> {
+    for digit in layout.digits() {
+        let links = Links::of_digit(layout, candidates, digit);
+
+        for cluster in clusters(&links, candidates.len(), digit) {
+            let wrap = cluster.iter().find(|color| {
+                combinations(color, 2)
+                    .iter()
+                    .any(|pair| sees(layout, pair[0], pair[1]))
+            });
+
+            let eliminations = wrap.map_or_else(
+                || {
+                    (0..candidates.len())
+                        .filter(|&i| {
+                            candidates[i].contains(digit)
+                                && !cluster.iter().any(|color| color.contains(&i))
+                                && cluster
+                                    .iter()
+                                    .all(|color| color.iter().any(|&j| sees(layout, i, j)))
+                        })
+                        .collect()
+                },
+                Clone::clone,
+            );
+
+            if let Some(step) = coloring_step(
+                layout,
+                Technique::SimpleColoring,
+                digit,
+                &[&cluster],
+                eliminations,
+            ) {
+                return Some(step);
+            }
+        }
+    }
+
+    None
+}
+
+// Two clusters with a color of one seeing a color of the other can't have both colors hold the
+// digit, so one of the opposite colors does. Cells seeing both opposite colors can't hold it. When a
+// color sees both colors of another cluster, it's ruled out instead.
+pub(super) fn multi_coloring(layout: &Layout, candidates: &[Candidates]) -> Option<Step> {
+    for digit in layout.digits() {
+        let links = Links::of_digit(layout, candidates, digit);
+        let clusters = clusters(&links, candidates.len(), digit);
+
+        let sees_color = |i: usize, color: &[usize]| color.iter().any(|&j| sees(layout, i, j));
+        let colors_see = |a: &[usize], b: &[usize]| a.iter().any(|&i| sees_color(i, b));
+
+        for (x, y) in clusters
+            .iter()
+            .enumerate()
+            .flat_map(|(i, x)| clusters[i + 1..].iter().map(move |y| (x, y)))
+        {
+            // Both ways around, as only one of the clusters may see both colors of the other
+            for (x, y) in [(x, y), (y, x)] {
+                for (a, c) in [(0, 0), (0, 1), (1, 0), (1, 1)] {
+                    let (color, opposite) = (&x[a], &x[1 - a]);
+                    let (other, other_opposite) = (&y[c], &y[1 - c]);
+
+                    if !colors_see(color, other) {
+                        continue;
+                    }
+
+                    let eliminations = if colors_see(color, other_opposite) {
+                        color.clone()
+                    } else {
+                        (0..candidates.len())
+                            .filter(|&i| {
+                                candidates[i].contains(digit)
+                                    && !opposite.contains(&i)
+                                    && !other_opposite.contains(&i)
+                                    && sees_color(i, opposite)
+                                    && sees_color(i, other_opposite)
+                            })
+                            .collect()
+                    };
+
+                    if let Some(step) = coloring_step(
+                        layout,
+                        Technique::MultiColoring,
+                        digit,
+                        &[x, y],
+                        eliminations,
+                    ) {
+                        return Some(step);
+                    }
+                }
+            }
+        }
+    }
+
+    None
+}
+
+// Colors every cell with strong links on the digit, one cluster at a time
+fn clusters(links: &Links<'_>, size: usize, digit: Digit) -> Vec<Cluster> {
+    let mut colored = vec![false; size];
+    let mut clusters = vec![];
+
+    for (start, _) in links.nodes() {
+        if colored[start] || links.strong((start, digit)).is_empty() {
+            continue;
+        }
+
+        let mut cluster: Cluster = [vec![], vec![]];
+        let mut queue = VecDeque::from([(start, 0)]);
+        colored[start] = true;
+
+        while let Some((i, color)) = queue.pop_front() {
+            cluster[color].push(i);
+
+            for &(j, _) in links.strong((i, digit)) {
+                if !colored[j] {
+                    colored[j] = true;
+                    queue.push_back((j, 1 - color));
+                }
+            }
+        }
+
+        clusters.push(cluster);
+    }
+
+    clusters
+}
+
+fn coloring_step(
+    layout: &Layout,
+    technique: Technique,
+    digit: Digit,
+    clusters: &[&Cluster],
+    eliminations: Vec<usize>,
+) -> Option<Step> {
+    let cells = clusters
+        .iter()
+        .flat_map(|cluster| cluster.iter().flatten())
+        .copied()
+        .collect::<Vec<_>>();
+
+    let eliminations = eliminations.into_iter().map(|i| (i, digit)).collect();
+
+    Step::elimination(layout, technique, vec![], &cells, eliminations).map(|step| {
+        step.with_pattern(Pattern::Coloring {
+            digit,
+            clusters: clusters
+                .iter()
+                .map(|cluster| {
+                    cluster
+                        .each_ref()
+                        .map(|color| color.iter().map(|&i| layout.coord(i)).collect())
+                })
+                .collect(),
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{Coord, House};
+    use crate::solve::logical::tests::{candidates_linked, find};
+
+    fn eliminations(coords: &[Coord]) -> Vec<(Coord, Digit)> {
+        coords.iter().map(|&coord| (coord, Digit(1))).collect()
+    }
+
+    // r1c2 and r7c9 take one color, r1c8 and r7c3 the other. The cells of box 1 in column 3 and
+    // of box 7 in column 2 see both.
+    #[test]
+    fn finds_color_traps() {
+        let candidates = candidates_linked(
+            1,
+            &[
+                (House::Row(0), [Coord(0, 1), Coord(0, 7)]),
+                (House::Col(7), [Coord(0, 7), Coord(6, 7)]),
+                (House::Row(6), [Coord(6, 7), Coord(6, 2)]),
+            ],
+        );
+        let step = find(Technique::SimpleColoring, &candidates).unwrap();
+
+        assert_eq!(
+            step.eliminations,
+            eliminations(&[Coord(1, 2), Coord(2, 2), Coord(7, 1), Coord(8, 1)])
+        );
+        assert_eq!(
+            step.pattern,
+            Some(Pattern::Coloring {
+                digit: Digit(1),
+                clusters: vec![[
+                    vec![Coord(0, 1), Coord(6, 7)],
+                    vec![Coord(0, 7), Coord(6, 2)]
+                ]],
+            })
+        );
+    }
+
+    // r1c2 and r2c3 take the same color and share a box, so their whole color is ruled out
+    #[test]
+    fn finds_color_wraps() {
+        let candidates = candidates_linked(
+            1,
+            &[
+                (House::Row(0), [Coord(0, 1), Coord(0, 7)]),
+                (House::Col(7), [Coord(0, 7), Coord(4, 7)]),
+                (House::Row(4), [Coord(4, 7), Coord(4, 2)]),
+                (House::Col(2), [Coord(4, 2), Coord(1, 2)]),
+            ],
+        );
+        let step = find(Technique::SimpleColoring, &candidates).unwrap();
+
+        assert_eq!(
+            step.eliminations,
+            eliminations(&[Coord(0, 1), Coord(4, 7), Coord(1, 2)])
+        );
+    }
+
+    // r1c1 sees r2c3 of the other cluster, so r1c5 or r7c3 holds 1, and r7c5 sees both
+    #[test]
+    fn finds_multi_coloring() {
+        let candidates = candidates_linked(
+            1,
+            &[
+                (House::Row(0), [Coord(0, 0), Coord(0, 4)]),
+                (House::Col(2), [Coord(1, 2), Coord(6, 2)]),
+            ],
+        );
+        assert_eq!(find(Technique::SimpleColoring, &candidates), None);
+        let step = find(Technique::MultiColoring, &candidates).unwrap();
+
+        assert_eq!(step.eliminations, eliminations(&[Coord(6, 4)]));
+        assert_eq!(
+            step.pattern,
+            Some(Pattern::Coloring {
+                digit: Digit(1),
+                clusters: vec![
+                    [vec![Coord(0, 0)], vec![Coord(0, 4)]],
+                    [vec![Coord(1, 2)], vec![Coord(6, 2)]]
+                ],
+            })
+        );
+    }
+}
@@ -0,0 +1,303 @@
+use crate::prelude::{Candidates, Digit, House, Layout};
+
+use super::{combinations, sees, Pattern, Step, Technique};
+
+// Builds a row or column from its number
+type LineHouse = fn(usize) -> House;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Fins {
+    None,
+    // Every base line keeps at least two cells in the cover lines
+    Finned,
+    // Some base line keeps a single cell in the cover lines, so the fish only exists with its fins
+    Sashimi,
+}
+
+// A digit whose candidates in `size` base lines all lie within `size` cover lines, which run the
+// other way, must take one cell of each cover line within the base lines. It can then be removed
+// from the rest of the cover lines. Fins are extra cells of the base lines outside the cover lines:
+// either a fin holds the digit, or the fish does, so only cells that also see every fin lose it.
+pub(super) fn fish(
+    layout: &Layout,
+    candidates: &[Candidates],
+    technique: Technique,
+    size: usize,
+    fins: Fins,
+) -> Option<Step> {
+    let house_size = layout.house_size();
+
+    for digit in layout.digits() {
+        for rows in [true, false] {
+            let (base_house, cover_house): (LineHouse, LineHouse) = if rows {
+                (House::Row, House::Col)
+            } else {
+                (House::Col, House::Row)
+            };
+
+            // The positions of the digit within each line, by the cover line they fall in
+            let positions = (0..house_size)
+                .map(|line| {
+                    layout
+                        .house_indices(base_house(line))
+                        .iter()
+                        .enumerate()
+                        .filter(|&(_, &i)| candidates[i].contains(digit))
+                        .fold(0_u32, |mask, (position, _)| mask | 1 << position)
+                })
+                .collect::<Vec<_>>();
+
+            let lines = (0..house_size)
+                .filter(|&line| {
+                    let count = positions[line].count_ones() as usize;
+                    count >= 2 && (fins != Fins::None || count <= size)
+                })
+                .collect::<Vec<_>>();
+
+            for base in combinations(&lines, size) {
+                let union = base.iter().fold(0, |mask, &line| mask | positions[line]);
+
+                let covers = if fins == Fins::None {
+                    if union.count_ones() as usize != size {
+                        continue;
+                    }
+
+                    vec![union]
+                } else {
+                    let lines = (0..house_size)
+                        .filter(|&line| union & 1 << line != 0)
+                        .collect::<Vec<_>>();
+
+                    if lines.len() <= size {
+                        continue;
+                    }
+
+                    combinations(&lines, size)
+                        .into_iter()
+                        .map(|cover| cover.iter().fold(0, |mask, &line| mask | 1 << line))
+                        .collect()
+                };
+
+                for cover in covers {
+                    let fish = Fish {
+                        layout,
+                        candidates,
+                        digit,
+                        base_house,
+                        cover_house,
+                        base: &base,
+                        cover,
+                    };
+
+                    if let Some(step) = fish.step(technique, &positions, fins) {
+                        return Some(step);
+                    }
+                }
+            }
+        }
+    }
+
+    None
+}
+
+struct Fish<'a> {
+    layout: &'a Layout,
+    candidates: &'a [Candidates],
+    digit: Digit,
+    base_house: LineHouse,
+    cover_house: LineHouse,
+    base: &'a [usize],
+    // One bit per cover line
+    cover: u32,
+}
+
+impl Fish<'_> {
+    fn step(&self, technique: Technique, positions: &[u32], fins: Fins) -> Option<Step> {
+        let layout = self.layout;
+        let mut is_sashimi = false;
+
+        for &line in self.base {
+            match (positions[line] & self.cover).count_ones() {
+                0 => return None,
+                1 => is_sashimi = true,
+                _ => {}
+            }
+        }
+
+        let fin_cells = self
+            .base_cells()
+            .filter(|&(_, position)| self.cover & 1 << position == 0)
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+
+        let expected = match fins {
+            Fins::None => Fins::None,
+            _ if fin_cells.is_empty() => Fins::None,
+            _ if is_sashimi => Fins::Sashimi,
+            _ => Fins::Finned,
+        };
+
+        if expected != fins {
+            return None;
+        }
+
+        let eliminations = self
+            .cover_lines()
+            .flat_map(|line| {
+                layout
+                    .house_indices((self.cover_house)(line))
+                    .iter()
+                    .copied()
+                    .enumerate()
+            })
+            .filter(|&(position, i)| {
+                !self.base.contains(&position)
+                    && self.candidates[i].contains(self.digit)
+                    && fin_cells.iter().all(|&fin| sees(layout, i, fin))
+            })
+            .map(|(_, i)| (i, self.digit))
+            .collect();
+
+        let base = self
+            .base
+            .iter()
+            .map(|&line| (self.base_house)(line))
+            .collect::<Vec<_>>();
+        let cover = self
+            .cover_lines()
+            .map(|line| (self.cover_house)(line))
+            .collect::<Vec<_>>();
+
+        let cells = self.base_cells().map(|(i, _)| i).collect::<Vec<_>>();
+        let houses = base.iter().chain(&cover).copied().collect();
+
+        Step::elimination(layout, technique, houses, &cells, eliminations).map(|step| {
+            step.with_pattern(Pattern::Fish {
+                digit: self.digit,
+                base,
+                cover,
+                fins: fin_cells.iter().map(|&i| layout.coord(i)).collect(),
+            })
+        })
+    }
+
+    fn cover_lines(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.layout.house_size()).filter(|&line| self.cover & 1 << line != 0)
+    }
+
+    // Every cell of the base lines with the digit, along with its position within its line
+    fn base_cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.base.iter().flat_map(|&line| {
+            self.layout
+                .house_indices((self.base_house)(line))
+                .iter()
+                .copied()
+                .enumerate()
+                .filter(|&(_, i)| self.candidates[i].contains(self.digit))
+                .map(|(position, i)| (i, position))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{Coord, LogicalSolver};
+    use crate::solve::logical::tests::{candidates_with, first_step};
+
+    // Every digit in every cell, apart from the digit in the rows listed, which only keep it in
+    // their columns
+    fn candidates_in_rows(digit: u8, rows: &[(usize, &[usize])]) -> Vec<Candidates> {
+        let mut candidates = candidates_with(&[]);
+
+        for &(row, cols) in rows {
+            for col in (0..9).filter(|col| !cols.contains(col)) {
+                candidates[row * 9 + col].remove(Digit(digit));
+            }
+        }
+
+        candidates
+    }
+
+    // The digit in the columns, apart from the rows
+    fn column_eliminations(digit: u8, cols: &[usize], rows: &[usize]) -> Vec<(Coord, Digit)> {
+        cols.iter()
+            .flat_map(|&col| (0..9).map(move |row| Coord(row, col)))
+            .filter(|coord| !rows.contains(&coord.0))
+            .map(|coord| (coord, Digit(digit)))
+            .collect()
+    }
+
+    #[test]
+    fn finds_x_wing() {
+        let candidates = candidates_in_rows(1, &[(0, &[2, 6]), (4, &[2, 6])]);
+        let step = first_step(&candidates, LogicalSolver::new()).unwrap();
+
+        assert_eq!(step.technique, Technique::XWing);
+        assert_eq!(step.eliminations, column_eliminations(1, &[2, 6], &[0, 4]));
+        assert_eq!(
+            step.pattern,
+            Some(Pattern::Fish {
+                digit: Digit(1),
+                base: vec![House::Row(0), House::Row(4)],
+                cover: vec![House::Col(2), House::Col(6)],
+                fins: vec![],
+            })
+        );
+    }
+
+    #[test]
+    fn finds_swordfish() {
+        let candidates = candidates_in_rows(2, &[(1, &[0, 3]), (4, &[3, 6]), (7, &[0, 6])]);
+        let step = first_step(&candidates, LogicalSolver::new()).unwrap();
+
+        assert_eq!(step.technique, Technique::Swordfish);
+        assert_eq!(
+            step.eliminations,
+            column_eliminations(2, &[0, 3, 6], &[1, 4, 7])
+        );
+    }
+
+    // The fin r5c9 shares its box with r4c8 and r6c8, which lose the digit whichever holds it
+    #[test]
+    fn finds_finned_x_wing() {
+        let candidates = candidates_in_rows(3, &[(0, &[1, 7]), (4, &[1, 7, 8])]);
+        let step = first_step(&candidates, LogicalSolver::new()).unwrap();
+
+        assert_eq!(step.technique, Technique::FinnedXWing);
+        assert_eq!(
+            step.eliminations,
+            [(Coord(3, 7), Digit(3)), (Coord(5, 7), Digit(3))]
+        );
+        assert!(matches!(
+            step.pattern,
+            Some(Pattern::Fish { ref fins, .. }) if fins == &[Coord(4, 8)]
+        ));
+    }
+
+    // Row 5 only keeps r5c2 within the cover columns, next to its fin r5c9
+    #[test]
+    fn finds_sashimi_x_wing() {
+        let candidates = candidates_in_rows(4, &[(0, &[1, 7]), (4, &[1, 8])]);
+        let layout = Layout::classic();
+
+        assert_eq!(
+            fish(
+                &layout,
+                &candidates,
+                Technique::FinnedXWing,
+                2,
+                Fins::Finned
+            ),
+            None
+        );
+
+        let step = first_step(&candidates, LogicalSolver::new()).unwrap();
+
+        assert_eq!(step.technique, Technique::SashimiXWing);
+        assert_eq!(
+            step.eliminations,
+            [(Coord(3, 7), Digit(4)), (Coord(5, 7), Digit(4))]
+        );
+    }
+}
@@ -0,0 +1,286 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::prelude::{Candidates, Layout};
+
+use super::{
+    links::{Links, Node},
+    ChainNode, Link, Pattern, Step, Technique,
+};
+
+// Whether each candidate is true when assuming a candidate true, along with the candidate it
+// follows from. Only the first conclusion about each candidate is kept.
+type Implications = HashMap<Node, (bool, Node)>;
+
+// Every candidate of a cell is assumed true in turn. Whatever follows from all of them holds, as one
+// of them is true.
+pub(super) fn cell_forcing_chain(layout: &Layout, candidates: &[Candidates]) -> Option<Step> {
+    let links = Links::new(layout, candidates);
+
+    (2..=layout.house_size()).find_map(|count| {
+        candidates
+            .iter()
+            .enumerate()
+            .filter(|(_, candidates)| candidates.count() == count)
+            .find_map(|(i, candidates)| {
+                let assumptions = candidates
+                    .digits()
+                    .map(|digit| (i, digit))
+                    .collect::<Vec<_>>();
+
+                forcing_chains(layout, &links, Technique::CellForcingChain, &assumptions)
+            })
+    })
+}
+
+// A digit is assumed true in every cell of a house it fits in, in turn
+pub(super) fn unit_forcing_chain(layout: &Layout, candidates: &[Candidates]) -> Option<Step> {
+    let links = Links::new(layout, candidates);
+
+    (2..=layout.house_size()).find_map(|count| {
+        layout.houses().find_map(|house| {
+            layout.digits().find_map(|digit| {
+                let assumptions = layout
+                    .house_indices(house)
+                    .iter()
+                    .filter(|&&i| candidates[i].contains(digit))
+                    .map(|&i| (i, digit))
+                    .collect::<Vec<_>>();
+
+                if assumptions.len() != count {
+                    return None;
+                }
+
+                forcing_chains(layout, &links, Technique::UnitForcingChain, &assumptions).map(
+                    |mut step| {
+                        step.houses.push(house);
+                        step
+                    },
+                )
+            })
+        })
+    })
+}
+
+// Looks for a candidate that's true whatever the assumption, and otherwise for one that's false
+fn forcing_chains(
+    layout: &Layout,
+    links: &Links<'_>,
+    technique: Technique,
+    assumptions: &[Node],
+) -> Option<Step> {
+    let branches = assumptions
+        .iter()
+        .map(|&assumption| implications(links, assumption))
+        .collect::<Vec<_>>();
+
+    let always = |is_on: bool| {
+        links.nodes().find(|node| {
+            branches
+                .iter()
+                .all(|branch| branch.get(node).is_some_and(|&(on, _)| on == is_on))
+        })
+    };
+
+    let (step, conclusion) = if let Some(node @ (index, digit)) = always(true) {
+        (
+            Step::placement(layout, technique, vec![], index, digit),
+            node,
+        )
+    } else {
+        let node = always(false)?;
+        let cells = assumptions.iter().map(|&(i, _)| i).collect::<Vec<_>>();
+        (
+            Step::elimination(layout, technique, vec![], &cells, vec![node])?,
+            node,
+        )
+    };
+
+    let chains = branches
+        .iter()
+        .zip(assumptions)
+        .map(|(branch, &assumption)| trace(layout, branch, assumption, conclusion))
+        .collect::<Vec<_>>();
+
+    let mut step = step.with_pattern(Pattern::Forcing { chains });
+    step.cells = assumptions.iter().map(|&(i, _)| layout.coord(i)).collect();
+    Some(step)
+}
+
+// Follows the links from a candidate assumed true: the candidates weakly linked to a true one are
+// false, and those strongly linked to a false one are true
+fn implications(links: &Links<'_>, assumption: Node) -> Implications {
+    let mut implications = HashMap::from([(assumption, (true, assumption))]);
+    let mut queue = VecDeque::from([(assumption, true)]);
+
+    while let Some((node, is_on)) = queue.pop_front() {
+        let next = if is_on {
+            links.weak(node).collect::<Vec<_>>()
+        } else {
+            links.strong(node).to_vec()
+        };
+
+        for next in next {
+            implications.entry(next).or_insert_with(|| {
+                queue.push_back((next, !is_on));
+                (!is_on, node)
+            });
+        }
+    }
+
+    implications
+}
+
+// The chain from the assumption to the conclusion. The assumptions of a forcing chain are strongly
+// linked as a whole, as one of them is true.
+fn trace(
+    layout: &Layout,
+    implications: &Implications,
+    assumption: Node,
+    conclusion: Node,
+) -> Vec<ChainNode> {
+    let mut chain = vec![];
+    let mut node = conclusion;
+
+    loop {
+        let (is_on, previous) = implications[&node];
+        let link = if is_on { Link::Strong } else { Link::Weak };
+        chain.push((layout.coord(node.0), node.1, link));
+
+        if node == assumption {
+            break;
+        }
+
+        node = previous;
+    }
+
+    chain.reverse();
+    chain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{Coord, Digit, House};
+    use crate::solve::logical::tests::{candidates_linked, candidates_with, find};
+
+    const CHAINS: [Technique; 4] = [
+        Technique::XChain,
+        Technique::XYChain,
+        Technique::NiceLoop,
+        Technique::AlternatingInferenceChain,
+    ];
+
+    fn chain(nodes: &[(Coord, u8)]) -> Vec<ChainNode> {
+        nodes
+            .iter()
+            .enumerate()
+            .map(|(k, &(coord, digit))| {
+                let link = if k % 2 == 0 { Link::Strong } else { Link::Weak };
+                (coord, Digit(digit), link)
+            })
+            .collect()
+    }
+
+    // Whichever of 1, 2 and 3 r1c1 holds, 4 ends up in a cell seeing r5c5, which no wing or chain
+    // starting from a single candidate shows
+    #[test]
+    fn finds_cell_forcing_chain() {
+        let candidates = candidates_with(&[
+            (Coord(0, 0), &[1, 2, 3]),
+            (Coord(0, 4), &[1, 4]),
+            (Coord(4, 0), &[2, 4]),
+            (Coord(1, 1), &[3, 5]),
+            (Coord(1, 4), &[4, 5]),
+        ]);
+
+        let wings = [
+            Technique::XYWing,
+            Technique::XYZWing,
+            Technique::WWing,
+            Technique::WXYZWing,
+        ];
+
+        for technique in wings.into_iter().chain(CHAINS) {
+            assert_eq!(find(technique, &candidates), None);
+        }
+
+        let step = find(Technique::CellForcingChain, &candidates).unwrap();
+
+        assert_eq!(step.eliminations, [(Coord(4, 4), Digit(4))]);
+        assert_eq!(step.cells, [Coord(0, 0); 3]);
+        assert_eq!(
+            step.pattern,
+            Some(Pattern::Forcing {
+                chains: vec![
+                    chain(&[
+                        (Coord(0, 0), 1),
+                        (Coord(0, 4), 1),
+                        (Coord(0, 4), 4),
+                        (Coord(4, 4), 4),
+                    ]),
+                    chain(&[
+                        (Coord(0, 0), 2),
+                        (Coord(4, 0), 2),
+                        (Coord(4, 0), 4),
+                        (Coord(4, 4), 4),
+                    ]),
+                    chain(&[
+                        (Coord(0, 0), 3),
+                        (Coord(1, 1), 3),
+                        (Coord(1, 1), 5),
+                        (Coord(1, 4), 5),
+                        (Coord(1, 4), 4),
+                        (Coord(4, 4), 4),
+                    ]),
+                ],
+            })
+        );
+    }
+
+    // 1 only fits in three cells of row 1, and each of them rules out 1 in r4c5, through the
+    // strong links in rows 5 and 6 for the outer two
+    #[test]
+    fn finds_unit_forcing_chain() {
+        let mut candidates = candidates_linked(
+            1,
+            &[
+                (House::Row(4), [Coord(4, 0), Coord(4, 5)]),
+                (House::Row(5), [Coord(5, 3), Coord(5, 8)]),
+            ],
+        );
+
+        for col in [1, 2, 3, 5, 6, 7] {
+            candidates[col].remove(Digit(1));
+        }
+
+        for technique in CHAINS.into_iter().chain([Technique::CellForcingChain]) {
+            assert_eq!(find(technique, &candidates), None);
+        }
+
+        let step = find(Technique::UnitForcingChain, &candidates).unwrap();
+
+        assert_eq!(step.eliminations, [(Coord(3, 4), Digit(1))]);
+        assert_eq!(step.houses, [House::Row(0)]);
+        assert_eq!(step.cells, [Coord(0, 0), Coord(0, 4), Coord(0, 8)]);
+        assert_eq!(
+            step.pattern,
+            Some(Pattern::Forcing {
+                chains: vec![
+                    chain(&[
+                        (Coord(0, 0), 1),
+                        (Coord(4, 0), 1),
+                        (Coord(4, 5), 1),
+                        (Coord(3, 4), 1),
+                    ]),
+                    chain(&[(Coord(0, 4), 1), (Coord(3, 4), 1)]),
+                    chain(&[
+                        (Coord(0, 8), 1),
+                        (Coord(5, 8), 1),
+                        (Coord(5, 3), 1),
+                        (Coord(3, 4), 1),
+                    ]),
+                ],
+            })
+        );
+    }
+}
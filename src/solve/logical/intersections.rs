@@ -0,0 +1,117 @@
+use crate::prelude::{Candidates, Digit, House, Layout};
+
+use super::{Step, Technique};
+
+// A digit confined to a single line within a box can be removed from the rest of that line
+pub(super) fn pointing(layout: &Layout, candidates: &[Candidates]) -> Option<Step> {
+    let squares = layout
+        .houses()
+        .filter(|house| matches!(house, House::Square(_)));
+
+    for square in squares {
+        for digit in layout.digits() {
+            let cells = cells_with_digit(layout, candidates, square, digit);
+
+            if cells.len() < 2 {
+                continue;
+            }
+
+            if let Some(line) = common_house(layout, &cells, |house| {
+                matches!(house, House::Row(_) | House::Col(_))
+            }) {
+                let eliminations = eliminate_outside(layout, candidates, line, &cells, digit);
+
+                if let Some(step) = Step::elimination(
+                    layout,
+                    Technique::PointingPair,
+                    vec![square, line],
+                    &cells,
+                    eliminations,
+                ) {
+                    return Some(step);
+                }
+            }
+        }
+    }
+
+    None
+}
+
+// A digit confined to a single box within a line can be removed from the rest of that box
+pub(super) fn box_line_reduction(layout: &Layout, candidates: &[Candidates]) -> Option<Step> {
+    let lines = layout
+        .houses()
+        .filter(|house| matches!(house, House::Row(_) | House::Col(_)));
+
+    for line in lines {
+        for digit in layout.digits() {
+            let cells = cells_with_digit(layout, candidates, line, digit);
+
+            if cells.len() < 2 {
+                continue;
+            }
+
+            if let Some(square) =
+                common_house(layout, &cells, |house| matches!(house, House::Square(_)))
+            {
+                let eliminations = eliminate_outside(layout, candidates, square, &cells, digit);
+
+                if let Some(step) = Step::elimination(
+                    layout,
+                    Technique::BoxLineReduction,
+                    vec![line, square],
+                    &cells,
+                    eliminations,
+                ) {
+                    return Some(step);
+                }
+            }
+        }
+    }
+
+    None
+}
+
+fn cells_with_digit(
+    layout: &Layout,
+    candidates: &[Candidates],
+    house: House,
+    digit: Digit,
+) -> Vec<usize> {
+    layout
+        .house_indices(house)
+        .iter()
+        .copied()
+        .filter(|&i| candidates[i].contains(digit))
+        .collect()
+}
+
+fn common_house(
+    layout: &Layout,
+    cells: &[usize],
+    select: impl Fn(&House) -> bool,
+) -> Option<House> {
+    layout
+        .houses_of_cell(cells[0])
+        .filter(select)
+        .find(|&house| {
+            let indices = layout.house_indices(house);
+            cells.iter().all(|index| indices.contains(index))
+        })
+}
+
+fn eliminate_outside(
+    layout: &Layout,
+    candidates: &[Candidates],
+    house: House,
+    cells: &[usize],
+    digit: Digit,
+) -> Vec<(usize, Digit)> {
+    layout
+        .house_indices(house)
+        .iter()
+        .copied()
+        .filter(|i| !cells.contains(i) && candidates[*i].contains(digit))
+        .map(|i| (i, digit))
+        .collect()
+}
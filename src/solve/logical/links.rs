@@ -0,0 +1,130 @@
+use crate::prelude::{Candidates, Digit, Layout};
+
+use super::sees;
+
+// A candidate: a digit within a cell
+pub(super) type Node = (usize, Digit);
+
+// The links between candidates that chains are made of. Two candidates are strongly linked when at
+// least one of them is true, and weakly linked when at most one of them is. Strong links come from
+// houses where a digit only fits in two cells, and from cells with only two candidates. Weak links
+// are left implicit, as every pair of candidates that see each other has one.
+pub(super) struct Links<'a> {
+    layout: &'a Layout,
+    candidates: &'a [Candidates],
+    // Only this digit's candidates are linked when set, through houses alone
+    digit: Option<Digit>,
+    // By node, as numbered by `id`
+    strong: Vec<Vec<Node>>,
+}
+
+impl<'a> Links<'a> {
+    pub(super) fn new(layout: &'a Layout, candidates: &'a [Candidates]) -> Self {
+        Self::build(layout, candidates, None)
+    }
+
+    pub(super) fn of_digit(layout: &'a Layout, candidates: &'a [Candidates], digit: Digit) -> Self {
+        Self::build(layout, candidates, Some(digit))
+    }
+
+    fn build(layout: &'a Layout, candidates: &'a [Candidates], digit: Option<Digit>) -> Self {
+        let mut links = Self {
+            layout,
+            candidates,
+            digit,
+            strong: vec![vec![]; layout.grid_size() * layout.house_size()],
+        };
+
+        for house in layout.houses() {
+            for digit in layout
+                .digits()
+                .filter(|&d| digit.is_none_or(|digit| d == digit))
+            {
+                let cells = layout
+                    .house_indices(house)
+                    .iter()
+                    .copied()
+                    .filter(|&i| candidates[i].contains(digit))
+                    .collect::<Vec<_>>();
+
+                if let [a, b] = cells[..] {
+                    links.add_strong((a, digit), (b, digit));
+                }
+            }
+        }
+
+        if digit.is_none() {
+            for (i, candidates) in candidates.iter().enumerate() {
+                if let [a, b] = candidates.digits().collect::<Vec<_>>()[..] {
+                    links.add_strong((i, a), (i, b));
+                }
+            }
+        }
+
+        links
+    }
+
+    fn add_strong(&mut self, a: Node, b: Node) {
+        // A pair of cells can share a row or column and a box
+        if self.is_strong(a, b) {
+            return;
+        }
+
+        let (id_a, id_b) = (self.id(a), self.id(b));
+        self.strong[id_a].push(b);
+        self.strong[id_b].push(a);
+    }
+
+    fn id(&self, (i, digit): Node) -> usize {
+        i * self.layout.house_size() + usize::from(digit.0) - 1
+    }
+
+    // Every candidate that's linked, in grid order
+    pub(super) fn nodes(&self) -> impl Iterator<Item = Node> + 'a {
+        let only = self.digit;
+
+        self.candidates
+            .iter()
+            .enumerate()
+            .flat_map(move |(i, candidates)| {
+                candidates
+                    .digits()
+                    .filter(move |&digit| only.is_none_or(|only| digit == only))
+                    .map(move |digit| (i, digit))
+            })
+    }
+
+    pub(super) fn strong(&self, node: Node) -> &[Node] {
+        &self.strong[self.id(node)]
+    }
+
+    pub(super) fn is_strong(&self, a: Node, b: Node) -> bool {
+        self.strong(a).contains(&b)
+    }
+
+    // The candidates that can't be true along with this one
+    pub(super) fn weak(&self, (i, digit): Node) -> impl Iterator<Item = Node> + '_ {
+        let peers = self
+            .layout
+            .peers(i)
+            .iter()
+            .copied()
+            .filter(move |&peer| self.candidates[peer].contains(digit))
+            .map(move |peer| (peer, digit));
+
+        let others = self.candidates[i]
+            .digits()
+            .filter(move |&other| self.digit.is_none() && other != digit)
+            .map(move |other| (i, other));
+
+        peers.chain(others)
+    }
+
+    pub(super) fn is_weak(&self, (i, a): Node, (j, b): Node) -> bool {
+        if i == j {
+            self.digit.is_none() && a != b
+        } else {
+            a == b && sees(self.layout, i, j)
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use crate::prelude::{Candidates, House, Layout};
+
+use super::{Step, Technique};
+
+pub(super) fn naked_single(layout: &Layout, candidates: &[Candidates]) -> Option<Step> {
+    candidates
+        .iter()
+        .enumerate()
+        .find_map(|(index, candidates)| {
+            if candidates.count() == 1 {
+                let digit = candidates.first()?;
+                Some(Step::placement(
+                    layout,
+                    Technique::NakedSingle,
+                    vec![],
+                    index,
+                    digit,
+                ))
+            } else {
+                None
+            }
+        })
+}
+
+pub(super) fn hidden_single(layout: &Layout, candidates: &[Candidates]) -> Option<Step> {
+    // Boxes are searched first, as their hidden singles are the easiest to spot
+    let houses = layout
+        .houses()
+        .filter(|house| matches!(house, House::Square(_)))
+        .chain(
+            layout
+                .houses()
+                .filter(|house| !matches!(house, House::Square(_))),
+        );
+
+    for house in houses {
+        for digit in layout.digits() {
+            let mut cells = layout
+                .house_indices(house)
+                .iter()
+                .copied()
+                .filter(|&i| candidates[i].contains(digit));
+
+            if let (Some(index), None) = (cells.next(), cells.next()) {
+                return Some(Step::placement(
+                    layout,
+                    Technique::HiddenSingle,
+                    vec![house],
+                    index,
+                    digit,
+                ));
+            }
+        }
+    }
+
+    None
+}
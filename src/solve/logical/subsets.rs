@@ -0,0 +1,108 @@
+use crate::prelude::{Candidates, Layout};
+
+use super::{combinations, Step, Technique};
+
+pub(super) fn naked_subset(
+    layout: &Layout,
+    candidates: &[Candidates],
+    technique: Technique,
+    size: usize,
+) -> Option<Step> {
+    for house in layout.houses() {
+        let indices = layout.house_indices(house);
+
+        let cells = indices
+            .iter()
+            .copied()
+            .filter(|&i| (2..=size).contains(&candidates[i].count()))
+            .collect::<Vec<_>>();
+
+        for subset in combinations(&cells, size) {
+            let digits = subset
+                .iter()
+                .fold(Candidates::empty(), |acc, &i| acc.union(candidates[i]));
+
+            if digits.count() != size {
+                continue;
+            }
+
+            let eliminations = indices
+                .iter()
+                .copied()
+                .filter(|i| !subset.contains(i))
+                .flat_map(|i| {
+                    candidates[i]
+                        .intersection(digits)
+                        .digits()
+                        .map(move |d| (i, d))
+                })
+                .collect();
+
+            if let Some(step) =
+                Step::elimination(layout, technique, vec![house], &subset, eliminations)
+            {
+                return Some(step);
+            }
+        }
+    }
+
+    None
+}
+
+pub(super) fn hidden_subset(
+    layout: &Layout,
+    candidates: &[Candidates],
+    technique: Technique,
+    size: usize,
+) -> Option<Step> {
+    for house in layout.houses() {
+        let indices = layout.house_indices(house);
+
+        let digits = layout
+            .digits()
+            .filter(|&digit| {
+                let count = indices
+                    .iter()
+                    .filter(|&&i| candidates[i].contains(digit))
+                    .count();
+
+                (2..=size).contains(&count)
+            })
+            .collect::<Vec<_>>();
+
+        for subset in combinations(&digits, size) {
+            let digits = subset
+                .iter()
+                .fold(Candidates::empty(), |acc, &digit| acc.union(digit.into()));
+
+            let cells = indices
+                .iter()
+                .copied()
+                .filter(|&i| !candidates[i].intersection(digits).is_empty())
+                .collect::<Vec<_>>();
+
+            if cells.len() != size {
+                continue;
+            }
+
+            let eliminations = cells
+                .iter()
+                .copied()
+                .flat_map(|i| {
+                    candidates[i]
+                        .difference(digits)
+                        .digits()
+                        .map(move |d| (i, d))
+                })
+                .collect();
+
+            if let Some(step) =
+                Step::elimination(layout, technique, vec![house], &cells, eliminations)
+            {
+                return Some(step);
+            }
+        }
+    }
+
+    None
+}
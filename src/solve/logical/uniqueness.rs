@@ -0,0 +1,662 @@
+use crate::prelude::{Candidates, Coord, Digit, House, Layout};
+
+use super::{combinations, common_peer_eliminations, Pattern, Step, Technique};
+
+// Four unsolved cells at the corners of a rectangle, sharing two candidates. Were the cells left
+// with only those two digits, they could be swapped around the rectangle for a second solution, as
+// every house holds either none of the cells or two of them. So in a puzzle with a unique solution,
+// some other digit must go in one of the cells.
+struct Rectangle {
+    // Clockwise from the top left, so that opposite corners are two apart
+    cells: [usize; 4],
+    digits: Candidates,
+}
+
+impl Rectangle {
+    // The candidates of each cell besides the rectangle's digits
+    fn extras(&self, candidates: &[Candidates]) -> [Candidates; 4] {
+        self.cells.map(|i| candidates[i].difference(self.digits))
+    }
+
+    // The corners with extra candidates, by position around the rectangle
+    fn with_extras(&self, candidates: &[Candidates]) -> Vec<usize> {
+        let extras = self.extras(candidates);
+        (0..4).filter(|&k| !extras[k].is_empty()).collect()
+    }
+
+    fn step(
+        &self,
+        layout: &Layout,
+        technique: Technique,
+        houses: Vec<House>,
+        eliminations: Vec<(usize, Digit)>,
+    ) -> Option<Step> {
+        Step::elimination(layout, technique, houses, &self.cells, eliminations).map(|step| {
+            step.with_pattern(Pattern::Rectangle {
+                digits: self.digits.digits().collect(),
+                corners: self.cells.iter().map(|&i| layout.coord(i)).collect(),
+            })
+        })
+    }
+}
+
+fn rectangles(layout: &Layout, candidates: &[Candidates]) -> Vec<Rectangle> {
+    let size = layout.house_size();
+    let mut rectangles = vec![];
+
+    for rows in combinations(&(0..size).collect::<Vec<_>>(), 2) {
+        for cols in combinations(&(0..size).collect::<Vec<_>>(), 2) {
+            let cells = [
+                Coord(rows[0], cols[0]),
+                Coord(rows[0], cols[1]),
+                Coord(rows[1], cols[1]),
+                Coord(rows[1], cols[0]),
+            ]
+            .map(|coord| layout.index(coord));
+
+            let common = cells.iter().fold(layout.candidates(), |acc, &i| {
+                acc.intersection(candidates[i])
+            });
+
+            if common.count() < 2 || !is_deadly(layout, &cells) {
+                continue;
+            }
+
+            for pair in combinations(&common.digits().collect::<Vec<_>>(), 2) {
+                rectangles.push(Rectangle {
+                    cells,
+                    digits: pair
+                        .into_iter()
+                        .fold(Candidates::empty(), |acc, digit| acc.union(digit.into())),
+                });
+            }
+        }
+    }
+
+    rectangles
+}
+
+// Swapping the digits only keeps every house valid when no house holds a single corner, such as a
+// rectangle spread over four boxes
+fn is_deadly(layout: &Layout, cells: &[usize; 4]) -> bool {
+    cells.iter().all(|&i| {
+        layout.house_ids_of_cell(i).iter().all(|&id| {
+            let indices = layout.house_indices_by_id(id);
+            cells.iter().filter(|cell| indices.contains(cell)).count() > 1
+        })
+    })
+}
+
+// Three corners are left with the rectangle's digits alone, so the fourth can't take either
+pub(super) fn type_1(layout: &Layout, candidates: &[Candidates]) -> Option<Step> {
+    rectangles(layout, candidates)
+        .into_iter()
+        .find_map(|rectangle| {
+            let [k] = rectangle.with_extras(candidates)[..] else {
+                return None;
+            };
+
+            let cell = rectangle.cells[k];
+            let eliminations = rectangle
+                .digits
+                .digits()
+                .map(|digit| (cell, digit))
+                .collect();
+
+            rectangle.step(
+                layout,
+                Technique::UniqueRectangleType1,
+                vec![],
+                eliminations,
+            )
+        })
+}
+
+// Two neighboring corners share a single extra candidate, which one of them must take
+pub(super) fn type_2(layout: &Layout, candidates: &[Candidates]) -> Option<Step> {
+    rectangles(layout, candidates)
+        .into_iter()
+        .find_map(|rectangle| {
+            let [a, b] = roof(&rectangle, candidates)?;
+            let extras = rectangle.extras(candidates);
+
+            if extras[a] != extras[b] || extras[a].count() != 1 {
+                return None;
+            }
+
+            let digit = extras[a].first()?;
+            let cells = [rectangle.cells[a], rectangle.cells[b]];
+            let eliminations = common_peer_eliminations(layout, candidates, &cells, digit);
+
+            rectangle.step(
+                layout,
+                Technique::UniqueRectangleType2,
+                vec![],
+                eliminations,
+            )
+        })
+}
+
+// Two neighboring corners must take one of their extra candidates between them, so they act as a
+// single cell with those candidates, which can form a naked subset with other cells of a house they
+// share
+pub(super) fn type_3(layout: &Layout, candidates: &[Candidates]) -> Option<Step> {
+    rectangles(layout, candidates)
+        .into_iter()
+        .find_map(|rectangle| {
+            let [a, b] = roof(&rectangle, candidates)?;
+            let extras = rectangle.extras(candidates);
+            let virtual_cell = extras[a].union(extras[b]);
+            let roof = [rectangle.cells[a], rectangle.cells[b]];
+
+            if virtual_cell.count() < 2 {
+                return None;
+            }
+
+            shared_houses(layout, roof).into_iter().find_map(|house| {
+                let others = layout
+                    .house_indices(house)
+                    .iter()
+                    .copied()
+                    .filter(|&i| !roof.contains(&i) && !candidates[i].is_empty())
+                    .collect::<Vec<_>>();
+
+                (1..=3.min(others.len())).find_map(|size| {
+                    combinations(&others, size).into_iter().find_map(|subset| {
+                        let digits = subset
+                            .iter()
+                            .fold(virtual_cell, |acc, &i| acc.union(candidates[i]));
+
+                        if digits.count() != size + 1 {
+                            return None;
+                        }
+
+                        let eliminations = others
+                            .iter()
+                            .filter(|i| !subset.contains(i))
+                            .flat_map(|&i| {
+                                candidates[i]
+                                    .intersection(digits)
+                                    .digits()
+                                    .map(move |digit| (i, digit))
+                            })
+                            .collect();
+
+                        let mut step = rectangle.step(
+                            layout,
+                            Technique::UniqueRectangleType3,
+                            vec![house],
+                            eliminations,
+                        )?;
+
+                        step.cells.extend(subset.iter().map(|&i| layout.coord(i)));
+                        Some(step)
+                    })
+                })
+            })
+        })
+}
+
+// Two neighboring corners are the only cells of a house they share with one of the rectangle's
+// digits, so one of them takes it, and neither can take the other digit
+pub(super) fn type_4(layout: &Layout, candidates: &[Candidates]) -> Option<Step> {
+    rectangles(layout, candidates)
+        .into_iter()
+        .find_map(|rectangle| {
+            let [a, b] = roof(&rectangle, candidates)?;
+            let roof = [rectangle.cells[a], rectangle.cells[b]];
+
+            shared_houses(layout, roof).into_iter().find_map(|house| {
+                rectangle.digits.digits().find_map(|digit| {
+                    if !is_confined(layout, candidates, house, digit, &roof) {
+                        return None;
+                    }
+
+                    let other = rectangle.digits.difference(digit.into()).first()?;
+
+                    rectangle.step(
+                        layout,
+                        Technique::UniqueRectangleType4,
+                        vec![house],
+                        roof.iter().map(|&i| (i, other)).collect(),
+                    )
+                })
+            })
+        })
+}
+
+// Two opposite corners, or three corners, share a single extra candidate, which one of them must
+// take
+pub(super) fn type_5(layout: &Layout, candidates: &[Candidates]) -> Option<Step> {
+    rectangles(layout, candidates)
+        .into_iter()
+        .find_map(|rectangle| {
+            let corners = rectangle.with_extras(candidates);
+            let extras = rectangle.extras(candidates);
+
+            let is_diagonal = matches!(corners[..], [a, b] if b - a == 2);
+
+            if !(is_diagonal || corners.len() == 3)
+                || corners.iter().any(|&k| extras[k] != extras[corners[0]])
+                || extras[corners[0]].count() != 1
+            {
+                return None;
+            }
+
+            let digit = extras[corners[0]].first()?;
+            let cells = corners
+                .iter()
+                .map(|&k| rectangle.cells[k])
+                .collect::<Vec<_>>();
+
+            let eliminations = common_peer_eliminations(layout, candidates, &cells, digit);
+
+            rectangle.step(
+                layout,
+                Technique::UniqueRectangleType5,
+                vec![],
+                eliminations,
+            )
+        })
+}
+
+// Two opposite corners have extra candidates, and one of the rectangle's digits only fits within
+// the rectangle in both of its rows, or both of its columns. The digit then takes two opposite
+// corners, which can't be the ones left with the rectangle's digits alone.
+pub(super) fn type_6(layout: &Layout, candidates: &[Candidates]) -> Option<Step> {
+    rectangles(layout, candidates)
+        .into_iter()
+        .find_map(|rectangle| {
+            let corners = rectangle.with_extras(candidates);
+
+            let [a, b] = corners[..] else {
+                return None;
+            };
+
+            if b - a != 2 {
+                return None;
+            }
+
+            let [c0, c1, c2, c3] = rectangle.cells;
+            let (Coord(top, left), Coord(bottom, right)) = (layout.coord(c0), layout.coord(c2));
+
+            rectangle.digits.digits().find_map(|digit| {
+                let lines = [
+                    [House::Row(top), House::Row(bottom)],
+                    [House::Col(left), House::Col(right)],
+                ];
+
+                let houses = lines.into_iter().find(|lines| {
+                    lines.iter().all(|&line| {
+                        is_confined(layout, candidates, line, digit, &[c0, c1, c2, c3])
+                    })
+                })?;
+
+                let eliminations = [rectangle.cells[a], rectangle.cells[b]]
+                    .map(|i| (i, digit))
+                    .to_vec();
+
+                rectangle.step(
+                    layout,
+                    Technique::UniqueRectangleType6,
+                    houses.to_vec(),
+                    eliminations,
+                )
+            })
+        })
+}
+
+// A corner is left with the rectangle's digits alone, and one of them only fits within the
+// rectangle in both the row and the column of the opposite corner. That corner can't take the other
+// digit, or the digit would take the remaining corners, leaving all four with the two digits.
+pub(super) fn hidden_rectangle(layout: &Layout, candidates: &[Candidates]) -> Option<Step> {
+    rectangles(layout, candidates)
+        .into_iter()
+        .find_map(|rectangle| {
+            let extras = rectangle.extras(candidates);
+
+            (0..4).filter(|&k| extras[k].is_empty()).find_map(|k| {
+                let opposite = rectangle.cells[(k + 2) % 4];
+                let Coord(row, col) = layout.coord(opposite);
+
+                rectangle.digits.digits().find_map(|digit| {
+                    let houses = vec![House::Row(row), House::Col(col)];
+
+                    if !houses.iter().all(|&house| {
+                        is_confined(layout, candidates, house, digit, &rectangle.cells)
+                    }) {
+                        return None;
+                    }
+
+                    let other = rectangle.digits.difference(digit.into()).first()?;
+
+                    rectangle.step(
+                        layout,
+                        Technique::HiddenRectangle,
+                        houses,
+                        vec![(opposite, other)],
+                    )
+                })
+            })
+        })
+}
+
+// Bivalue Universal Grave: were every unsolved cell left with two candidates, each fitting twice
+// in each of their houses, the candidates could be taken in either of two ways. With a single cell
+// left with three candidates, it must take the one fitting three times in each of its houses.
+pub(super) fn bug_plus_one(layout: &Layout, candidates: &[Candidates]) -> Option<Step> {
+    let mut extra = None;
+
+    for (i, candidates) in candidates.iter().enumerate() {
+        match candidates.count() {
+            0 | 2 => {}
+            3 if extra.is_none() => extra = Some(i),
+            _ => return None,
+        }
+    }
+
+    let cell = extra?;
+
+    let count = |house: House, digit: Digit| {
+        layout
+            .house_indices(house)
+            .iter()
+            .filter(|&&i| candidates[i].contains(digit))
+            .count()
+    };
+
+    let digit = candidates[cell].digits().find(|&digit| {
+        layout
+            .houses_of_cell(cell)
+            .all(|house| count(house, digit) == 3)
+    })?;
+
+    let is_grave = layout.houses().all(|house| {
+        let has_cell = layout.house_indices(house).contains(&cell);
+
+        layout.digits().all(|other| match count(house, other) {
+            3 => has_cell && other == digit,
+            count => count == 0 || count == 2,
+        })
+    });
+
+    is_grave.then(|| Step::placement(layout, Technique::BugPlusOne, vec![], cell, digit))
+}
+
+// Two neighboring corners with extra candidates, when the other two have none
+fn roof(rectangle: &Rectangle, candidates: &[Candidates]) -> Option<[usize; 2]> {
+    match rectangle.with_extras(candidates)[..] {
+        [a, b] if b - a != 2 => Some([a, b]),
+        _ => None,
+    }
+}
+
+fn shared_houses(layout: &Layout, [a, b]: [usize; 2]) -> Vec<House> {
+    layout
+        .houses_of_cell(a)
+        .filter(|&house| layout.house_indices(house).contains(&b))
+        .collect()
+}
+
+// Whether the digit only fits within the given cells in the house
+fn is_confined(
+    layout: &Layout,
+    candidates: &[Candidates],
+    house: House,
+    digit: Digit,
+    cells: &[usize],
+) -> bool {
+    layout
+        .house_indices(house)
+        .iter()
+        .all(|i| !candidates[*i].contains(digit) || cells.contains(i))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{Cage, LogicalSolver, Sudoku};
+    use crate::solve::logical::tests::{candidates_with, find};
+
+    // Every technique of the file, which only the one the candidates lay out should find
+    const TECHNIQUES: [Technique; 8] = [
+        Technique::UniqueRectangleType1,
+        Technique::UniqueRectangleType2,
+        Technique::UniqueRectangleType3,
+        Technique::UniqueRectangleType4,
+        Technique::UniqueRectangleType5,
+        Technique::UniqueRectangleType6,
+        Technique::HiddenRectangle,
+        Technique::BugPlusOne,
+    ];
+
+    // The rectangle on 1 and 2 at r1c1, r1c4, r2c4 and r2c1, spread over boxes 1 and 2
+    const CORNERS: [Coord; 4] = [Coord(0, 0), Coord(0, 3), Coord(1, 3), Coord(1, 0)];
+
+    fn find_only(technique: Technique, candidates: &[Candidates]) -> Step {
+        for other in TECHNIQUES.into_iter().filter(|&other| other != technique) {
+            assert_eq!(find(other, candidates), None, "{other}");
+        }
+
+        let step = find(technique, candidates).unwrap();
+
+        assert_eq!(
+            step.pattern,
+            Some(Pattern::Rectangle {
+                digits: vec![Digit(1), Digit(2)],
+                corners: CORNERS.to_vec(),
+            })
+        );
+
+        step
+    }
+
+    // The corners with their candidates, clockwise from r1c1. Corners without any keep every digit.
+    fn rectangle(corners: [&[u8]; 4]) -> Vec<Candidates> {
+        let cells = CORNERS
+            .into_iter()
+            .zip(corners)
+            .filter(|(_, digits)| !digits.is_empty())
+            .collect::<Vec<_>>();
+
+        candidates_with(&cells)
+    }
+
+    fn eliminations(digit: u8, coords: impl IntoIterator<Item = Coord>) -> Vec<(Coord, Digit)> {
+        coords
+            .into_iter()
+            .map(|coord| (coord, Digit(digit)))
+            .collect()
+    }
+
+    // The rest of row 2, outside the rectangle
+    fn rest_of_row_2() -> impl Iterator<Item = Coord> {
+        [1, 2, 4, 5, 6, 7, 8].into_iter().map(|col| Coord(1, col))
+    }
+
+    #[test]
+    fn finds_type_1() {
+        let candidates = rectangle([&[1, 2], &[1, 2], &[1, 2], &[1, 2, 3]]);
+        let step = find_only(Technique::UniqueRectangleType1, &candidates);
+
+        assert_eq!(
+            step.eliminations,
+            [(Coord(1, 0), Digit(1)), (Coord(1, 0), Digit(2))]
+        );
+    }
+
+    // r2c1 or r2c4 is 3, which the rest of row 2 sees
+    #[test]
+    fn finds_type_2() {
+        let candidates = rectangle([&[1, 2], &[1, 2], &[1, 2, 3], &[1, 2, 3]]);
+        let step = find_only(Technique::UniqueRectangleType2, &candidates);
+
+        assert_eq!(step.eliminations, eliminations(3, rest_of_row_2()));
+    }
+
+    // r2c1 and r2c4 hold 3 or 4 between them, which makes a naked pair with r2c7
+    #[test]
+    fn finds_type_3() {
+        let mut candidates = rectangle([&[1, 2], &[1, 2], &[1, 2, 4], &[1, 2, 3]]);
+        candidates[9 + 6] = Digit(3).into();
+        candidates[9 + 6].add(Digit(4));
+
+        let step = find_only(Technique::UniqueRectangleType3, &candidates);
+
+        assert_eq!(step.houses, [House::Row(1)]);
+        assert_eq!(step.cells[4..], [Coord(1, 6)]);
+        assert_eq!(
+            step.eliminations,
+            rest_of_row_2()
+                .filter(|&coord| coord != Coord(1, 6))
+                .flat_map(|coord| [(coord, Digit(3)), (coord, Digit(4))])
+                .collect::<Vec<_>>()
+        );
+    }
+
+    // 1 only fits in r2c1 and r2c4 of row 2, so neither is 2
+    #[test]
+    fn finds_type_4() {
+        let mut candidates = rectangle([&[1, 2], &[1, 2], &[1, 2, 4], &[1, 2, 3]]);
+
+        for coord in rest_of_row_2() {
+            candidates[coord.0 * 9 + coord.1].remove(Digit(1));
+        }
+
+        let step = find_only(Technique::UniqueRectangleType4, &candidates);
+
+        assert_eq!(step.houses, [House::Row(1)]);
+        assert_eq!(
+            step.eliminations,
+            [(Coord(1, 3), Digit(2)), (Coord(1, 0), Digit(2))]
+        );
+    }
+
+    // The opposite corners r1c4 and r2c1 share the extra 3, so the cells seeing both lose it
+    #[test]
+    fn finds_type_5() {
+        let candidates = rectangle([&[1, 2], &[1, 2, 3], &[1, 2], &[1, 2, 3]]);
+        let step = find_only(Technique::UniqueRectangleType5, &candidates);
+
+        assert_eq!(
+            step.eliminations,
+            eliminations(3, [Coord(0, 1), Coord(0, 2), Coord(1, 4), Coord(1, 5)])
+        );
+    }
+
+    // 1 only fits within the rectangle in rows 1 and 2, so it takes r1c1 and r2c4 or the other
+    // corners, which would be left with 1 and 2 alone
+    #[test]
+    fn finds_type_6() {
+        let mut candidates = rectangle([&[1, 2], &[], &[1, 2], &[]]);
+
+        for row in 0..2 {
+            for col in [1, 2, 4, 5, 6, 7, 8] {
+                candidates[row * 9 + col].remove(Digit(1));
+            }
+        }
+
+        let step = find_only(Technique::UniqueRectangleType6, &candidates);
+
+        assert_eq!(step.houses, [House::Row(0), House::Row(1)]);
+        assert_eq!(
+            step.eliminations,
+            [(Coord(0, 3), Digit(1)), (Coord(1, 0), Digit(1))]
+        );
+    }
+
+    // 1 only fits within the rectangle in row 2 and column 4, so r2c4 can't be 2, or 1 would take
+    // r1c4 and r2c1
+    #[test]
+    fn finds_hidden_rectangle() {
+        let mut candidates = rectangle([&[1, 2], &[], &[], &[]]);
+
+        for coord in rest_of_row_2().chain((2..9).map(|row| Coord(row, 3))) {
+            candidates[coord.0 * 9 + coord.1].remove(Digit(1));
+        }
+
+        let step = find_only(Technique::HiddenRectangle, &candidates);
+
+        assert_eq!(step.houses, [House::Row(1), House::Col(3)]);
+        assert_eq!(step.eliminations, [(Coord(1, 3), Digit(2))]);
+    }
+
+    // Every unsolved cell is left with two candidates, each fitting twice in each house, apart from
+    // r1c1, where 3 fits three times in each of its houses
+    #[test]
+    fn finds_bug_plus_one() {
+        let cells: [(Coord, &[u8]); 11] = [
+            (Coord(0, 0), &[1, 2, 3]),
+            (Coord(0, 4), &[1, 3]),
+            (Coord(1, 4), &[1, 3]),
+            (Coord(1, 1), &[1, 3]),
+            (Coord(4, 1), &[1, 3]),
+            (Coord(4, 0), &[1, 3]),
+            (Coord(0, 7), &[2, 3]),
+            (Coord(2, 7), &[2, 3]),
+            (Coord(2, 2), &[2, 3]),
+            (Coord(7, 2), &[2, 3]),
+            (Coord(7, 0), &[2, 3]),
+        ];
+
+        // The other cells are solved
+        let listed = candidates_with(&cells);
+        let mut candidates = vec![Candidates::empty(); listed.len()];
+
+        for (Coord(row, col), _) in cells {
+            candidates[row * 9 + col] = listed[row * 9 + col];
+        }
+
+        for technique in TECHNIQUES
+            .into_iter()
+            .filter(|&t| t != Technique::BugPlusOne)
+        {
+            assert_eq!(find(technique, &candidates), None, "{technique}");
+        }
+
+        let step = find(Technique::BugPlusOne, &candidates).unwrap();
+
+        assert_eq!(step.placements, [(Coord(0, 0), Digit(3))]);
+        assert_eq!(step.eliminations, []);
+    }
+
+    // Two solutions, which swap 1 and 3 around r4c6, r4c9, r5c9 and r5c6
+    const TWO_SOLUTIONS: &str =
+        "530678912672100048108342507859760420426850790713024056961537084287419630345206179";
+
+    // Assuming a single solution, r5c9 can't be 3. A cage picking the solution where it is tells
+    // the rectangle's digits apart, so the solver stops assuming anything about it.
+    #[test]
+    fn leaves_sudokus_with_cages_alone() {
+        let solver = LogicalSolver::new().with_uniqueness();
+        let mut sudoku = TWO_SOLUTIONS.parse::<Sudoku>().unwrap();
+        assert_eq!(sudoku.solutions().count(), 2);
+
+        let step = loop {
+            let step = solver.next_step(&sudoku).unwrap();
+
+            if step.technique.assumes_uniqueness() {
+                break step;
+            }
+
+            sudoku.apply_step(&step);
+        };
+
+        assert_eq!(step.technique, Technique::HiddenRectangle);
+        assert_eq!(step.eliminations, [(Coord(4, 8), Digit(3))]);
+
+        sudoku
+            .add_cage(Cage::new(5, [Coord(2, 5), Coord(3, 5)]))
+            .unwrap();
+
+        let solutions = sudoku.solutions().collect::<Vec<_>>();
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(
+            solutions[0].cell(Coord(4, 8)).unwrap().digit,
+            Some(Digit(3))
+        );
+
+        let step = solver.next_step(&sudoku).unwrap();
+        assert!(!step.technique.assumes_uniqueness(), "{step}");
+    }
+}
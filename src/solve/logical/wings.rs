@@ -0,0 +1,336 @@
+use crate::prelude::{Candidates, Digit, Layout};
+
+use super::{combinations, common_peer_eliminations, sees, Pattern, Step, Technique};
+
+// A bivalue pivot xy seeing the pincers xz and yz: whichever digit the pivot takes, one of the
+// pincers is left with z, so z can be removed from the cells seeing both pincers
+pub(super) fn xy_wing(layout: &Layout, candidates: &[Candidates]) -> Option<Step> {
+    for pivot in cells_with_count(candidates, 2..=2) {
+        let pincers = layout
+            .peers(pivot)
+            .iter()
+            .copied()
+            .filter(|&i| {
+                candidates[i].count() == 2
+                    && candidates[i].intersection(candidates[pivot]).count() == 1
+            })
+            .collect::<Vec<_>>();
+
+        for pair in combinations(&pincers, 2) {
+            let [a, b] = [pair[0], pair[1]];
+            let z = candidates[a].intersection(candidates[b]);
+
+            if z.count() != 1
+                || z.intersection(candidates[pivot]).count() != 0
+                || candidates[a] == candidates[b]
+            {
+                continue;
+            }
+
+            let z = z.first().unwrap();
+
+            if let Some(step) = wing(
+                layout,
+                candidates,
+                Technique::XYWing,
+                z,
+                &[pivot],
+                &pair,
+                &pair,
+            ) {
+                return Some(step);
+            }
+        }
+    }
+
+    None
+}
+
+// A pivot xyz seeing the pincers xz and yz: z is in the pivot or one of the pincers, so it can be
+// removed from the cells seeing all three
+pub(super) fn xyz_wing(layout: &Layout, candidates: &[Candidates]) -> Option<Step> {
+    for pivot in cells_with_count(candidates, 3..=3) {
+        let pincers = layout
+            .peers(pivot)
+            .iter()
+            .copied()
+            .filter(|&i| {
+                candidates[i].count() == 2 && candidates[i].difference(candidates[pivot]).is_empty()
+            })
+            .collect::<Vec<_>>();
+
+        for pair in combinations(&pincers, 2) {
+            let [a, b] = [pair[0], pair[1]];
+            let z = candidates[a].intersection(candidates[b]);
+
+            if z.count() != 1 {
+                continue;
+            }
+
+            let z = z.first().unwrap();
+
+            if let Some(step) = wing(
+                layout,
+                candidates,
+                Technique::XYZWing,
+                z,
+                &[pivot],
+                &pair,
+                &[pivot, a, b],
+            ) {
+                return Some(step);
+            }
+        }
+    }
+
+    None
+}
+
+// Two bivalue cells xy that don't see each other, joined by a strong link on x: a house where x
+// only fits in two cells, each seeing one of them. Either end of the link is x, so one of the
+// bivalue cells is y, and y can be removed from the cells seeing both of them.
+pub(super) fn w_wing(layout: &Layout, candidates: &[Candidates]) -> Option<Step> {
+    let bivalue = cells_with_count(candidates, 2..=2).collect::<Vec<_>>();
+
+    for pair in combinations(&bivalue, 2) {
+        let [a, b] = [pair[0], pair[1]];
+
+        if candidates[a] != candidates[b] || sees(layout, a, b) {
+            continue;
+        }
+
+        for x in candidates[a].digits() {
+            let y = candidates[a].difference(x.into()).first().unwrap();
+
+            for house in layout.houses() {
+                let link = layout
+                    .house_indices(house)
+                    .iter()
+                    .copied()
+                    .filter(|&i| candidates[i].contains(x))
+                    .collect::<Vec<_>>();
+
+                let [p, q] = link[..] else {
+                    continue;
+                };
+
+                let pivot = if sees(layout, p, a) && sees(layout, q, b) {
+                    [p, q]
+                } else if sees(layout, q, a) && sees(layout, p, b) {
+                    [q, p]
+                } else {
+                    continue;
+                };
+
+                if let Some(mut step) = wing(
+                    layout,
+                    candidates,
+                    Technique::WWing,
+                    y,
+                    &pivot,
+                    &pair,
+                    &pair,
+                ) {
+                    step.houses.push(house);
+                    return Some(step);
+                }
+            }
+        }
+    }
+
+    None
+}
+
+// A pivot seeing three pincers, the four cells holding four digits between them. When every digit
+// but z only appears in cells that see each other, each of those digits takes at most one of the
+// cells, which leaves z for at least one of them. It can then be removed from the cells seeing
+// every cell of the wing with z.
+pub(super) fn wxyz_wing(layout: &Layout, candidates: &[Candidates]) -> Option<Step> {
+    for pivot in cells_with_count(candidates, 2..=4) {
+        let pincers = layout
+            .peers(pivot)
+            .iter()
+            .copied()
+            .filter(|&i| {
+                candidates[i].count() >= 2 && candidates[i].union(candidates[pivot]).count() <= 4
+            })
+            .collect::<Vec<_>>();
+
+        for trio in combinations(&pincers, 3) {
+            let cells = [pivot, trio[0], trio[1], trio[2]];
+
+            let digits = cells
+                .iter()
+                .fold(Candidates::empty(), |acc, &i| acc.union(candidates[i]));
+
+            if digits.count() != 4 {
+                continue;
+            }
+
+            let cells_with = |digit: Digit| {
+                cells
+                    .iter()
+                    .copied()
+                    .filter(|&i| candidates[i].contains(digit))
+                    .collect::<Vec<_>>()
+            };
+
+            let mut unrestricted = digits.digits().filter(|&digit| {
+                let cells = cells_with(digit);
+                combinations(&cells, 2)
+                    .iter()
+                    .any(|pair| !sees(layout, pair[0], pair[1]))
+            });
+
+            let (Some(z), None) = (unrestricted.next(), unrestricted.next()) else {
+                continue;
+            };
+
+            if let Some(step) = wing(
+                layout,
+                candidates,
+                Technique::WXYZWing,
+                z,
+                &[pivot],
+                &trio,
+                &cells_with(z),
+            ) {
+                return Some(step);
+            }
+        }
+    }
+
+    None
+}
+
+// The unsolved cells with a number of candidates within the range
+fn cells_with_count(
+    candidates: &[Candidates],
+    counts: std::ops::RangeInclusive<usize>,
+) -> impl Iterator<Item = usize> + '_ {
+    (0..candidates.len()).filter(move |&i| counts.contains(&candidates[i].count()))
+}
+
+// Removes the digit from the cells that see every one of `sources`, the cells of the wing that
+// may hold it
+fn wing(
+    layout: &Layout,
+    candidates: &[Candidates],
+    technique: Technique,
+    digit: Digit,
+    pivot: &[usize],
+    pincers: &[usize],
+    sources: &[usize],
+) -> Option<Step> {
+    let eliminations = common_peer_eliminations(layout, candidates, sources, digit);
+    let cells = pivot.iter().chain(pincers).copied().collect::<Vec<_>>();
+
+    Step::elimination(layout, technique, vec![], &cells, eliminations).map(|step| {
+        step.with_pattern(Pattern::Wing {
+            digit,
+            pivot: pivot.iter().map(|&i| layout.coord(i)).collect(),
+            pincers: pincers.iter().map(|&i| layout.coord(i)).collect(),
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{Coord, House};
+    use crate::solve::logical::tests::{candidates_with, find};
+
+    #[test]
+    fn finds_xy_wing() {
+        let candidates = candidates_with(&[
+            (Coord(0, 0), &[1, 2]),
+            (Coord(0, 4), &[1, 3]),
+            (Coord(4, 0), &[2, 3]),
+        ]);
+        let step = find(Technique::XYWing, &candidates).unwrap();
+
+        assert_eq!(step.eliminations, [(Coord(4, 4), Digit(3))]);
+        assert_eq!(
+            step.pattern,
+            Some(Pattern::Wing {
+                digit: Digit(3),
+                pivot: vec![Coord(0, 0)],
+                pincers: vec![Coord(0, 4), Coord(4, 0)],
+            })
+        );
+    }
+
+    // Only the cells seeing the pivot too lose the digit, which are in its box and column
+    #[test]
+    fn finds_xyz_wing() {
+        let candidates = candidates_with(&[
+            (Coord(0, 0), &[1, 2, 3]),
+            (Coord(0, 1), &[1, 3]),
+            (Coord(4, 0), &[2, 3]),
+        ]);
+        assert_eq!(find(Technique::XYWing, &candidates), None);
+        let step = find(Technique::XYZWing, &candidates).unwrap();
+
+        assert_eq!(
+            step.eliminations,
+            [(Coord(1, 0), Digit(3)), (Coord(2, 0), Digit(3))]
+        );
+    }
+
+    // 1 only fits in r9c1 and r9c9 of row 9, which see r1c1 and r5c9 respectively
+    #[test]
+    fn finds_w_wing() {
+        let mut candidates = candidates_with(&[(Coord(0, 0), &[1, 2]), (Coord(4, 8), &[1, 2])]);
+
+        for col in 1..8 {
+            candidates[8 * 9 + col].remove(Digit(1));
+        }
+
+        assert_eq!(find(Technique::XYWing, &candidates), None);
+        assert_eq!(find(Technique::XYZWing, &candidates), None);
+        let step = find(Technique::WWing, &candidates).unwrap();
+
+        assert_eq!(
+            step.eliminations,
+            [(Coord(0, 8), Digit(2)), (Coord(4, 0), Digit(2))]
+        );
+        assert_eq!(step.houses, [House::Row(8)]);
+        assert_eq!(
+            step.pattern,
+            Some(Pattern::Wing {
+                digit: Digit(2),
+                pivot: vec![Coord(8, 0), Coord(8, 8)],
+                pincers: vec![Coord(0, 0), Coord(4, 8)],
+            })
+        );
+    }
+
+    // Every digit but 4 stays within cells that see each other, and 4 is left for one of the
+    // pincers
+    #[test]
+    fn finds_wxyz_wing() {
+        let candidates = candidates_with(&[
+            (Coord(0, 0), &[1, 2, 3]),
+            (Coord(0, 1), &[1, 4]),
+            (Coord(0, 2), &[2, 4]),
+            (Coord(4, 0), &[3, 4]),
+        ]);
+        assert_eq!(find(Technique::XYWing, &candidates), None);
+        assert_eq!(find(Technique::XYZWing, &candidates), None);
+        assert_eq!(find(Technique::WWing, &candidates), None);
+        let step = find(Technique::WXYZWing, &candidates).unwrap();
+
+        assert_eq!(
+            step.eliminations,
+            [(Coord(1, 0), Digit(4)), (Coord(2, 0), Digit(4))]
+        );
+        assert_eq!(
+            step.pattern,
+            Some(Pattern::Wing {
+                digit: Digit(4),
+                pivot: vec![Coord(0, 0)],
+                pincers: vec![Coord(0, 1), Coord(0, 2), Coord(4, 0)],
+            })
+        );
+    }
+}
@@ -0,0 +1,253 @@
+use crate::prelude::{Candidates, Digit, MultiSudoku, Sudoku};
+
+use super::search::{Search, SearchSpace};
+
+// TODO: docs
+
+// Solves overlapping grids as a single puzzle, so that placing a digit in a shared cell narrows
+// down the candidates of every grid that covers it
+#[derive(Default)]
+pub struct MultiSolver;
+
+impl MultiSolver {
+    pub const fn new() -> Self {
+        Self
+    }
+
+    pub fn solutions(self, multi: &MultiSudoku) -> MultiSolutions {
+        MultiSolutions::new(multi.clone())
+    }
+
+    pub fn count_solutions(self, multi: &MultiSudoku, limit: usize) -> usize {
+        self.solutions(multi).take(limit).count()
+    }
+
+    pub fn solve(self, multi: &mut MultiSudoku) -> bool {
+        let Some(solution) = self.solutions(multi).next() else {
+            return false;
+        };
+
+        *multi = solution;
+        true
+    }
+}
+
+// Lazily enumerates every solution, searching over the cells of the whole canvas
+pub struct MultiSolutions(Search<MultiSudoku>);
+
+impl MultiSolutions {
+    fn new(multi: MultiSudoku) -> Self {
+        Self(Search::new(multi))
+    }
+}
+
+impl Iterator for MultiSolutions {
+    type Item = MultiSudoku;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
+
+// Without a bitboard, the candidates of each guess are always kept
+impl SearchSpace for MultiSudoku {
+    type Solution = Self;
+
+    fn size(&self) -> usize {
+        self.size()
+    }
+
+    fn digit(&self, index: usize) -> Option<Digit> {
+        self.digit(index)
+    }
+
+    fn place(&mut self, index: usize, digit: Digit) {
+        self.set_digit(index, digit);
+    }
+
+    fn clear(&mut self, index: usize) {
+        self.clear_digit(index);
+    }
+
+    fn initial_candidates(&self) -> Option<Vec<Candidates>> {
+        let mut all_candidates = (0..self.size())
+            .map(|index| SearchSpace::candidates(self, index))
+            .collect::<Vec<_>>();
+
+        restrict_candidates(self, &mut all_candidates);
+        Some(all_candidates)
+    }
+
+    fn candidates(&self, index: usize) -> Candidates {
+        match self.digit(index) {
+            Some(_) => Candidates::empty(),
+            None => self.cell_candidates(index),
+        }
+    }
+
+    fn narrow_candidates(&self, index: usize, digit: Digit, all_candidates: &mut [Candidates]) {
+        all_candidates[index] = Candidates::empty();
+
+        for &peer in self.peers(index) {
+            all_candidates[peer].remove(digit);
+        }
+
+        restrict_candidates(self, all_candidates);
+    }
+
+    fn house_count(&self) -> usize {
+        self.houses().len()
+    }
+
+    fn house_indices(&self, id: usize) -> &[usize] {
+        &self.houses()[id]
+    }
+
+    fn missing_digits(&self, id: usize) -> Candidates {
+        let placed = self.houses()[id]
+            .iter()
+            .filter_map(|&index| self.digit(index))
+            .fold(Candidates::empty(), |acc, digit| acc.union(digit.into()));
+
+        self.layout().candidates().difference(placed)
+    }
+
+    fn is_valid(&self) -> bool {
+        self.is_valid()
+    }
+
+    fn is_solution(&self) -> bool {
+        !self.grids().iter().any(Sudoku::has_constraints) || self.is_valid()
+    }
+
+    fn solution(&self) -> Self {
+        self.clone()
+    }
+}
+
+// Lets the constraints of each grid prune the candidates of its own cells
+fn restrict_candidates(multi: &MultiSudoku, all_candidates: &mut [Candidates]) {
+    for (grid, sudoku) in multi.grids().iter().enumerate() {
+        if !sudoku.has_constraints() {
+            continue;
+        }
+
+        let cells = (0..sudoku.size())
+            .map(|i| multi.index_of(grid, i).expect("grid exists"))
+            .collect::<Vec<_>>();
+
+        let mut grid_candidates = cells
+            .iter()
+            .map(|&index| all_candidates[index])
+            .collect::<Vec<_>>();
+
+        for constraint in sudoku.constraints() {
+            constraint.restrict_candidates(sudoku, &mut grid_candidates);
+        }
+
+        for (index, candidates) in cells.into_iter().zip(grid_candidates) {
+            all_candidates[index] = all_candidates[index].intersection(candidates);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{
+        AntiKing, AntiKnight, BruteForceSolver, Coord, Gattai, Layout, NonConsecutive, SudokuConfig,
+    };
+
+    const PUZZLE: &str =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+
+    #[test]
+    fn single_grid_agrees_with_brute_force() {
+        let mut sudoku = PUZZLE.parse::<Sudoku>().unwrap();
+
+        for col in 0..9 {
+            sudoku.clear_cell(Coord(0, col));
+            sudoku.clear_cell(Coord(1, col));
+        }
+
+        let multi = MultiSudoku::from_grids(vec![sudoku.clone()], [Coord(0, 0)]).unwrap();
+        assert_eq!(
+            multi.count_solutions(usize::MAX),
+            BruteForceSolver.count_solutions(&sudoku, usize::MAX)
+        );
+
+        let sudoku = Sudoku::new_empty()
+            .with_cell(Coord(4, 2), Digit(1))
+            .with_cell(Coord(5, 6), Digit(2))
+            .with_constraint(AntiKnight)
+            .unwrap()
+            .with_constraint(AntiKing)
+            .unwrap()
+            .with_constraint(NonConsecutive)
+            .unwrap();
+
+        let multi = MultiSudoku::from_grids(vec![sudoku.clone()], [Coord(0, 0)]).unwrap();
+        let solutions = multi.solutions().collect::<Vec<_>>();
+
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(
+            solutions[0].grids(),
+            BruteForceSolver.solutions(&sudoku).collect::<Vec<_>>()
+        );
+    }
+
+    // Every solution of the first grid extends to as many solutions of the twin as the second
+    // grid has once given the shared box
+    #[test]
+    fn counts_twin_solutions() {
+        let layout = Layout::new(2);
+        let multi = MultiSudoku::twin_of(layout.clone()).unwrap();
+
+        let expected = BruteForceSolver
+            .solutions(&Sudoku::new_empty_with_layout(layout.clone()))
+            .map(|first| {
+                let mut second = Sudoku::new_empty_with_layout(layout.clone());
+
+                for (row, col) in [(0, 0), (0, 1), (1, 0), (1, 1)] {
+                    let cell = first.cell(Coord(row + 2, col + 2)).unwrap();
+                    second.set_cell(Coord(row, col), cell.digit.unwrap());
+                }
+
+                BruteForceSolver.count_solutions(&second, usize::MAX)
+            })
+            .sum::<usize>();
+
+        assert_eq!(multi.count_solutions(usize::MAX), expected);
+    }
+
+    #[test]
+    fn solves_generated_samurai() {
+        let config = SudokuConfig::new(0).with_seed(0);
+        let mut solved = Gattai.generate(&MultiSudoku::samurai(), config);
+
+        assert!(solved.is_unique());
+        assert!(MultiSolver.solve(&mut solved));
+        assert!(solved.is_filled() && solved.is_valid());
+
+        // The middle grid shares a corner box with each of the others, such as its top left box
+        // with the bottom right box of the first grid
+        let digit =
+            |grid: usize, coord: Coord| solved.grid(grid).unwrap().cell(coord).unwrap().digit;
+
+        for (grid, grid_box, middle_box) in [
+            (0, Coord(6, 6), Coord(0, 0)),
+            (1, Coord(6, 0), Coord(0, 6)),
+            (3, Coord(0, 6), Coord(6, 0)),
+            (4, Coord(0, 0), Coord(6, 6)),
+        ] {
+            for row in 0..3 {
+                for col in 0..3 {
+                    assert_eq!(
+                        digit(grid, Coord(grid_box.row() + row, grid_box.col() + col)),
+                        digit(2, Coord(middle_box.row() + row, middle_box.col() + col))
+                    );
+                }
+            }
+        }
+    }
+}
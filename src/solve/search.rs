@@ -0,0 +1,203 @@
+use crate::prelude::{Candidates, Digit};
+
+// TODO: tests
+// TODO: docs
+
+// What the depth-first search fills in: cells numbered from 0 to `size`, grouped into houses where
+// each digit goes once
+pub(super) trait SearchSpace {
+    type Solution;
+
+    fn size(&self) -> usize;
+
+    fn digit(&self, index: usize) -> Option<Digit>;
+
+    fn place(&mut self, index: usize, digit: Digit);
+
+    fn clear(&mut self, index: usize);
+
+    // The candidates of each cell to start from, which are then kept along with each guess. `None`
+    // has them recomputed from the digits placed so far with `candidates` instead, which doesn't
+    // allocate.
+    fn initial_candidates(&self) -> Option<Vec<Candidates>>;
+
+    fn candidates(&self, index: usize) -> Candidates;
+
+    // Narrows the kept candidates down after placing `digit` in `index`
+    fn narrow_candidates(&self, index: usize, digit: Digit, all_candidates: &mut [Candidates]);
+
+    fn house_count(&self) -> usize;
+
+    fn house_indices(&self, id: usize) -> &[usize];
+
+    // The digits that aren't placed in the house yet
+    fn missing_digits(&self, id: usize) -> Candidates;
+
+    // Whether the digits placed so far break no rule
+    fn is_valid(&self) -> bool;
+
+    // The search never breaks a house, but constraints only prune soundly, so full grids are
+    // checked against them
+    fn is_solution(&self) -> bool {
+        self.is_valid()
+    }
+
+    fn solution(&self) -> Self::Solution;
+}
+
+// Lazily enumerates every solution with a depth-first search, keeping the digits left to try for
+// each guess on an explicit stack. Digits are placed and cleared in place.
+pub(super) struct Search<S> {
+    space: S,
+    stack: Vec<Frame>,
+    // Where candidates are recomputed when they aren't kept, so going deeper doesn't allocate
+    buffer: Vec<Candidates>,
+    filled: bool,
+}
+
+struct Frame {
+    index: usize,
+    remaining: Candidates,
+    all_candidates: Option<Vec<Candidates>>,
+}
+
+impl<S: SearchSpace> Search<S> {
+    pub(super) fn new(space: S) -> Self {
+        let mut search = Self {
+            buffer: vec![Candidates::empty(); space.size()],
+            space,
+            stack: vec![],
+            filled: false,
+        };
+
+        if !search.space.is_valid() {
+            return search;
+        }
+
+        let all_candidates = search.space.initial_candidates();
+
+        match search.next_guess(all_candidates.as_deref()) {
+            Some((index, remaining)) => search.stack.push(Frame {
+                index,
+                remaining,
+                all_candidates,
+            }),
+            None => search.filled = true,
+        }
+
+        search
+    }
+
+    // Branches on the cell with the least candidates, unless a digit has a single position left
+    // within a house, or none at all. Branching on digits with more positions scatters the search
+    // across the grid, which gets lost easily once other constraints are involved. Returns the cell
+    // along with the digits to try, which are empty at a dead end, or `None` once solved.
+    fn next_guess(&mut self, all_candidates: Option<&[Candidates]>) -> Option<(usize, Candidates)> {
+        let all_candidates = match all_candidates {
+            Some(all_candidates) => all_candidates,
+            None => {
+                for (index, candidates) in self.buffer.iter_mut().enumerate() {
+                    *candidates = self.space.candidates(index);
+                }
+
+                &self.buffer
+            }
+        };
+
+        let mut best: Option<(usize, Candidates)> = None;
+
+        for (index, &candidates) in all_candidates.iter().enumerate() {
+            if self.space.digit(index).is_some() {
+                continue;
+            }
+
+            if candidates.count() <= 1 {
+                return Some((index, candidates));
+            }
+
+            if best.is_none_or(|(_, best)| candidates.count() < best.count()) {
+                best = Some((index, candidates));
+            }
+        }
+
+        let (best_index, _) = best?;
+
+        for id in 0..self.space.house_count() {
+            let indices = self.space.house_indices(id);
+
+            // The digits seen in at least one and at least two cells of the house
+            let (once, twice) = indices.iter().fold(
+                (Candidates::empty(), Candidates::empty()),
+                |(once, twice), &index| {
+                    let candidates = all_candidates[index];
+                    (
+                        once.union(candidates),
+                        twice.union(once.intersection(candidates)),
+                    )
+                },
+            );
+
+            let missing = self.space.missing_digits(id);
+
+            if !missing.difference(once).is_empty() {
+                return Some((best_index, Candidates::empty()));
+            }
+
+            if let Some(digit) = missing.difference(twice).first() {
+                let index = indices
+                    .iter()
+                    .copied()
+                    .find(|&index| all_candidates[index].contains(digit))
+                    .expect("digit has a position in the house");
+
+                return Some((index, digit.into()));
+            }
+        }
+
+        best
+    }
+}
+
+impl<S: SearchSpace> Iterator for Search<S> {
+    type Item = S::Solution;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.filled {
+            self.filled = false;
+            return Some(self.space.solution());
+        }
+
+        loop {
+            let frame = self.stack.last_mut()?;
+            let index = frame.index;
+
+            let Some(digit) = frame.remaining.first() else {
+                self.space.clear(index);
+                self.stack.pop();
+                continue;
+            };
+
+            frame.remaining.remove(digit);
+            let all_candidates = frame.all_candidates.clone();
+
+            self.space.clear(index);
+            self.space.place(index, digit);
+
+            let all_candidates = all_candidates.map(|mut all_candidates| {
+                self.space
+                    .narrow_candidates(index, digit, &mut all_candidates);
+                all_candidates
+            });
+
+            match self.next_guess(all_candidates.as_deref()) {
+                Some((index, remaining)) => self.stack.push(Frame {
+                    index,
+                    remaining,
+                    all_candidates,
+                }),
+                None if !self.space.is_solution() => {}
+                None => return Some(self.space.solution()),
+            }
+        }
+    }
+}
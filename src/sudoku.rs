@@ -3,23 +3,29 @@ use std::{
     fmt::{Display, Write},
     hash::Hash,
     slice::Chunks,
+    str::FromStr,
+    sync::Arc,
 };
 
-use bitflags::bitflags;
 use derive_more::{Deref, Display};
 use rand::Rng;
 use thiserror::Error;
 
 use crate::prelude::{
-    BruteForceSolver, Generate, LatinSquares, Solve, SudokuConfig, DIGITS, DIGIT_INDICES,
-    GRID_SIZE, HOUSE_SIZE, SQUARE_SIZE,
+    BruteForceSolver, Cage, CageError, Constraint, ConstraintError, Generate, GenerateError, House,
+    LatinSquares, Layout, LogicalSolver, Rating, Solve, Step, SudokuConfig, DIGITS, GRID_SIZE,
+    HOUSE_SIZE, MAX_HOUSE_SIZE,
 };
 
-// TODO: tests
 // TODO: docs
 
-#[derive(Debug, PartialEq, Eq, Clone)]
-pub struct Sudoku(pub [Cell; GRID_SIZE]);
+#[derive(Debug, Clone)]
+pub struct Sudoku {
+    layout: Layout,
+    cells: Vec<Cell>,
+    cages: Vec<Cage>,
+    constraints: Vec<Arc<dyn Constraint>>,
+}
 
 impl Sudoku {
     #[must_use]
@@ -27,6 +33,37 @@ impl Sudoku {
         Self::default()
     }
 
+    #[must_use]
+    pub fn new_empty_with_layout(layout: Layout) -> Self {
+        let cells = (0..layout.grid_size())
+            .map(|index| Cell {
+                coord: layout.coord(index),
+                ..Cell::default()
+            })
+            .collect();
+
+        Self {
+            layout,
+            cells,
+            cages: vec![],
+            constraints: vec![],
+        }
+    }
+
+    // Fails when the number of cells doesn't match the layout
+    pub fn from_cells_with_layout(cells: Vec<Cell>, layout: Layout) -> Result<Self, Vec<Cell>> {
+        if cells.len() == layout.grid_size() {
+            Ok(Self {
+                layout,
+                cells,
+                cages: vec![],
+                constraints: vec![],
+            })
+        } else {
+            Err(cells)
+        }
+    }
+
     #[must_use]
     pub fn new_filled(config: SudokuConfig) -> Self {
         Self::new_filled_with_generator(LatinSquares, config)
@@ -37,67 +74,185 @@ impl Sudoku {
         Self::new_with_generator(LatinSquares, config)
     }
 
+    // Only fails when the config asks for a difficulty or techniques that no puzzle was found with
+    pub fn try_new_unique(config: SudokuConfig) -> Result<Self, GenerateError> {
+        Self::try_new_with_generator(LatinSquares, config)
+    }
+
     #[inline]
     #[must_use]
     pub const fn size(&self) -> usize {
-        self.0.len()
+        self.cells.len()
     }
 
+    #[inline]
     #[must_use]
-    pub fn new_with_generator(generator: impl Generate, config: SudokuConfig) -> Self {
-        generator.generate(config)
+    pub const fn layout(&self) -> &Layout {
+        &self.layout
     }
 
+    #[inline]
     #[must_use]
-    pub fn new_filled_with_generator(generator: impl Generate, config: SudokuConfig) -> Self {
-        generator.generate_filled(config)
+    pub fn house_size(&self) -> usize {
+        self.layout.house_size()
     }
 
+    #[inline]
     #[must_use]
-    pub const fn into_inner(self) -> [Cell; GRID_SIZE] {
-        self.0
+    pub fn box_size(&self) -> Option<(usize, usize)> {
+        self.layout.box_size()
     }
 
+    #[inline]
     #[must_use]
-    pub const fn as_slice(&self) -> &[Cell] {
-        &self.0
+    pub fn is_jigsaw(&self) -> bool {
+        self.layout.is_jigsaw()
     }
 
+    #[inline]
     #[must_use]
-    pub fn as_slice_mut(&mut self) -> &mut [Cell] {
-        &mut self.0
+    pub fn is_diagonal(&self) -> bool {
+        self.layout.is_diagonal()
     }
 
-    pub fn solve_with(&mut self, solver: impl Solve) -> bool {
-        solver.solve(self)
+    #[inline]
+    #[must_use]
+    pub const fn is_killer(&self) -> bool {
+        !self.cages.is_empty()
     }
 
-    pub fn count_solutions(&self, limit: usize) -> usize {
-        if self.is_filled() {
-            return 1;
-        }
+    #[inline]
+    #[must_use]
+    pub fn cages(&self) -> &[Cage] {
+        &self.cages
+    }
 
-        let mut solutions = Vec::with_capacity(limit);
+    // Fails when the cage has cells outside the grid or in another cage, or when its sum can't be
+    // made out of different digits
+    pub fn add_cage(&mut self, cage: Cage) -> Result<(), CageError> {
+        let house_size = self.house_size();
 
-        for (i, cell) in self.cells().enumerate().filter(|(_, c)| c.digit.is_none()) {
-            for digit in self.cell_candidates(i).digits() {
-                let mut sudoku = self.clone();
+        if cage.is_empty() {
+            return Err(CageError::Empty);
+        }
 
-                sudoku.set_cell(cell.coord, digit);
+        if cage.len() > house_size {
+            return Err(CageError::TooLarge {
+                max: house_size,
+                found: cage.len(),
+            });
+        }
 
-                let solved = sudoku.solve_with(BruteForceSolver::new());
+        for &coord in cage.cells() {
+            if !self.layout.contains(coord) {
+                return Err(CageError::InvalidCell { coord });
+            }
 
-                if solved && (solutions.is_empty() || solutions.iter().any(|s| s != &sudoku)) {
-                    if solutions.len() == limit {
-                        return solutions.len();
-                    } else {
-                        solutions.push(sudoku);
-                    }
-                }
+            if self.cage_of_cell(coord).is_some() {
+                return Err(CageError::OverlappingCell { coord });
             }
         }
 
-        solutions.len()
+        if Cage::combinations(self.layout.candidates(), cage.len(), cage.sum()).is_empty() {
+            return Err(CageError::ImpossibleSum {
+                sum: cage.sum(),
+                cells: cage.len(),
+            });
+        }
+
+        self.cages.push(cage);
+        Ok(())
+    }
+
+    pub fn clear_cages(&mut self) {
+        self.cages.clear();
+    }
+
+    // Every constraint on top of the houses, including cages
+    pub fn constraints(&self) -> impl Iterator<Item = &dyn Constraint> {
+        self.cages
+            .iter()
+            .map(|cage| cage as &dyn Constraint)
+            .chain(self.constraints.iter().map(AsRef::as_ref))
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn has_constraints(&self) -> bool {
+        self.is_killer() || !self.constraints.is_empty()
+    }
+
+    // Fails when the constraint refers to cells outside the grid
+    pub fn add_constraint(
+        &mut self,
+        constraint: impl Constraint + 'static,
+    ) -> Result<(), ConstraintError> {
+        constraint.check_cells(&self.layout)?;
+        self.constraints.push(Arc::new(constraint));
+        Ok(())
+    }
+
+    pub fn with_constraint(
+        mut self,
+        constraint: impl Constraint + 'static,
+    ) -> Result<Self, ConstraintError> {
+        self.add_constraint(constraint)?;
+        Ok(self)
+    }
+
+    // Removes every constraint, except for cages
+    pub fn clear_constraints(&mut self) {
+        self.constraints.clear();
+    }
+
+    #[must_use]
+    pub fn cage_of_cell<I: SudokuIndex>(&self, i: I) -> Option<&Cage> {
+        let coord = self.layout.coord(self.layout.index(i));
+        self.cages.iter().find(|cage| cage.contains(coord))
+    }
+
+    #[must_use]
+    pub fn new_with_generator(generator: impl Generate, config: SudokuConfig) -> Self {
+        generator.generate(config)
+    }
+
+    pub fn try_new_with_generator(
+        generator: impl Generate,
+        config: SudokuConfig,
+    ) -> Result<Self, GenerateError> {
+        generator.try_generate(config)
+    }
+
+    #[must_use]
+    pub fn new_filled_with_generator(generator: impl Generate, config: SudokuConfig) -> Self {
+        generator.generate_filled(config)
+    }
+
+    #[must_use]
+    pub fn into_inner(self) -> Vec<Cell> {
+        self.cells
+    }
+
+    #[must_use]
+    pub fn as_slice(&self) -> &[Cell] {
+        &self.cells
+    }
+
+    #[must_use]
+    pub fn as_slice_mut(&mut self) -> &mut [Cell] {
+        &mut self.cells
+    }
+
+    pub fn solve_with(&mut self, solver: impl Solve) -> bool {
+        solver.solve(self)
+    }
+
+    pub fn solutions(&self) -> impl Iterator<Item = Self> {
+        BruteForceSolver::new().solutions(self)
+    }
+
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        BruteForceSolver::new().count_solutions(self, limit)
     }
 
     pub fn is_unique(&self) -> bool {
@@ -106,12 +261,12 @@ impl Sudoku {
 
     #[must_use]
     pub fn count_filled_cells(&self) -> usize {
-        self.0.iter().filter_map(|cell| cell.digit).count()
+        self.cells.iter().filter_map(|cell| cell.digit).count()
     }
 
     #[must_use]
     pub fn count_unfilled_cells(&self) -> usize {
-        self.0.len() - self.count_filled_cells()
+        self.cells.len() - self.count_filled_cells()
     }
 
     #[must_use]
@@ -121,24 +276,25 @@ impl Sudoku {
 
     #[must_use]
     pub fn is_filled(&self) -> bool {
-        self.count_filled_cells() == self.0.len()
+        self.count_filled_cells() == self.cells.len()
     }
 
     #[must_use]
     pub fn cell<I: SudokuIndex>(&self, i: I) -> Option<&Cell> {
-        self.0.get(i.into_index())
+        self.cells.get(self.layout.index(i))
     }
 
     pub fn cell_mut<I: SudokuIndex>(&mut self, i: I) -> Option<&mut Cell> {
-        self.0.get_mut(i.into_index())
+        let index = self.layout.index(i);
+        self.cells.get_mut(index)
     }
 
     pub fn cells(&self) -> impl Iterator<Item = &'_ Cell> {
-        self.0.iter()
+        self.cells.iter()
     }
 
     pub fn set_cell<I: SudokuIndex>(&mut self, i: I, digit: Digit) -> Option<&mut Cell> {
-        let mut cell = self.cell_mut(i.into_index())?;
+        let cell = self.cell_mut(i)?;
         cell.digit = Some(digit);
         Some(cell)
     }
@@ -149,164 +305,128 @@ impl Sudoku {
     }
 
     pub fn clear_cell<I: SudokuIndex>(&mut self, i: I) -> Option<&mut Cell> {
-        let mut cell = self.cell_mut(i.into_index())?;
+        let cell = self.cell_mut(i)?;
         cell.digit = None;
         Some(cell)
     }
 
     #[inline]
     pub fn cells_mut(&mut self) -> impl Iterator<Item = &'_ mut Cell> {
-        self.0.iter_mut()
+        self.cells.iter_mut()
+    }
+
+    pub fn house(&self, house: House) -> impl Iterator<Item = &'_ Cell> {
+        self.layout
+            .house_indices(house)
+            .iter()
+            .map(|&index| &self.cells[index])
+    }
+
+    pub fn house_mut(&mut self, house: House) -> impl Iterator<Item = &'_ mut Cell> {
+        let indices = self.layout.house_indices(house);
+
+        self.cells
+            .iter_mut()
+            .enumerate()
+            .filter_map(move |(index, cell)| {
+                if indices.contains(&index) {
+                    Some(cell)
+                } else {
+                    None
+                }
+            })
     }
 
     pub fn row(&self, index: usize) -> impl Iterator<Item = &'_ Cell> {
-        Self::assert_house_index(index);
-        self.0.iter().skip(index * HOUSE_SIZE).take(HOUSE_SIZE)
+        self.assert_house_index(index);
+        self.house(House::Row(index))
     }
 
     pub fn row_mut(&mut self, index: usize) -> impl Iterator<Item = &'_ mut Cell> {
-        Self::assert_house_index(index);
-        self.0.iter_mut().skip(index * HOUSE_SIZE).take(HOUSE_SIZE)
+        self.assert_house_index(index);
+        self.house_mut(House::Row(index))
     }
 
     pub fn rows(&self) -> Chunks<'_, Cell> {
-        self.0.chunks(HOUSE_SIZE)
+        self.cells.chunks(self.house_size())
     }
 
     pub fn col(&self, index: usize) -> impl Iterator<Item = &'_ Cell> {
-        Self::assert_house_index(index);
-
-        self.0
-            .iter()
-            .enumerate()
-            .filter_map(move |(cell_index, cell)| {
-                if Coord::from_index(cell_index).col() == index {
-                    Some(cell)
-                } else {
-                    None
-                }
-            })
+        self.assert_house_index(index);
+        self.house(House::Col(index))
     }
 
     pub fn col_mut(&mut self, index: usize) -> impl Iterator<Item = &'_ mut Cell> {
-        Self::assert_house_index(index);
-
-        self.0
-            .iter_mut()
-            .enumerate()
-            .filter_map(move |(cell_index, cell)| {
-                if Coord::from_index(cell_index).col() == index {
-                    Some(cell)
-                } else {
-                    None
-                }
-            })
+        self.assert_house_index(index);
+        self.house_mut(House::Col(index))
     }
 
     #[must_use]
     pub fn cols(&self) -> Vec<impl Iterator<Item = &'_ Cell>> {
-        DIGIT_INDICES.map(|i| self.col(i)).collect()
+        (0..self.house_size()).map(|i| self.col(i)).collect()
     }
 
     pub fn square<I: SudokuIndex>(&self, i: I) -> impl Iterator<Item = &'_ Cell> {
-        let index = i.into_index_of(SQUARE_SIZE);
-        Self::assert_house_index(index);
-
-        let square_indices = Self::square_indices(Coord::from_index_of(index, SQUARE_SIZE));
-
-        self.0.iter().enumerate().filter_map(move |(index, cell)| {
-            if square_indices.contains(&index) {
-                Some(cell)
-            } else {
-                None
-            }
-        })
+        let index = i.into_index_of(self.squares_per_band());
+        self.assert_house_index(index);
+        self.house(House::Square(index))
     }
 
     pub fn square_mut<I: SudokuIndex>(&mut self, i: I) -> impl Iterator<Item = &'_ mut Cell> {
-        let index = i.into_index_of(SQUARE_SIZE);
-        Self::assert_house_index(index);
-
-        let square_indices = Self::square_indices(Coord::from_index_of(index, SQUARE_SIZE));
-
-        self.0
-            .iter_mut()
-            .enumerate()
-            .filter_map(move |(index, cell)| {
-                if square_indices.contains(&index) {
-                    Some(cell)
-                } else {
-                    None
-                }
-            })
+        let index = i.into_index_of(self.squares_per_band());
+        self.assert_house_index(index);
+        self.house_mut(House::Square(index))
     }
 
     pub fn square_of_cell<I: SudokuIndex>(&self, i: I) -> impl Iterator<Item = &'_ Cell> {
-        let coord = Coord::from_index(i.into_index());
-        self.square(Self::cell_coord_to_square_coord(coord))
+        self.square(self.layout.square_of_cell(i))
     }
 
     pub fn square_mut_of_cell<I: SudokuIndex>(
         &mut self,
         i: I,
     ) -> impl Iterator<Item = &'_ mut Cell> {
-        let coord = Coord::from_index(i.into_index());
-        self.square_mut(Self::cell_coord_to_square_coord(coord))
+        self.square_mut(self.layout.square_of_cell(i))
     }
 
-    const fn cell_coord_to_square_coord(cell_coord: Coord) -> Coord {
-        Coord(
-            cell_coord.row() / SQUARE_SIZE,
-            cell_coord.col() / SQUARE_SIZE,
-        )
+    #[must_use]
+    pub fn square_indices<I: SudokuIndex>(&self, i: I) -> &[usize] {
+        let index = i.into_index_of(self.squares_per_band());
+        self.assert_house_index(index);
+        self.layout.house_indices(House::Square(index))
     }
 
-    pub const fn square_indices(Coord(row, col): Coord) -> [usize; HOUSE_SIZE] {
-        let square_row = row * HOUSE_SIZE * SQUARE_SIZE;
-        let square_col = col * SQUARE_SIZE;
-
-        let square_index = square_row + square_col;
-
-        [
-            square_index,
-            square_index + 1,
-            square_index + 2,
-            //
-            square_index + HOUSE_SIZE,
-            square_index + HOUSE_SIZE + 1,
-            square_index + HOUSE_SIZE + 2,
-            //
-            square_index + HOUSE_SIZE * 2,
-            square_index + HOUSE_SIZE * 2 + 1,
-            square_index + HOUSE_SIZE * 2 + 2,
-        ]
+    // The number of squares side by side in each band of rows. Jigsaw regions aren't arranged in
+    // bands, so squares are indexed by their number alone.
+    fn squares_per_band(&self) -> usize {
+        self.box_size()
+            .map_or_else(|| self.house_size(), |(box_height, _)| box_height)
     }
 
-    pub const fn square_indices_of_cell(Coord(row, col): Coord) -> [usize; HOUSE_SIZE] {
-        Self::square_indices(Coord(row / SQUARE_SIZE, col / SQUARE_SIZE))
+    #[must_use]
+    pub fn square_indices_of_cell<I: SudokuIndex>(&self, i: I) -> &[usize] {
+        self.square_indices(self.layout.square_of_cell(i))
     }
 
     pub fn cell_candidates<I: SudokuIndex>(&self, i: I) -> Candidates {
-        let index = i.into_index();
-        let mut candidates = Candidates::all();
+        let index = self.layout.index(i);
+        let mut candidates = self.layout.candidates();
 
-        let Coord(row, col) = Coord::from_index(index);
-
-        for cell in self
-            .row(row)
-            .chain(self.col(col))
-            .chain(self.square_of_cell(index))
-        {
-            if let Some(digit) = cell.digit {
+        for &peer in self.layout.peers(index) {
+            if let Some(digit) = self.cells[peer].digit {
                 candidates.remove(digit);
             }
         }
 
-        candidates
+        self.constraints()
+            .fold(candidates, |candidates, constraint| {
+                candidates.intersection(constraint.cell_candidates(self, index))
+            })
     }
 
     pub fn all_candidates(&self) -> Vec<Candidates> {
-        self.cells()
+        let mut all_candidates = self
+            .cells()
             .enumerate()
             .map(|(i, cell)| {
                 if cell.digit.is_some() {
@@ -315,7 +435,47 @@ impl Sudoku {
                     self.cell_candidates(i)
                 }
             })
-            .collect::<Vec<_>>()
+            .collect::<Vec<_>>();
+
+        for constraint in self.constraints() {
+            constraint.restrict_candidates(self, &mut all_candidates);
+        }
+
+        all_candidates
+    }
+
+    /// Like `all_candidates`, but narrowed down by any candidates already stored in the cells.
+    pub fn current_candidates(&self) -> Vec<Candidates> {
+        self.cells()
+            .zip(self.all_candidates())
+            .map(|(cell, candidates)| {
+                if cell.candidates.is_empty() {
+                    candidates
+                } else {
+                    candidates.intersection(cell.candidates)
+                }
+            })
+            .collect()
+    }
+
+    #[must_use]
+    pub fn rate(&self) -> Option<Rating> {
+        Rating::new(self)
+    }
+
+    #[must_use]
+    pub fn next_step(&self) -> Option<Step> {
+        LogicalSolver::new().next_step(self)
+    }
+
+    pub fn apply_step(&mut self, step: &Step) {
+        let mut candidates = self.current_candidates();
+
+        step.apply(self, &mut candidates);
+
+        self.cells_mut()
+            .zip(candidates)
+            .for_each(|(cell, candidates)| cell.candidates = candidates);
     }
 
     pub fn solve_all_candidates(&mut self) {
@@ -331,97 +491,274 @@ impl Sudoku {
         fn house_is_unique<'a>(house_iter: impl Iterator<Item = &'a Cell>) -> bool {
             let mut used = HashSet::new();
             house_iter
-                .filter(|cell| cell.digit.is_some())
-                .all(move |cell| used.insert(cell))
+                .filter_map(|cell| cell.digit)
+                .all(move |digit| used.insert(digit))
+        }
+
+        self.cells()
+            .filter_map(|cell| cell.digit)
+            .all(|digit| self.layout.is_valid_digit(digit))
+            && self
+                .layout
+                .houses()
+                .all(|house| house_is_unique(self.house(house)))
+            && self
+                .constraints()
+                .all(|constraint| constraint.is_valid(self))
+    }
+
+    // Writes each cage on its own line, such as `15: r1c1 r1c2 r2c1`
+    #[must_use]
+    pub fn to_str_cages(&self) -> String {
+        self.cages.iter().map(|cage| format!("{cage}\n")).collect()
+    }
+
+    // Adds a cage for each non-empty line, in the format of `to_str_cages`. Either every cage is
+    // added, or none of them are.
+    pub fn add_cages_from_str(&mut self, s: &str) -> Result<(), CageError> {
+        let previous = self.cages.len();
+
+        let result = s
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .try_for_each(|line| self.add_cage(line.parse()?));
+
+        if result.is_err() {
+            self.cages.truncate(previous);
         }
 
-        self.rows().all(|row| house_is_unique(row.iter()))
-            && self.cols().into_iter().all(house_is_unique)
-            && DIGIT_INDICES.map(|i| self.square(i)).all(house_is_unique)
+        result
     }
 
     #[inline]
     #[track_caller]
-    fn assert_house_index(index: usize) {
+    fn assert_house_index(&self, index: usize) {
+        let house_size = self.house_size();
+
         assert!(
-            index < HOUSE_SIZE,
+            index < house_size,
             "house index must be between 0 and {}, got {} instead",
-            HOUSE_SIZE - 1,
+            house_size - 1,
             index
         );
     }
 
     pub fn to_str_line(&self) -> String {
         self.cells()
-            .map(|cell| {
-                cell.digit
-                    .map_or('0', |d| char::from_digit((*d).into(), 10).unwrap())
-            })
+            .map(|cell| cell.digit.map_or('0', Digit::to_char))
             .collect::<String>()
     }
 
-    pub fn from_str_line(s: &str) -> Self {
-        Self::try_from(
-            s.chars()
-                .enumerate()
-                .map(|(i, c)| Cell {
-                    coord: Coord::from_index(i),
-                    digit: if c == '0' {
-                        None
-                    } else {
-                        Some(Digit::new(c.to_digit(10).unwrap().try_into().unwrap()).unwrap())
-                    },
-                    candidates: Candidates::empty(),
-                    is_given: c != '0',
-                })
-                .collect::<Vec<_>>(),
-        )
-        .unwrap()
+    pub fn to_str_grid(&self) -> String {
+        let mut grid = String::new();
+
+        for (row_index, row) in self.rows().enumerate() {
+            if let Some((box_height, box_width)) = self.box_size() {
+                if row_index > 0 && row_index % box_height == 0 {
+                    let border = vec!["-".repeat(box_width * 2 - 1); box_height];
+                    grid.push_str(&border.join("-+-"));
+                    grid.push('\n');
+                }
+            }
+
+            for (cell_index, cell) in row.iter().enumerate() {
+                if cell_index > 0 {
+                    grid.push_str(
+                        if self.is_wall(cell.coord, cell.coord.with_col(cell_index - 1)) {
+                            " | "
+                        } else {
+                            " "
+                        },
+                    );
+                }
+
+                grid.push(cell.digit.map_or('.', Digit::to_char));
+            }
+
+            grid.push('\n');
+        }
+
+        grid
+    }
+
+    // Whether two cells are in different squares, so a wall should be drawn between them
+    fn is_wall(&self, a: Coord, b: Coord) -> bool {
+        self.layout.square_of_cell(a) != self.layout.square_of_cell(b)
+    }
+
+    pub fn from_str_line(s: &str) -> Result<Self, ParseError> {
+        s.parse()
+    }
+
+    pub fn from_str_with_layout(s: &str, layout: Layout) -> Result<Self, ParseError> {
+        let cell_chars = Self::cell_chars(s).collect::<Vec<_>>();
+
+        if cell_chars.len() != layout.grid_size() {
+            return Err(ParseError::InvalidLength {
+                expected: layout.grid_size(),
+                found: cell_chars.len(),
+            });
+        }
+
+        let mut sudoku = Self::new_empty_with_layout(layout);
+        let house_size = sudoku.house_size();
+
+        for (cell, (index, char)) in sudoku.cells.iter_mut().zip(cell_chars) {
+            let digit = match char {
+                '0' | '.' | '_' => None,
+                _ => Some(
+                    Digit::from_char(char, house_size)
+                        .ok_or(ParseError::InvalidChar { char, index })?,
+                ),
+            };
+
+            cell.digit = digit;
+            cell.is_given = digit.is_some();
+        }
+
+        if let Some((coord, digit)) = sudoku.find_duplicate_given() {
+            return Err(ParseError::DuplicateDigit { digit, coord });
+        }
+
+        Ok(sudoku)
+    }
+
+    // Lines containing `-` or `=` are treated as box borders, and `|`, `:`, `+` and whitespace
+    // are ignored, so the output of `to_str_grid` and `Display` can be parsed back. Yields each
+    // remaining char along with its index in `s`.
+    fn cell_chars(s: &str) -> impl Iterator<Item = (usize, char)> + '_ {
+        s.split_inclusive('\n')
+            .scan(0, |offset, line| {
+                let line_offset = *offset;
+                *offset += line.chars().count();
+
+                let is_border = line.contains(['-', '=']);
+
+                Some(
+                    line.chars()
+                        .enumerate()
+                        .filter(move |&(_, char)| {
+                            !is_border && !char.is_whitespace() && !matches!(char, '|' | ':' | '+')
+                        })
+                        .map(move |(i, char)| (line_offset + i, char)),
+                )
+            })
+            .flatten()
+    }
+
+    fn find_duplicate_given(&self) -> Option<(Coord, Digit)> {
+        self.cells().enumerate().find_map(|(index, cell)| {
+            let digit = cell.digit?;
+
+            let is_duplicate = self
+                .layout
+                .peers(index)
+                .iter()
+                .take_while(|&&i| i < index)
+                .any(|&i| self.cells[i].digit == Some(digit));
+
+            is_duplicate.then_some((self.layout.coord(index), digit))
+        })
+    }
+}
+
+impl FromStr for Sudoku {
+    type Err = ParseError;
+
+    // Accepts both a single line of cells and multi-line grids, of any supported size. The layout
+    // is inferred from the number of cells, so grids with rectangular boxes are assumed to have
+    // boxes wider than they are tall. Use `from_str_with_layout` for any other layout.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let found = Self::cell_chars(s).count();
+
+        let layout = Layout::from_grid_size(found).ok_or_else(|| {
+            let expected = Layout::grid_sizes()
+                .min_by_key(|grid_size| grid_size.abs_diff(found))
+                .unwrap_or(GRID_SIZE);
+
+            ParseError::InvalidLength { expected, found }
+        })?;
+
+        Self::from_str_with_layout(s, layout)
     }
 }
 
 impl From<[Cell; GRID_SIZE]> for Sudoku {
     fn from(value: [Cell; GRID_SIZE]) -> Self {
-        Self(value)
+        Self {
+            layout: Layout::classic(),
+            cells: value.into(),
+            cages: vec![],
+            constraints: vec![],
+        }
     }
 }
 
+// The layout is inferred from the number of cells
 impl TryFrom<Vec<Cell>> for Sudoku {
     type Error = Vec<Cell>;
 
     fn try_from(value: Vec<Cell>) -> Result<Self, Self::Error> {
-        Ok(Self(value.try_into()?))
+        match Layout::from_grid_size(value.len()) {
+            Some(layout) => Self::from_cells_with_layout(value, layout),
+            None => Err(value),
+        }
     }
 }
 
-impl Default for Sudoku {
-    fn default() -> Self {
-        let mut cells = [Cell::default(); GRID_SIZE];
+impl PartialEq for Sudoku {
+    fn eq(&self, other: &Self) -> bool {
+        self.layout == other.layout
+            && self.cells == other.cells
+            && self.cages == other.cages
+            && self.constraints == other.constraints
+    }
+}
 
-        for (index, cell) in cells.iter_mut().enumerate() {
-            cell.coord = Coord::from_index(index);
-        }
+impl Eq for Sudoku {}
 
-        Self(cells)
+impl Default for Sudoku {
+    fn default() -> Self {
+        Self::new_empty_with_layout(Layout::classic())
     }
 }
 
+// Draws the walls between squares, so jigsaw regions are drawn as well as boxes
 impl Display for Sudoku {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         const PIPE: char = '|';
 
-        f.write_str(".-----.-----.-----.\n")?;
+        let house_size = self.house_size();
+        let last = house_size - 1;
 
-        for (row_index, row) in self.rows().enumerate() {
-            f.write_char(PIPE)?;
+        // The top and bottom borders only break where a wall meets them
+        let edge = |f: &mut std::fmt::Formatter<'_>, row: usize, corner: char| {
+            f.write_char(corner)?;
 
-            for (cell_index, cell) in row.iter().enumerate() {
-                match &cell.digit {
-                    Some(digit) => write!(f, "{}", *digit)?,
-                    None => f.write_char('.')?,
+            for col in 0..house_size {
+                f.write_char('-')?;
+
+                if col == last || self.is_wall(Coord(row, col), Coord(row, col + 1)) {
+                    f.write_char(corner)?;
+                } else {
+                    f.write_char('-')?;
                 }
+            }
+
+            Ok(())
+        };
+
+        edge(f, 0, '.')?;
+        f.write_char('\n')?;
+
+        for row in 0..house_size {
+            f.write_char(PIPE)?;
+
+            for col in 0..house_size {
+                let cell = self.cell(Coord(row, col)).unwrap();
+                f.write_char(cell.digit.map_or('.', Digit::to_char))?;
 
-                if (cell_index + 1) % SQUARE_SIZE == 0 {
+                if col == last || self.is_wall(Coord(row, col), Coord(row, col + 1)) {
                     f.write_char(PIPE)?;
                 } else {
                     f.write_char(' ')?;
@@ -430,14 +767,36 @@ impl Display for Sudoku {
 
             f.write_char('\n')?;
 
-            if row_index < HOUSE_SIZE - 1 && (row_index + 1) % SQUARE_SIZE == 0 {
-                f.write_str(":----- ----- -----:\n")?;
+            if row == last {
+                break;
             }
-        }
 
-        f.write_str("'-----'-----'-----'")?;
+            let below = |col| self.is_wall(Coord(row, col), Coord(row + 1, col));
 
-        Ok(())
+            if (0..house_size).any(below) {
+                let mut line = String::from(":");
+
+                for col in 0..house_size {
+                    line.push(if below(col) { '-' } else { ' ' });
+
+                    if col == last {
+                        line.push(':');
+                    } else if below(col)
+                        && below(col + 1)
+                        && !self.is_wall(Coord(row, col), Coord(row, col + 1))
+                        && !self.is_wall(Coord(row + 1, col), Coord(row + 1, col + 1))
+                    {
+                        line.push('-');
+                    } else {
+                        line.push(' ');
+                    }
+                }
+
+                writeln!(f, "{line}")?;
+            }
+        }
+
+        edge(f, last, '\'')
     }
 }
 
@@ -445,8 +804,13 @@ impl Display for Sudoku {
 pub enum ParseError {
     #[error("invalid character `{char}` at index {index}")]
     InvalidChar { char: char, index: usize },
+    #[error("expected {expected} cells, found {found}")]
+    InvalidLength { expected: usize, found: usize },
+    #[error("digit {digit} at row {}, column {} is already in its row, column or box", .coord.row() + 1, .coord.col() + 1)]
+    DuplicateDigit { digit: Digit, coord: Coord },
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct Cell {
     pub coord: Coord,
@@ -461,12 +825,30 @@ pub struct Digit(pub u8);
 impl Digit {
     #[must_use]
     #[inline]
+    #[deprecated(
+        note = "only accepts the digits of 9x9 grids, use `Layout::is_valid_digit` instead"
+    )]
     pub fn is_valid(value: u8) -> bool {
         DIGITS.contains(&value)
     }
 
+    #[must_use]
+    #[inline]
+    pub fn is_valid_of(value: u8, size: usize) -> bool {
+        (1..=size).contains(&value.into())
+    }
+
+    #[deprecated(note = "only accepts the digits of 9x9 grids, use `Layout::digit` instead")]
     pub fn new(value: u8) -> SudokuResult<Self> {
-        Self::try_from(value)
+        Self::new_of(value, HOUSE_SIZE)
+    }
+
+    pub fn new_of(value: u8, size: usize) -> SudokuResult<Self> {
+        if Self::is_valid_of(value, size) {
+            Ok(Self(value))
+        } else {
+            Err(SudokuError::InvalidDigit { digit: value, size })
+        }
     }
 
     #[must_use]
@@ -474,127 +856,156 @@ impl Digit {
     pub const fn new_unchecked(value: u8) -> Self {
         Self(value)
     }
-}
 
-impl TryFrom<u8> for Digit {
-    type Error = SudokuError;
+    // Digits past 9 are written as letters, so that every cell takes a single char: 10 is `A`,
+    // 16 is `G` and 25 is `P`
+    #[must_use]
+    pub fn to_char(self) -> char {
+        char::from_digit(self.0.into(), 36).map_or('?', |char| char.to_ascii_uppercase())
+    }
 
-    fn try_from(value: u8) -> Result<Self, Self::Error> {
-        if Self::is_valid(value) {
-            Ok(Self(value))
-        } else {
-            Err(SudokuError::InvalidDigit(value))
-        }
+    #[must_use]
+    pub fn from_char(char: char, size: usize) -> Option<Self> {
+        Self::new_of(char.to_digit(36)?.try_into().ok()?, size).ok()
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-pub struct Candidates(CandidatesInner);
+impl From<Digit> for u8 {
+    fn from(value: Digit) -> Self {
+        value.0
+    }
+}
+
+// Supports every digit up to `MAX_HOUSE_SIZE`, one bit per digit
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Candidates(u32);
 
 impl Candidates {
     #[must_use]
     #[inline]
     pub const fn empty() -> Self {
-        Self(CandidatesInner::empty())
+        Self(0)
     }
 
     #[must_use]
     #[inline]
+    #[deprecated(note = "only holds the digits of 9x9 grids, use `Layout::candidates` instead")]
     pub const fn all() -> Self {
-        Self(CandidatesInner::ALL)
+        Self::all_of(HOUSE_SIZE)
     }
 
-    pub fn add(&mut self, digit: Digit) {
-        self.0.insert(digit.into());
+    #[must_use]
+    #[inline]
+    pub const fn all_of(size: usize) -> Self {
+        debug_assert!(size <= MAX_HOUSE_SIZE);
+        Self((1 << size) - 1)
     }
 
-    pub fn remove(&mut self, digit: Digit) {
-        self.0.remove(digit.into());
+    pub const fn add(&mut self, digit: Digit) {
+        self.0 |= Self::bit(digit);
     }
 
-    pub fn toggle(&mut self, digit: Digit) {
-        self.0.toggle(digit.into());
+    pub const fn remove(&mut self, digit: Digit) {
+        self.0 &= !Self::bit(digit);
+    }
+
+    pub const fn toggle(&mut self, digit: Digit) {
+        self.0 ^= Self::bit(digit);
     }
 
     #[must_use]
-    pub fn contains(&self, digit: Digit) -> bool {
-        self.0.contains(digit.into())
+    pub const fn contains(&self, digit: Digit) -> bool {
+        self.0 & Self::bit(digit) != 0
     }
 
-    // PERF: inefficient
-    pub fn digits(&self) -> impl Iterator<Item = Digit> + '_ {
-        DIGITS
-            .map(Digit::new_unchecked)
-            .filter(|digit| self.contains(*digit))
+    pub fn digits(&self) -> impl Iterator<Item = Digit> {
+        let mut bits = self.0;
+
+        std::iter::from_fn(move || {
+            if bits == 0 {
+                return None;
+            }
+
+            #[allow(clippy::cast_possible_truncation)]
+            let digit = Digit::new_unchecked(bits.trailing_zeros() as u8 + 1);
+            bits &= bits - 1;
+
+            Some(digit)
+        })
     }
 
-    // PERF: inefficient
-    pub fn count(&self) -> usize {
-        self.digits().count()
+    #[must_use]
+    #[inline]
+    pub const fn count(&self) -> usize {
+        self.0.count_ones() as usize
     }
-}
 
-impl Default for Candidates {
-    fn default() -> Self {
-        Self(CandidatesInner::empty())
+    #[must_use]
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
     }
-}
 
-impl From<Digit> for Candidates {
-    fn from(value: Digit) -> Self {
-        let mut candidates = Self::empty();
-        candidates.add(value);
-        candidates
+    #[must_use]
+    pub fn first(&self) -> Option<Digit> {
+        self.digits().next()
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
     }
-}
 
-bitflags! {
-    struct CandidatesInner: usize {
-        const _1 = 0b0000_0000_0000_0001;
-        const _2 = 0b0000_0000_0000_0010;
-        const _3 = 0b0000_0000_0000_0100;
-        const _4 = 0b0000_0000_0000_1000;
-        const _5 = 0b0000_0000_0001_0000;
-        const _6 = 0b0000_0000_0010_0000;
-        const _7 = 0b0000_0000_0100_0000;
-        const _8 = 0b0000_0000_1000_0000;
-        const _9 = 0b0000_0001_0000_0000;
-        const ALL = Self::_1.bits | Self::_2.bits | Self::_3.bits | Self::_4.bits
-                    | Self::_5.bits | Self::_6.bits | Self::_7.bits | Self::_8.bits | Self::_9.bits;
+    #[must_use]
+    #[inline]
+    pub const fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn difference(self, other: Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+
+    #[inline]
+    const fn bit(digit: Digit) -> u32 {
+        1 << (digit.0 - 1)
     }
 }
 
-impl From<Digit> for CandidatesInner {
+impl From<Digit> for Candidates {
     fn from(value: Digit) -> Self {
-        match value.0 {
-            1 => Self::_1,
-            2 => Self::_2,
-            3 => Self::_3,
-            4 => Self::_4,
-            5 => Self::_5,
-            6 => Self::_6,
-            7 => Self::_7,
-            8 => Self::_8,
-            9 => Self::_9,
-            _ => unreachable!(),
-        }
+        let mut candidates = Self::empty();
+        candidates.add(value);
+        candidates
     }
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
 pub struct Coord(pub usize, pub usize);
 
 impl Coord {
     #[inline]
     #[must_use]
+    #[deprecated(note = "only picks cells of 9x9 grids, use `Layout::random_coord` instead")]
     pub fn random(rng: &mut impl Rng) -> Self {
-        let random_row = rng.gen_range(DIGIT_INDICES);
-        let random_col = rng.gen_range(DIGIT_INDICES);
+        Self::random_of(rng, HOUSE_SIZE)
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn random_of(rng: &mut impl Rng, size: usize) -> Self {
+        let random_row = rng.gen_range(0..size);
+        let random_col = rng.gen_range(0..size);
         Self(random_row, random_col)
     }
 
     #[inline]
     #[must_use]
+    #[deprecated(note = "only numbers the cells of 9x9 grids, use `Layout::coord` instead")]
     pub const fn from_index(index: usize) -> Self {
         Self::from_index_of(index, HOUSE_SIZE)
     }
@@ -636,6 +1047,7 @@ where
 {
     fn into_index_of(self, size: usize) -> usize;
 
+    #[deprecated(note = "only numbers the cells of 9x9 grids, use `Layout::index` instead")]
     fn into_index(self) -> usize {
         self.into_index_of(HOUSE_SIZE)
     }
@@ -650,7 +1062,6 @@ impl SudokuIndex for usize {
 
 impl SudokuIndex for Coord {
     #[inline]
-    #[must_use]
     fn into_index_of(self, size: usize) -> usize {
         (self.row() * size) + self.col()
     }
@@ -660,6 +1071,269 @@ pub type SudokuResult<T> = Result<T, SudokuError>;
 
 #[derive(Error, Debug)]
 pub enum SudokuError {
-    #[error("digit must be between 1 and 9, got {0}")]
-    InvalidDigit(u8),
+    #[error("digit must be between 1 and {size}, got {digit}")]
+    InvalidDigit { digit: u8, size: usize },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{generate_filled_with_layout, Jigsaw};
+
+    const PUZZLE: &str =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+
+    #[test]
+    fn parses_line() {
+        let sudoku = PUZZLE.parse::<Sudoku>().unwrap();
+        let cell = sudoku.cell(Coord(0, 1)).unwrap();
+
+        assert_eq!(cell.digit, Some(Digit(3)));
+        assert!(cell.is_given);
+        assert_eq!(sudoku.cell(Coord(0, 2)).unwrap().digit, None);
+        assert_eq!(sudoku.to_str_line(), PUZZLE);
+        assert_eq!(sudoku.to_str_line().parse::<Sudoku>().unwrap(), sudoku);
+    }
+
+    #[test]
+    fn parses_empty_cell_chars() {
+        let sudoku = PUZZLE.parse::<Sudoku>().unwrap();
+
+        for empty in ['.', '_'] {
+            let line = PUZZLE.replace('0', &empty.to_string());
+            assert_eq!(line.parse::<Sudoku>().unwrap(), sudoku);
+        }
+    }
+
+    #[test]
+    fn infers_layout_from_length() {
+        let sudoku = "1200340000000000".parse::<Sudoku>().unwrap();
+        assert_eq!(sudoku.house_size(), 4);
+        assert_eq!(sudoku.box_size(), Some((2, 2)));
+
+        let sudoku = "0".repeat(36).parse::<Sudoku>().unwrap();
+        assert_eq!(sudoku.box_size(), Some((2, 3)));
+    }
+
+    #[test]
+    fn reports_invalid_char() {
+        let line = format!("53x{}", &PUZZLE[3..]);
+
+        assert!(matches!(
+            line.parse::<Sudoku>(),
+            Err(ParseError::InvalidChar {
+                char: 'x',
+                index: 2
+            })
+        ));
+
+        // Digits beyond the house size are invalid too
+        assert!(matches!(
+            "5000000000000000".parse::<Sudoku>(),
+            Err(ParseError::InvalidChar {
+                char: '5',
+                index: 0
+            })
+        ));
+    }
+
+    #[test]
+    fn reports_invalid_length() {
+        assert!(matches!(
+            PUZZLE[..80].parse::<Sudoku>(),
+            Err(ParseError::InvalidLength {
+                expected: 81,
+                found: 80
+            })
+        ));
+    }
+
+    #[test]
+    fn reports_duplicate_digit() {
+        let line = format!("55{}", &PUZZLE[2..]);
+
+        assert!(matches!(
+            line.parse::<Sudoku>(),
+            Err(ParseError::DuplicateDigit {
+                digit: Digit(5),
+                coord: Coord(0, 1)
+            })
+        ));
+    }
+
+    #[test]
+    fn parses_display_back() {
+        let sudoku = PUZZLE.parse::<Sudoku>().unwrap();
+
+        assert_eq!(sudoku.to_string().parse::<Sudoku>().unwrap(), sudoku);
+        assert_eq!(sudoku.to_str_grid().parse::<Sudoku>().unwrap(), sudoku);
+    }
+
+    #[test]
+    fn parses_pasted_grid() {
+        let grid = "
+            +-------+-------+-------+
+            | 5 3 . | . 7 . | . . . |
+            | 6 . . | 1 9 5 | . . . |
+            | . 9 8 | . . . | . 6 . |
+            +-------+-------+-------+
+            | 8 . . | . 6 . | . . 3 |
+            | 4 . . | 8 . 3 | . . 1 |
+            | 7 . . | . 2 . | . . 6 |
+            +=======+=======+=======+
+            | . 6 . | . . . | 2 8 . |
+            | . . . | 4 1 9 | . . 5 |
+            | . . . | . 8 . | . 7 9 |
+            +-------+-------+-------+
+        ";
+
+        assert_eq!(grid.parse::<Sudoku>().unwrap(), PUZZLE.parse().unwrap());
+    }
+
+    #[test]
+    fn parses_display_back_with_layout() {
+        let mut rng = SudokuConfig::new(0).with_seed(0).rng();
+        let layout = Jigsaw::random_layout(HOUSE_SIZE, &mut rng);
+        let sudoku = generate_filled_with_layout(layout.clone(), &mut rng).unwrap();
+
+        let parsed = Sudoku::from_str_with_layout(&sudoku.to_string(), layout).unwrap();
+        assert_eq!(parsed.to_str_line(), sudoku.to_str_line());
+
+        let mut sudoku = Sudoku::new_empty_with_layout(Layout::new(4));
+        sudoku.set_cell(Coord(0, 0), Digit(16));
+        sudoku.set_cell(Coord(15, 15), Digit(10));
+
+        let parsed = sudoku.to_string().parse::<Sudoku>().unwrap();
+        assert_eq!(parsed.to_str_line(), sudoku.to_str_line());
+    }
+
+    #[test]
+    fn reports_index_within_grid() {
+        let grid = "1 2 | . .\n. . | x .\n----+----\n. . | . .\n. . | . .\n";
+
+        assert!(matches!(
+            grid.parse::<Sudoku>(),
+            Err(ParseError::InvalidChar {
+                char: 'x',
+                index: 16
+            })
+        ));
+    }
+
+    #[test]
+    fn writes_and_adds_cages_back() {
+        let mut sudoku = Sudoku::new_empty();
+        sudoku
+            .add_cage(Cage::new(3, [Coord(0, 0), Coord(0, 1)]))
+            .unwrap();
+        sudoku
+            .add_cage(Cage::new(24, [Coord(8, 8), Coord(8, 7), Coord(7, 8)]))
+            .unwrap();
+
+        let cages = sudoku.to_str_cages();
+        assert_eq!(cages, "3: r1c1 r1c2\n24: r8c9 r9c8 r9c9\n");
+
+        let mut parsed = Sudoku::new_empty();
+        parsed.add_cages_from_str(&cages).unwrap();
+        assert_eq!(parsed, sudoku);
+    }
+
+    #[test]
+    fn rejects_invalid_cages() {
+        let mut sudoku = Sudoku::new_empty();
+        sudoku
+            .add_cage(Cage::new(3, [Coord(0, 0), Coord(0, 1)]))
+            .unwrap();
+
+        let cage = |sum, cells: &[Coord]| Cage::new(sum, cells.iter().copied());
+
+        assert!(matches!(
+            sudoku.add_cage(cage(3, &[])),
+            Err(CageError::Empty)
+        ));
+        assert!(matches!(
+            sudoku.add_cage(Cage::new(45, (0..10).map(|col| Coord(1, col)))),
+            Err(CageError::TooLarge { max: 9, found: 10 })
+        ));
+        assert!(matches!(
+            sudoku.add_cage(cage(10, &[Coord(8, 9)])),
+            Err(CageError::InvalidCell { coord: Coord(8, 9) })
+        ));
+        assert!(matches!(
+            sudoku.add_cage(cage(10, &[Coord(0, 1), Coord(0, 2)])),
+            Err(CageError::OverlappingCell { coord: Coord(0, 1) })
+        ));
+        assert!(matches!(
+            sudoku.add_cage(cage(2, &[Coord(1, 0), Coord(1, 1)])),
+            Err(CageError::ImpossibleSum { sum: 2, cells: 2 })
+        ));
+        assert_eq!(sudoku.cages().len(), 1);
+    }
+
+    #[test]
+    fn adds_every_cage_or_none() {
+        let mut sudoku = Sudoku::new_empty();
+
+        let result = sudoku.add_cages_from_str("3: r1c1 r1c2\n10: r2c1 r2c2\n4: r1c2 r1c3\n");
+
+        assert!(matches!(result, Err(CageError::OverlappingCell { .. })));
+        assert!(sudoku.cages().is_empty());
+
+        let result = sudoku.add_cages_from_str("3: r1c1 r1c2\n10 r2c1 r2c2\n");
+
+        assert!(matches!(result, Err(CageError::InvalidFormat { .. })));
+        assert!(sudoku.cages().is_empty());
+    }
+
+    #[test]
+    fn steps_through_to_the_solution() {
+        let mut sudoku = PUZZLE.parse::<Sudoku>().unwrap();
+        let solution = sudoku.solutions().next().unwrap();
+
+        while let Some(step) = sudoku.next_step() {
+            for &(coord, digit) in &step.placements {
+                assert_eq!(solution.cell(coord).unwrap().digit, Some(digit));
+            }
+
+            sudoku.apply_step(&step);
+
+            for &(coord, digit) in &step.placements {
+                assert_eq!(sudoku.cell(coord).unwrap().digit, Some(digit));
+            }
+        }
+
+        assert_eq!(sudoku.to_str_line(), solution.to_str_line());
+        assert!(sudoku.next_step().is_none());
+    }
+
+    #[test]
+    fn keeps_eliminations_in_cell_candidates() {
+        // Needs a pointing pair early on
+        let mut sudoku =
+            "400000201800201000030500000000800000070003060002040890640090002000000400000000700"
+                .parse::<Sudoku>()
+                .unwrap();
+
+        let step = std::iter::from_fn(|| {
+            let step = sudoku.next_step()?;
+            sudoku.apply_step(&step);
+            Some(step)
+        })
+        .find(|step| !step.eliminations.is_empty())
+        .unwrap();
+
+        for &(coord, digit) in &step.eliminations {
+            let candidates = sudoku.cell(coord).unwrap().candidates;
+            assert!(!candidates.is_empty() && !candidates.contains(digit));
+        }
+
+        // The next step builds on the eliminations rather than finding them again
+        assert_ne!(sudoku.next_step(), Some(step));
+    }
+
+    #[test]
+    fn finds_no_step_in_broken_grids() {
+        let sudoku = ".2341...........".parse::<Sudoku>().unwrap();
+        assert!(sudoku.next_step().is_none());
+    }
 }